@@ -0,0 +1,124 @@
+//! Compares the scanner's old `RwLock<HashMap<String, MarketState>>` + full clone against
+//! `MarketStore` at a few hundred symbols.
+//!
+//! Run with `cargo bench --bench market_store`.
+
+use scanner_rust::ingestors::market_store::{MarketState, MarketStore};
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+const EXCHANGES: [&str; 3] = ["binance", "hyperliquid", "bybit"];
+const SYMBOL_COUNTS: [usize; 3] = [100, 300, 500];
+const ROUNDS: usize = 200;
+
+fn book(seed: usize) -> MarketState {
+    let mid = 100.0 + seed as f64;
+    MarketState {
+        bids: (0..20).map(|i| (mid - i as f64 * 0.01, 1.0 + i as f64)).collect(),
+        asks: (0..20).map(|i| (mid + i as f64 * 0.01, 1.0 + i as f64)).collect(),
+        funding: 0.0001,
+    }
+}
+
+fn symbols(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("SYM{}", i)).collect()
+}
+
+/// One evaluation pass over every pair, the way the render tick used to do it.
+fn legacy_pass(map: &RwLock<HashMap<String, MarketState>>, syms: &[String]) -> f64 {
+    let snap = map.blocking_read().clone();
+    let mut acc = 0.0;
+    for sym in syms {
+        for ex in EXCHANGES {
+            if let Some(state) = snap.get(&format!("{}_{}", ex, sym)) {
+                acc += state.bids[0].0;
+            }
+        }
+    }
+    acc
+}
+
+fn store_pass(store: &MarketStore, sym_ids: &[usize]) -> f64 {
+    let mut acc = 0.0;
+    for &sym in sym_ids {
+        for ex in 0..store.exchange_count() {
+            if let Some(state) = store.snapshot(ex, sym) {
+                acc += state.bids[0].0;
+            }
+        }
+    }
+    acc
+}
+
+/// Runs `pass` ROUNDS times while a writer thread keeps publishing, returning mean time per pass.
+fn contended<W, P>(writer: W, mut pass: P) -> Duration
+where
+    W: FnMut(usize) + Send + 'static,
+    P: FnMut() -> f64,
+{
+    let stop = Arc::new(AtomicBool::new(false));
+    let stop_w = Arc::clone(&stop);
+    let mut writer = writer;
+    let handle = thread::spawn(move || {
+        let mut i = 0;
+        while !stop_w.load(Ordering::Relaxed) {
+            writer(i);
+            i += 1;
+        }
+    });
+
+    let start = Instant::now();
+    for _ in 0..ROUNDS { black_box(pass()); }
+    let elapsed = start.elapsed();
+
+    stop.store(true, Ordering::Relaxed);
+    handle.join().unwrap();
+    elapsed / ROUNDS as u32
+}
+
+fn main() {
+    println!("{:>8} | {:>14} | {:>14} | {:>8}", "SYMBOLS", "LEGACY/PASS", "STORE/PASS", "SPEEDUP");
+    for n in SYMBOL_COUNTS {
+        let syms = symbols(n);
+
+        let legacy = Arc::new(RwLock::new(HashMap::new()));
+        for (i, sym) in syms.iter().enumerate() {
+            for ex in EXCHANGES {
+                legacy.blocking_write().insert(format!("{}_{}", ex, sym), book(i));
+            }
+        }
+        let legacy_w = Arc::clone(&legacy);
+        let syms_w = syms.clone();
+        let legacy_time = contended(
+            move |i| {
+                let sym = &syms_w[i % syms_w.len()];
+                legacy_w.blocking_write().insert(format!("{}_{}", EXCHANGES[i % 3], sym), book(i));
+            },
+            || legacy_pass(&legacy, &syms),
+        );
+
+        let store = Arc::new(MarketStore::new(&EXCHANGES, n));
+        let sym_ids: Vec<usize> = syms.iter().map(|s| store.intern_symbol(s).unwrap()).collect();
+        for (i, &sym) in sym_ids.iter().enumerate() {
+            for ex in 0..store.exchange_count() {
+                store.publish(ex, sym, book(i));
+            }
+        }
+        let store_w = Arc::clone(&store);
+        let store_time = contended(
+            move |i| store_w.publish(i % 3, i % n, book(i)),
+            || store_pass(&store, &sym_ids),
+        );
+
+        println!(
+            "{:>8} | {:>14?} | {:>14?} | {:>7.1}x",
+            n, legacy_time, store_time,
+            legacy_time.as_secs_f64() / store_time.as_secs_f64().max(f64::EPSILON)
+        );
+    }
+}
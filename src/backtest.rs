@@ -0,0 +1,8 @@
+#[path = "backtest/recorder.rs"]
+pub mod recorder;
+
+#[path = "backtest/engine.rs"]
+pub mod engine;
+
+#[path = "backtest/sweep.rs"]
+pub mod sweep;
@@ -0,0 +1,348 @@
+use super::recorder::Recording;
+use crate::execution::exchange::ExchangeClient;
+use crate::execution::execution_config::ExecutionConfig;
+use crate::execution::executor::{Clients, ExecutedFill, Executor};
+use crate::execution::paper::{fold_fill, signed, FundingPayment, PaperConfig, PaperExchange};
+use crate::ingestors::fees::FeeModel;
+use crate::ingestors::market_store::{parse_market_payload, MarketStore};
+use crate::ingestors::signals::{SignalKind, TradeSignal};
+use crate::ingestors::strategy::{evaluate_update, StrategyState};
+use crate::ingestors::strategy_config::StrategyConfig;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+const MAX_SYMBOLS: usize = 512;
+/// Funding is checked on this replay-time cadence; payouts are hourly at the finest.
+const FUNDING_CHECK_MS: i64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct BacktestConfig {
+    /// Delay between a signal being emitted and its orders reaching the venue.
+    pub latency_ms: i64,
+    pub paper: PaperConfig,
+    /// Executor settings, loaded once by the caller so a run never depends on the environment.
+    pub execution: ExecutionConfig,
+    /// Replay-time spacing of equity curve points.
+    pub equity_sample_ms: i64,
+    /// Print every order and fill as it happens.
+    pub verbose: bool,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self { latency_ms: 50, paper: PaperConfig::default(), execution: ExecutionConfig::default(), equity_sample_ms: 60_000, verbose: true }
+    }
+}
+
+/// One round trip of one pair, from its OPEN_LIMIT to the fills of its CLOSE_LIMIT.
+#[derive(Debug, Clone, Serialize)]
+pub struct TradeRecord {
+    pub pair_id: String,
+    pub entry_reason: String,
+    pub exit_reason: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub expected_edge: f64,
+    pub notional: f64,
+    /// Price PnL of both legs; includes unrealised PnL if still open at the end.
+    pub basis_pnl: f64,
+    pub funding: f64,
+    pub fees: f64,
+    pub net: f64,
+    #[serde(skip)]
+    legs: HashMap<(String, String), (f64, f64)>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct EquityPoint {
+    pub ts: DateTime<Utc>,
+    pub equity: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BacktestStats {
+    pub trades: usize,
+    pub closed: usize,
+    /// Share of closed trades with positive net PnL.
+    pub hit_rate: f64,
+    pub avg_hold_secs: f64,
+    pub basis_pnl: f64,
+    pub funding_pnl: f64,
+    pub fees: f64,
+    pub net_pnl: f64,
+    pub starting_equity: f64,
+    pub final_equity: f64,
+    /// Largest peak-to-trough fall of the equity curve, in USDT and as a share of the peak.
+    pub max_drawdown: f64,
+    pub max_drawdown_pct: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub stats: BacktestStats,
+    pub trades: Vec<TradeRecord>,
+    pub equity: Vec<EquityPoint>,
+}
+
+fn total_equity(exchanges: &[Arc<PaperExchange>]) -> f64 {
+    exchanges.iter().map(|e| e.summary().equity).sum()
+}
+
+/// Trades plus the routing from signal ID (open or close) to the trade it belongs to.
+#[derive(Default)]
+struct TradeLog {
+    trades: Vec<TradeRecord>,
+    by_signal: HashMap<Uuid, usize>,
+}
+
+impl TradeLog {
+    fn on_signal(&mut self, signal: &TradeSignal, now: DateTime<Utc>) {
+        match signal.kind {
+            SignalKind::OpenLimit => {
+                let notional = signal.legs.iter()
+                    .map(|leg| leg.size * leg.limit_price.unwrap_or(0.0))
+                    .fold(0.0, f64::max);
+                self.by_signal.insert(signal.id, self.trades.len());
+                self.trades.push(TradeRecord {
+                    pair_id: signal.pair_id.clone(),
+                    entry_reason: signal.reason.clone(),
+                    exit_reason: None,
+                    opened_at: now,
+                    closed_at: None,
+                    expected_edge: signal.expected_edge,
+                    notional,
+                    basis_pnl: 0.0,
+                    funding: 0.0,
+                    fees: 0.0,
+                    net: 0.0,
+                    legs: HashMap::new(),
+                });
+            }
+            SignalKind::CloseLimit => {
+                let Some(&idx) = signal.opens.and_then(|open| self.by_signal.get(&open)) else { return };
+                self.by_signal.insert(signal.id, idx);
+                self.trades[idx].exit_reason = Some(signal.reason.clone());
+                self.trades[idx].closed_at = Some(now);
+            }
+        }
+    }
+
+    fn on_fill(&mut self, executed: &ExecutedFill) {
+        let Some(&idx) = self.by_signal.get(&executed.signal_id) else { return };
+        let (trade, fill) = (&mut self.trades[idx], &executed.fill);
+        let leg = trade.legs.entry((fill.venue.clone(), fill.symbol.clone())).or_insert((0.0, 0.0));
+        trade.basis_pnl += fold_fill(leg, signed(fill.side, fill.qty), fill.price);
+        trade.fees += fill.fee;
+    }
+
+    /// Splits a venue payout across the trades holding that position, by quantity.
+    fn on_funding(&mut self, payment: &FundingPayment) {
+        if payment.qty.abs() < 1e-12 { return; }
+        let key = (payment.venue.clone(), payment.symbol.clone());
+        for trade in &mut self.trades {
+            let Some((qty, _)) = trade.legs.get(&key) else { continue };
+            trade.funding += payment.amount * qty / payment.qty;
+        }
+    }
+
+    /// Marks whatever is still open to the last books and fills in the net figures.
+    fn finish(&mut self, venues: &HashMap<String, Arc<PaperExchange>>) {
+        for trade in &mut self.trades {
+            let unrealised: f64 = trade.legs.iter().map(|((venue, symbol), (qty, entry))| {
+                let mark = venues.get(venue).and_then(|v| v.mark(symbol)).unwrap_or(*entry);
+                (mark - entry) * qty
+            }).sum();
+            trade.basis_pnl += unrealised;
+            trade.net = trade.basis_pnl + trade.funding - trade.fees;
+        }
+    }
+}
+
+fn summarise(trades: &[TradeRecord], equity: &[EquityPoint], starting_equity: f64) -> BacktestStats {
+    let closed: Vec<&TradeRecord> = trades.iter().filter(|t| t.closed_at.is_some()).collect();
+    let wins = closed.iter().filter(|t| t.net > 0.0).count();
+    let hold_secs: i64 = closed.iter()
+        .filter_map(|t| t.closed_at.map(|c| (c - t.opened_at).num_seconds()))
+        .sum();
+
+    let (mut peak, mut max_drawdown, mut max_drawdown_pct) = (f64::MIN, 0.0_f64, 0.0_f64);
+    for point in equity {
+        peak = peak.max(point.equity);
+        max_drawdown = max_drawdown.max(peak - point.equity);
+        if peak > 0.0 { max_drawdown_pct = max_drawdown_pct.max((peak - point.equity) / peak); }
+    }
+
+    let sum = |f: fn(&TradeRecord) -> f64| trades.iter().map(f).sum::<f64>();
+    BacktestStats {
+        trades: trades.len(),
+        closed: closed.len(),
+        hit_rate: if closed.is_empty() { 0.0 } else { wins as f64 / closed.len() as f64 },
+        avg_hold_secs: if closed.is_empty() { 0.0 } else { hold_secs as f64 / closed.len() as f64 },
+        basis_pnl: sum(|t| t.basis_pnl),
+        funding_pnl: sum(|t| t.funding),
+        fees: sum(|t| t.fees),
+        net_pnl: sum(|t| t.net),
+        starting_equity,
+        final_equity: equity.last().map(|p| p.equity).unwrap_or(starting_equity),
+        max_drawdown,
+        max_drawdown_pct,
+    }
+}
+
+/// Replays `events` through the live decision code (`strategy::evaluate_update`) and the
+/// live executor, with paper venues standing in for the exchanges.
+pub async fn run(
+    events: &[(i64, String)],
+    venues: &[&str],
+    fees: &FeeModel,
+    strategy: &StrategyConfig,
+    config: &BacktestConfig,
+) -> BacktestReport {
+    let store = Arc::new(MarketStore::new(venues, MAX_SYMBOLS));
+    let exchanges = PaperExchange::for_venues(venues, &store, fees, config.paper);
+    let by_venue: HashMap<String, Arc<PaperExchange>> = venues.iter().zip(&exchanges)
+        .map(|(venue, e)| (venue.to_string(), Arc::clone(e)))
+        .collect();
+    let clients: Clients = by_venue.iter()
+        .map(|(venue, e)| (venue.clone(), Arc::clone(e) as Arc<dyn ExchangeClient>))
+        .collect();
+
+    let (fill_tx, mut fill_rx) = mpsc::unbounded_channel();
+    let mut executor = Executor::offline(clients)
+        .with_config(&config.execution)
+        .with_fees(fees)
+        .with_books(Arc::clone(&store))
+        .with_fill_tap(fill_tx);
+    if !config.verbose { executor = executor.quiet(); }
+    let mut strat = StrategyState::new(strategy);
+    let mut log = TradeLog::default();
+    let mut pending: VecDeque<(DateTime<Utc>, TradeSignal)> = VecDeque::new();
+    let mut equity = Vec::new();
+    let starting_equity = total_equity(&exchanges);
+    let (mut next_funding, mut next_sample) = (i64::MIN, i64::MIN);
+
+    for (ts_ms, payload) in events {
+        let Some(now) = DateTime::<Utc>::from_timestamp_millis(*ts_ms) else { continue };
+        let Some((exchange, symbol, state)) = parse_market_payload(payload) else { continue };
+        let (Some(ex_id), Some(sym_id)) = (store.exchange_id(exchange), store.intern_symbol(symbol)) else { continue };
+        store.publish(ex_id, sym_id, state);
+        for exchange in &exchanges { exchange.set_time(now); }
+
+        // Orders only reach the venue once their latency has elapsed in replay time
+        while pending.front().is_some_and(|(at, _)| *at <= now) {
+            let Some((at, signal)) = pending.pop_front() else { break };
+            executor.execute(signal, at).await;
+        }
+        exchanges[ex_id].on_book(&store.symbol_name(sym_id));
+        executor.poll_orders(now).await;
+        while let Ok(executed) = fill_rx.try_recv() { log.on_fill(&executed); }
+
+        if *ts_ms >= next_funding {
+            for exchange in &exchanges {
+                for payment in exchange.accrue_funding(now) { log.on_funding(&payment); }
+            }
+            next_funding = ts_ms + FUNDING_CHECK_MS;
+        }
+
+        for (_, _, signal) in evaluate_update(&store, (ex_id, sym_id), &mut strat, (fees, strategy), now) {
+            let Some(signal) = signal else { continue };
+            log.on_signal(&signal, now);
+            pending.push_back((now + Duration::milliseconds(config.latency_ms), signal));
+        }
+
+        if *ts_ms >= next_sample {
+            equity.push(EquityPoint { ts: now, equity: total_equity(&exchanges) });
+            next_sample = ts_ms + config.equity_sample_ms;
+        }
+    }
+
+    if let Some((ts_ms, _)) = events.last() {
+        if let Some(ts) = DateTime::<Utc>::from_timestamp_millis(*ts_ms) {
+            equity.push(EquityPoint { ts, equity: total_equity(&exchanges) });
+        }
+    }
+    log.finish(&by_venue);
+    let stats = summarise(&log.trades, &equity, starting_equity);
+    BacktestReport { stats, trades: log.trades, equity }
+}
+
+pub fn print_summary(report: &BacktestReport) {
+    let s = &report.stats;
+    println!("📈 Backtest: {} trade(s), {} closed, hit rate {:.0}%, avg hold {:.0}s",
+        s.trades, s.closed, s.hit_rate * 100.0, s.avg_hold_secs);
+    println!("   net {:+.2} = basis {:+.2} + funding {:+.2} - fees {:.2}", s.net_pnl, s.basis_pnl, s.funding_pnl, s.fees);
+    println!("   equity {:.2} -> {:.2}, max drawdown {:.2} ({:.2}%)",
+        s.starting_equity, s.final_equity, s.max_drawdown, s.max_drawdown_pct * 100.0);
+    for t in &report.trades {
+        let hold = t.closed_at.map(|c| format!("{}s", (c - t.opened_at).num_seconds())).unwrap_or_else(|| "open".to_string());
+        println!("   {:<24} {} {:>8} | {:<10} -> {:<10} | net {:+8.2} (basis {:+.2}, fund {:+.2}, fees {:.2})",
+            t.pair_id, t.opened_at.format("%m-%d %H:%M:%S"), hold, t.entry_reason,
+            t.exit_reason.as_deref().unwrap_or("-"), t.net, t.basis_pnl, t.funding, t.fees);
+    }
+}
+
+/// `scanner-rust backtest <recording> [report.json]`.
+pub async fn run_file(path: &str, out: &str, venues: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
+    let recording = Recording::load(path)?;
+    println!("⏪ Replaying {} updates ({:.1}h) from {}", recording.events.len(), recording.span_ms() as f64 / 3_600_000.0, path);
+    let config = BacktestConfig { execution: ExecutionConfig::load(), ..BacktestConfig::default() };
+    let report = run(&recording.events, venues, &FeeModel::load(), &StrategyConfig::load(), &config).await;
+    print_summary(&report);
+    std::fs::write(out, serde_json::to_string_pretty(&report)?)?;
+    println!("💾 Trades, equity curve and stats written to {}", out);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(1_772_000_000 + secs, 0).unwrap()
+    }
+
+    fn trade(opened: i64, closed: Option<i64>, net: f64) -> TradeRecord {
+        TradeRecord {
+            pair_id: "BINANCE-BYBIT-BTC".to_string(),
+            entry_reason: "GreatEntry".to_string(),
+            exit_reason: closed.map(|_| "TakeProfit".to_string()),
+            opened_at: at(opened),
+            closed_at: closed.map(at),
+            expected_edge: 0.0005,
+            notional: 1000.0,
+            basis_pnl: net + 0.5,
+            funding: 0.0,
+            fees: 0.5,
+            net,
+            legs: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn summaries_take_the_deepest_fall_from_the_running_peak() {
+        let equity: Vec<EquityPoint> = [100.0, 120.0, 90.0, 130.0, 104.0].iter().enumerate()
+            .map(|(i, equity)| EquityPoint { ts: at(i as i64 * 60), equity: *equity })
+            .collect();
+        let trades = [trade(0, Some(60), 5.0), trade(0, Some(180), -2.0), trade(60, Some(120), 3.0), trade(120, None, 10.0)];
+        let stats = summarise(&trades, &equity, 100.0);
+
+        assert_eq!((stats.trades, stats.closed), (4, 3));
+        // The open trade's gain does not count towards the hit rate
+        assert!((stats.hit_rate - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(stats.avg_hold_secs, 100.0);
+        assert_eq!((stats.max_drawdown, stats.max_drawdown_pct), (30.0, 0.25));
+        assert_eq!((stats.starting_equity, stats.final_equity), (100.0, 104.0));
+        assert_eq!((stats.net_pnl, stats.fees), (16.0, 2.0));
+    }
+
+    #[test]
+    fn empty_runs_summarise_to_their_starting_equity() {
+        let stats = summarise(&[], &[], 20_000.0);
+        assert_eq!((stats.hit_rate, stats.avg_hold_secs, stats.max_drawdown), (0.0, 0.0, 0.0));
+        assert_eq!(stats.final_equity, 20_000.0);
+    }
+}
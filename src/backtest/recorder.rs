@@ -0,0 +1,60 @@
+use futures::StreamExt;
+use std::io::{BufRead, BufReader};
+use tokio::io::AsyncWriteExt;
+
+/// `market:data` payloads with the unix ms they arrived at, in arrival order.
+///
+/// On disk this is one `ts_ms<TAB>payload` line per update, exactly as the scanner saw it,
+/// so funding rates travel with the books.
+pub struct Recording {
+    pub events: Vec<(i64, String)>,
+}
+
+impl Recording {
+    pub fn load(path: &str) -> std::io::Result<Self> {
+        let reader = BufReader::new(std::fs::File::open(path)?);
+        let mut events = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            let Some((ts, payload)) = line.split_once('\t') else { continue };
+            let Ok(ts) = ts.parse::<i64>() else { continue };
+            events.push((ts, payload.to_string()));
+        }
+        // Appends from several writers can interleave slightly out of order
+        events.sort_by_key(|(ts, _)| *ts);
+        Ok(Self { events })
+    }
+
+    pub fn span_ms(&self) -> i64 {
+        match (self.events.first(), self.events.last()) {
+            (Some((first, _)), Some((last, _))) => last - first,
+            _ => 0,
+        }
+    }
+}
+
+/// Appends every `market:data` message to `path` until the feed ends.
+pub async fn record(path: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let client = redis::Client::open("redis://localhost/")?;
+    let mut pubsub_conn = client.get_async_pubsub().await?;
+    pubsub_conn.subscribe("market:data").await?;
+    let mut stream = pubsub_conn.into_on_message();
+
+    let file = tokio::fs::OpenOptions::new().create(true).append(true).open(path).await?;
+    let mut out = tokio::io::BufWriter::new(file);
+    println!("⏺️  Recording market:data to {}", path);
+
+    let mut written: u64 = 0;
+    while let Some(msg) = stream.next().await {
+        let payload: String = msg.get_payload().unwrap_or_default();
+        let line = format!("{}\t{}\n", chrono::Utc::now().timestamp_millis(), payload);
+        out.write_all(line.as_bytes()).await?;
+        written += 1;
+        if written.is_multiple_of(1000) {
+            out.flush().await?;
+            println!("⏺️  {} updates recorded", written);
+        }
+    }
+    out.flush().await?;
+    Ok(())
+}
@@ -0,0 +1,385 @@
+use super::engine::{self, BacktestConfig, BacktestStats};
+use super::recorder::Recording;
+use crate::execution::execution_config::ExecutionConfig;
+use crate::ingestors::fees::FeeModel;
+use crate::ingestors::strategy_config::StrategyConfig;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::ops::Range;
+use std::sync::Arc;
+
+const DEFAULT_CONFIG_PATH: &str = "config/sweep.json";
+/// Drawdown floor for scoring, so a candidate that barely trades cannot divide by ~0.
+const MIN_DRAWDOWN: f64 = 0.001;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchMode { Grid, Random }
+
+/// Values to try for each threshold. An empty list keeps the base config's value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SweepSpace {
+    pub mode: SearchMode,
+    /// Candidates drawn in random mode.
+    pub samples: usize,
+    pub seed: u64,
+    /// Consecutive walk-forward windows the recording is cut into.
+    pub folds: usize,
+    /// Share of each window used for fitting; the rest is the out-of-sample test.
+    pub train_ratio: f64,
+    pub acceptable: Vec<f64>,
+    pub great: Vec<f64>,
+    pub sniper: Vec<f64>,
+    pub min_streak: Vec<u32>,
+    pub obi_limit: Vec<f64>,
+    pub sentinel_ms: Vec<i64>,
+    pub sniper_ttl_secs: Vec<i64>,
+    pub target_edge: Vec<f64>,
+    pub take_profit_ratio: Vec<f64>,
+    pub stop_loss: Vec<f64>,
+    pub pre_payout_secs: Vec<i64>,
+}
+
+impl Default for SweepSpace {
+    fn default() -> Self {
+        Self {
+            mode: SearchMode::Random,
+            samples: 50,
+            seed: 7,
+            folds: 3,
+            train_ratio: 0.7,
+            acceptable: vec![0.0001, 0.0002, 0.0003],
+            great: vec![0.0004, 0.0005, 0.0007],
+            sniper: vec![0.0010, 0.0015, 0.0025],
+            min_streak: vec![3, 5, 8],
+            obi_limit: vec![0.4, 0.6, 0.8],
+            sentinel_ms: vec![250, 500, 1000],
+            sniper_ttl_secs: vec![300, 600, 900],
+            target_edge: vec![0.0001, 0.0002, 0.0003],
+            take_profit_ratio: vec![0.6, 0.8, 0.9],
+            stop_loss: vec![0.0005, 0.0010, 0.0020],
+            pre_payout_secs: vec![15, 30, 60],
+        }
+    }
+}
+
+impl SweepSpace {
+    /// Reads `SWEEP_CONFIG` (or `config/sweep.json`); missing fields keep their defaults.
+    pub fn load() -> Self {
+        let path = std::env::var("SWEEP_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        let Ok(raw) = std::fs::read_to_string(&path) else { return Self::default() };
+        serde_json::from_str(&raw).unwrap_or_else(|e| {
+            println!("⚠️ [Sweep] Ignoring {}: {}", path, e);
+            Self::default()
+        })
+    }
+}
+
+// --- 🎲 CANDIDATES ---
+
+type Setter = Box<dyn Fn(&mut StrategyConfig, usize)>;
+
+/// One axis of the search: how many values it has and how to apply the i-th.
+fn axis<T: Copy + 'static>(values: &[T], set: fn(&mut StrategyConfig, T)) -> Option<(usize, Setter)> {
+    if values.is_empty() { return None; }
+    let values = values.to_vec();
+    Some((values.len(), Box::new(move |config, i| set(config, values[i]))))
+}
+
+fn axes(space: &SweepSpace) -> Vec<(usize, Setter)> {
+    [
+        axis(&space.acceptable, |c, v| c.entry.acceptable = v),
+        axis(&space.great, |c, v| c.entry.great = v),
+        axis(&space.sniper, |c, v| c.entry.sniper = v),
+        axis(&space.min_streak, |c, v| c.entry.min_streak = v),
+        axis(&space.obi_limit, |c, v| c.entry.obi_limit = v),
+        axis(&space.sentinel_ms, |c, v| c.entry.sentinel_ms = v),
+        axis(&space.sniper_ttl_secs, |c, v| c.entry.sniper_ttl_secs = v),
+        axis(&space.target_edge, |c, v| c.entry.target_edge = v),
+        axis(&space.take_profit_ratio, |c, v| c.exit.take_profit_ratio = v),
+        axis(&space.stop_loss, |c, v| c.exit.stop_loss = v),
+        axis(&space.pre_payout_secs, |c, v| c.exit.pre_payout_secs = v),
+    ].into_iter().flatten().collect()
+}
+
+/// SplitMix64; plenty for drawing grid points and keeps runs reproducible from `seed`.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Every grid point (or `samples` random ones), applied on top of `base`. Tier cutoffs that
+/// are out of order are skipped.
+pub fn candidates(space: &SweepSpace, base: &StrategyConfig) -> Vec<StrategyConfig> {
+    let axes = axes(space);
+    let grid_size: usize = axes.iter().map(|(len, _)| *len).product();
+    let picks: Vec<Vec<usize>> = match space.mode {
+        SearchMode::Grid => (0..grid_size).map(|mut n| {
+            axes.iter().map(|(len, _)| { let i = n % len; n /= len; i }).collect()
+        }).collect(),
+        SearchMode::Random => {
+            let mut rng = SplitMix(space.seed);
+            (0..space.samples).map(|_| axes.iter().map(|(len, _)| (rng.next() % *len as u64) as usize).collect()).collect()
+        }
+    };
+
+    let mut seen = HashSet::new();
+    picks.into_iter().filter_map(|pick| {
+        let mut config = base.clone();
+        for ((_, set), i) in axes.iter().zip(pick) { set(&mut config, i); }
+        let ordered = config.entry.acceptable <= config.entry.great && config.entry.great <= config.entry.sniper;
+        let key = serde_json::to_string(&config).unwrap_or_default();
+        (ordered && seen.insert(key)).then_some(config)
+    }).collect()
+}
+
+// --- 🚶 WALK-FORWARD ---
+
+/// Return on starting equity per unit of max drawdown.
+pub fn risk_adjusted(stats: &BacktestStats) -> f64 {
+    if stats.starting_equity <= 0.0 { return 0.0; }
+    (stats.net_pnl / stats.starting_equity) / stats.max_drawdown_pct.max(MIN_DRAWDOWN)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Fold {
+    pub train: Range<usize>,
+    pub test: Range<usize>,
+}
+
+/// Cuts the recording into `folds` equal stretches of time, each split into train then test.
+pub fn folds(events: &[(i64, String)], folds: usize, train_ratio: f64) -> Vec<Fold> {
+    let (Some((first, _)), Some((last, _))) = (events.first(), events.last()) else { return Vec::new() };
+    let folds = folds.max(1);
+    let span = (last - first + 1) as f64 / folds as f64;
+    let index_at = |ts: f64| events.partition_point(|(t, _)| (*t as f64) < ts);
+    (0..folds).map(|k| {
+        let start = *first as f64 + span * k as f64;
+        let split = start + span * train_ratio.clamp(0.0, 1.0);
+        let end = start + span;
+        Fold { train: index_at(start)..index_at(split), test: index_at(split)..index_at(end) }
+    }).collect()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateResult {
+    pub config: StrategyConfig,
+    /// Risk-adjusted score per fold.
+    pub train_scores: Vec<f64>,
+    pub test_scores: Vec<f64>,
+    pub mean_train: f64,
+    pub mean_test: f64,
+    pub test_net_pnl: f64,
+    pub test_trades: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FoldWinner {
+    pub fold: usize,
+    /// Rank (in `ranking`) of the candidate that scored best on this fold's train window.
+    pub candidate: usize,
+    pub train_score: f64,
+    pub test_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SweepReport {
+    pub folds: Vec<Fold>,
+    /// Best in-sample mean score first; test scores never feed the order, they only validate it.
+    pub ranking: Vec<CandidateResult>,
+    /// Classic walk-forward: fit on each train window, score the pick on the next test window.
+    pub walk_forward: Vec<FoldWinner>,
+    pub walk_forward_score: f64,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    if values.is_empty() { 0.0 } else { values.iter().sum::<f64>() / values.len() as f64 }
+}
+
+async fn evaluate(
+    events: Arc<Vec<(i64, String)>>,
+    folds: Arc<Vec<Fold>>,
+    venues: &'static [&'static str],
+    (fees, backtest): (Arc<FeeModel>, Arc<BacktestConfig>),
+    config: StrategyConfig,
+) -> CandidateResult {
+    let (mut train_scores, mut test_scores, mut test_net_pnl, mut test_trades) = (Vec::new(), Vec::new(), 0.0, 0);
+    for fold in folds.iter() {
+        let train = engine::run(&events[fold.train.clone()], venues, &fees, &config, &backtest).await;
+        let test = engine::run(&events[fold.test.clone()], venues, &fees, &config, &backtest).await;
+        train_scores.push(risk_adjusted(&train.stats));
+        test_scores.push(risk_adjusted(&test.stats));
+        test_net_pnl += test.stats.net_pnl;
+        test_trades += test.stats.trades;
+    }
+    CandidateResult {
+        mean_train: mean(&train_scores),
+        mean_test: mean(&test_scores),
+        config,
+        train_scores,
+        test_scores,
+        test_net_pnl,
+        test_trades,
+    }
+}
+
+/// Classic walk-forward: on each fold, the candidate with the best train score and what it then
+/// scored on that fold's test window.
+fn walk_forward(ranking: &[CandidateResult], folds: usize) -> Vec<FoldWinner> {
+    (0..folds).filter_map(|fold| {
+        let (candidate, best) = ranking.iter().enumerate()
+            .max_by(|(_, a), (_, b)| a.train_scores[fold].total_cmp(&b.train_scores[fold]))?;
+        Some(FoldWinner { fold, candidate, train_score: best.train_scores[fold], test_score: best.test_scores[fold] })
+    }).collect()
+}
+
+/// Backtests every candidate on every fold, in parallel across cores.
+pub async fn run(
+    events: Vec<(i64, String)>,
+    venues: &'static [&'static str],
+    fees: FeeModel,
+    space: &SweepSpace,
+    (base, backtest): (&StrategyConfig, &BacktestConfig),
+) -> SweepReport {
+    let folds = Arc::new(folds(&events, space.folds, space.train_ratio));
+    let (events, fees) = (Arc::new(events), Arc::new(fees));
+    let backtest = Arc::new(BacktestConfig { verbose: false, ..backtest.clone() });
+    let configs = candidates(space, base);
+    let workers = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4);
+    println!("🔬 Sweeping {} candidate(s) over {} fold(s) on {} worker(s)", configs.len(), folds.len(), workers);
+
+    let total = configs.len();
+    let mut done = 0;
+    let mut ranking = Vec::with_capacity(total);
+    let mut results = futures::stream::iter(configs).map(|config| {
+        let (events, folds, fees, backtest) = (Arc::clone(&events), Arc::clone(&folds), Arc::clone(&fees), Arc::clone(&backtest));
+        tokio::spawn(evaluate(events, folds, venues, (fees, backtest), config))
+    }).buffer_unordered(workers);
+    while let Some(result) = results.next().await {
+        let Ok(result) = result else { continue };
+        done += 1;
+        if done % 10 == 0 || done == total { println!("   {}/{} evaluated", done, total); }
+        ranking.push(result);
+    }
+    // Picking on the test windows would make them in-sample too
+    ranking.sort_by(|a, b| b.mean_train.total_cmp(&a.mean_train));
+
+    let walk_forward = walk_forward(&ranking, folds.len());
+    let walk_forward_score = mean(&walk_forward.iter().map(|w| w.test_score).collect::<Vec<_>>());
+
+    SweepReport { folds: folds.to_vec(), ranking, walk_forward, walk_forward_score }
+}
+
+/// `scanner-rust sweep <recording> [best_strategy.json]`.
+pub async fn run_file(path: &str, out: &str, venues: &'static [&'static str]) -> Result<(), Box<dyn std::error::Error>> {
+    let recording = Recording::load(path)?;
+    let space = SweepSpace::load();
+    let backtest = BacktestConfig { execution: ExecutionConfig::load(), ..BacktestConfig::default() };
+    let report = run(recording.events, venues, FeeModel::load(), &space, (&StrategyConfig::load(), &backtest)).await;
+
+    println!("🏆 Top candidates by in-sample score (return / max drawdown), out-of-sample shown for validation:");
+    for (rank, result) in report.ranking.iter().take(10).enumerate() {
+        let (entry, exit) = (&result.config.entry, &result.config.exit);
+        println!("   #{:<2} train {:+.3} | test {:+.3} | {:>3} trades {:+8.2} | tiers {:.1}/{:.1}/{:.1}bp streak {} obi {:.1} sentinel {}ms ttl {}s edge {:.1}bp | tp {:.0}% sl {:.1}bp pre {}s",
+            rank + 1, result.mean_train, result.mean_test, result.test_trades, result.test_net_pnl,
+            entry.acceptable * 1e4, entry.great * 1e4, entry.sniper * 1e4, entry.min_streak, entry.obi_limit, entry.sentinel_ms,
+            entry.sniper_ttl_secs, entry.target_edge * 1e4, exit.take_profit_ratio * 100.0, exit.stop_loss * 1e4, exit.pre_payout_secs);
+    }
+    for w in &report.walk_forward {
+        println!("   fold {}: train winner #{} scored {:+.3} in-sample, {:+.3} out-of-sample", w.fold, w.candidate + 1, w.train_score, w.test_score);
+    }
+    println!("   walk-forward out-of-sample score: {:+.3}", report.walk_forward_score);
+
+    let Some(best) = report.ranking.first() else { return Err("no candidates to evaluate".into()) };
+    std::fs::write(out, serde_json::to_string_pretty(&best.config)?)?;
+    std::fs::write("sweep_report.json", serde_json::to_string_pretty(&report)?)?;
+    println!("💾 Winner written to {} (load with STRATEGY_CONFIG={}); full ranking in sweep_report.json", out, out);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A space that only varies the tier cutoffs and streak; everything else keeps the base value.
+    fn space(mode: SearchMode, acceptable: Vec<f64>, great: Vec<f64>, min_streak: Vec<u32>) -> SweepSpace {
+        SweepSpace {
+            mode, acceptable, great, min_streak,
+            sniper: vec![], obi_limit: vec![], sentinel_ms: vec![], sniper_ttl_secs: vec![], target_edge: vec![],
+            take_profit_ratio: vec![], stop_loss: vec![], pre_payout_secs: vec![],
+            ..SweepSpace::default()
+        }
+    }
+
+    fn result(train_scores: Vec<f64>, test_scores: Vec<f64>) -> CandidateResult {
+        CandidateResult {
+            config: StrategyConfig::default(),
+            mean_train: mean(&train_scores),
+            mean_test: mean(&test_scores),
+            train_scores,
+            test_scores,
+            test_net_pnl: 0.0,
+            test_trades: 0,
+        }
+    }
+
+    #[test]
+    fn grids_cover_every_combination() {
+        let base = StrategyConfig::default();
+        let configs = candidates(&space(SearchMode::Grid, vec![0.0001, 0.0002, 0.0003], vec![0.0004, 0.0005], vec![3, 5]), &base);
+        assert_eq!(configs.len(), 12);
+        assert!(configs.iter().all(|c| c.entry.sniper == base.entry.sniper), "unswept axes keep the base value");
+        let streaks: HashSet<u32> = configs.iter().map(|c| c.entry.min_streak).collect();
+        assert_eq!(streaks, HashSet::from([3, 5]));
+    }
+
+    #[test]
+    fn out_of_order_tiers_and_duplicates_are_dropped() {
+        let base = StrategyConfig::default();
+        // 6bp acceptable is above the 5bp great cutoff
+        let configs = candidates(&space(SearchMode::Grid, vec![0.0001, 0.0006], vec![0.0005], vec![]), &base);
+        assert_eq!(configs.iter().map(|c| c.entry.acceptable).collect::<Vec<_>>(), [0.0001]);
+
+        let repeated = candidates(&space(SearchMode::Grid, vec![0.0002, 0.0002], vec![], vec![5, 5]), &base);
+        assert_eq!(repeated.len(), 1);
+        // Random draws from a two-point space can only find two distinct configs
+        let drawn = candidates(&space(SearchMode::Random, vec![0.0001, 0.0002], vec![], vec![]), &base);
+        assert_eq!(drawn.len(), 2);
+    }
+
+    #[test]
+    fn folds_split_time_not_event_counts() {
+        // Dense first half, sparse second half
+        let mut events: Vec<(i64, String)> = (0..80).map(|i| (i, String::new())).collect();
+        events.extend((0..20).map(|i| (100 + i * 5, String::new())));
+        let folds = folds(&events, 2, 0.5);
+        assert_eq!(folds.len(), 2);
+        // The span is 0..=195, so each fold covers 98ms and trains on the first 49
+        assert_eq!((folds[0].train.clone(), folds[0].test.clone()), (0..49, 49..80));
+        assert_eq!(folds[1].train.start, 80);
+        assert_eq!(folds[1].test.end, events.len());
+        assert!(super::folds(&[], 3, 0.7).is_empty());
+    }
+
+    #[test]
+    fn walk_forward_picks_on_train_scores_only() {
+        let ranking = [
+            result(vec![1.0, 0.2], vec![-1.0, 0.5]),
+            result(vec![0.5, 0.9], vec![2.0, -0.3]),
+        ];
+        let winners = walk_forward(&ranking, 2);
+        let picks: Vec<(usize, usize, f64)> = winners.iter().map(|w| (w.fold, w.candidate, w.test_score)).collect();
+        // Candidate 1 tested better on fold 0, but the pick may not look at test scores
+        assert_eq!(picks, [(0, 0, -1.0), (1, 1, -0.3)]);
+        assert!(walk_forward(&[], 2).is_empty());
+    }
+}
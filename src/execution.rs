@@ -0,0 +1,51 @@
+#[path = "execution/exchange.rs"]
+pub mod exchange;
+
+#[path = "execution/mock_exchange.rs"]
+pub mod mock_exchange;
+
+#[path = "execution/executor.rs"]
+pub mod executor;
+
+#[path = "execution/paper.rs"]
+pub mod paper;
+
+#[path = "execution/alerts.rs"]
+pub mod alerts;
+
+#[path = "execution/leg_risk.rs"]
+pub mod leg_risk;
+
+#[path = "execution/execution_config.rs"]
+pub mod execution_config;
+
+#[path = "execution/binance_client.rs"]
+pub mod binance_client;
+
+#[path = "execution/bybit_client.rs"]
+pub mod bybit_client;
+
+#[path = "execution/hyperliquid_client.rs"]
+pub mod hyperliquid_client;
+
+#[path = "execution/order_manager.rs"]
+pub mod order_manager;
+
+#[path = "execution/hybrid.rs"]
+pub mod hybrid;
+
+#[path = "execution/risk.rs"]
+pub mod risk;
+
+#[path = "execution/ledger.rs"]
+pub mod ledger;
+
+#[path = "execution/funding_recon.rs"]
+pub mod funding_recon;
+
+#[path = "execution/account_monitor.rs"]
+pub mod account_monitor;
+
+#[cfg(test)]
+#[path = "execution/mock_server.rs"]
+mod mock_server;
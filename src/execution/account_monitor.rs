@@ -0,0 +1,176 @@
+use super::alerts::{Alert, AlertLevel, ALERTS_CHANNEL};
+use super::exchange::ExchangeError;
+use super::executor::Clients;
+use crate::ingestors::accounts::{AccountSnapshot, LegHealth, VenueAccount};
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountMonitorConfig {
+    pub enabled: bool,
+    pub poll_ms: u64,
+    /// A leg whose mark is closer than this share to its liquidation price raises an alert and
+    /// blocks new entries on its venue until it recovers.
+    pub min_liquidation_distance: f64,
+}
+
+impl Default for AccountMonitorConfig {
+    fn default() -> Self {
+        Self { enabled: true, poll_ms: 10_000, min_liquidation_distance: 0.15 }
+    }
+}
+
+/// Reads balance and positions from every venue. A venue that fails is reported with its error.
+pub async fn poll(clients: Clients) -> Vec<VenueAccount> {
+    let mut accounts = Vec::new();
+    for (venue, client) in clients {
+        let read = async {
+            let balance = client.balance().await?;
+            let positions = client.positions().await?;
+            Ok::<_, ExchangeError>((balance, positions))
+        };
+        let account = match read.await {
+            Ok((balance, positions)) => VenueAccount {
+                venue: venue.clone(),
+                equity: balance.equity,
+                available: balance.available,
+                margin_used: balance.margin_used,
+                margin_ratio: if balance.equity > 0.0 { balance.margin_used / balance.equity } else { 0.0 },
+                legs: positions.into_iter().map(|p| {
+                    // Venues report entry and unrealised PnL; the mark falls out of the two
+                    let mark = if p.qty != 0.0 { p.entry_price + p.unrealized_pnl / p.qty } else { p.entry_price };
+                    LegHealth {
+                        venue: venue.clone(),
+                        symbol: p.symbol,
+                        qty: p.qty,
+                        mark,
+                        liquidation_price: p.liquidation_price,
+                        distance: p.liquidation_price.filter(|_| mark > 0.0).map(|liq| (mark - liq).abs() / mark),
+                    }
+                }).collect(),
+                error: None,
+            },
+            Err(e) => VenueAccount { venue: venue.clone(), error: Some(e.to_string()), ..VenueAccount::default() },
+        };
+        accounts.push(account);
+    }
+    accounts.sort_by(|a, b| a.venue.cmp(&b.venue));
+    accounts
+}
+
+/// Turns polls into snapshots and alerts once per leg each time it crosses the threshold.
+pub struct AccountMonitor {
+    config: AccountMonitorConfig,
+    /// (venue, symbol) of legs currently too close to liquidation.
+    endangered: HashSet<(String, String)>,
+}
+
+impl AccountMonitor {
+    pub fn new(config: AccountMonitorConfig) -> Self {
+        Self { config, endangered: HashSet::new() }
+    }
+
+    pub fn config(&self) -> &AccountMonitorConfig {
+        &self.config
+    }
+
+    pub async fn on_poll(&mut self, venues: Vec<VenueAccount>, now: DateTime<Utc>, conn: &mut redis::aio::MultiplexedConnection) -> AccountSnapshot {
+        let (alerts, blocked) = self.review(&venues, now);
+        for alert in alerts {
+            println!("{} [Accounts] {}", alert.icon(), alert.message);
+            if let Ok(raw) = serde_json::to_string(&alert) {
+                let _: () = conn.publish(ALERTS_CHANNEL, raw).await.unwrap_or(());
+            }
+        }
+        let snapshot = AccountSnapshot { ts: now, venues, blocked };
+        snapshot.publish(conn).await;
+        snapshot
+    }
+
+    /// Updates the endangered legs from one poll; returns the alerts it raised and the venues now blocked.
+    fn review(&mut self, venues: &[VenueAccount], now: DateTime<Utc>) -> (Vec<Alert>, Vec<String>) {
+        let mut alerts = Vec::new();
+        let mut endangered = HashSet::new();
+        for account in venues {
+            if let Some(error) = &account.error {
+                println!("⚠️ [Accounts] Could not read {}: {}", account.venue, error);
+                // Keep what we knew about its legs rather than unblocking on a failed read
+                endangered.extend(self.endangered.iter().filter(|(venue, _)| *venue == account.venue).cloned());
+                continue;
+            }
+            for leg in &account.legs {
+                let Some(distance) = leg.distance.filter(|d| *d < self.config.min_liquidation_distance) else { continue };
+                let key = (leg.venue.clone(), leg.symbol.clone());
+                if !self.endangered.contains(&key) {
+                    alerts.push(Alert::new(AlertLevel::Critical, "accounts", format!(
+                        "{} {} is {:.1}% from liquidation (mark {:.4}, liq {:.4}); entries on {} blocked",
+                        leg.venue, leg.symbol, distance * 100.0, leg.mark, leg.liquidation_price.unwrap_or(0.0), leg.venue,
+                    ), now));
+                }
+                endangered.insert(key);
+            }
+        }
+        for (venue, symbol) in self.endangered.difference(&endangered) {
+            alerts.push(Alert::new(AlertLevel::Info, "accounts", format!("{} {} is clear of liquidation again", venue, symbol), now));
+        }
+        self.endangered = endangered;
+
+        let mut blocked: Vec<String> = self.endangered.iter().map(|(venue, _)| venue.clone()).collect();
+        blocked.sort();
+        blocked.dedup();
+        (alerts, blocked)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(venue: &str, legs: &[(&str, f64, Option<f64>)]) -> VenueAccount {
+        VenueAccount {
+            venue: venue.to_string(),
+            equity: 10_000.0,
+            available: 8_000.0,
+            legs: legs.iter().map(|(symbol, mark, liq)| LegHealth {
+                venue: venue.to_string(),
+                symbol: symbol.to_string(),
+                qty: 1.0,
+                mark: *mark,
+                liquidation_price: *liq,
+                distance: liq.map(|liq| (mark - liq).abs() / mark),
+            }).collect(),
+            ..VenueAccount::default()
+        }
+    }
+
+    #[test]
+    fn legs_near_liquidation_block_their_venue_until_they_recover() {
+        let mut monitor = AccountMonitor::new(AccountMonitorConfig::default());
+        let now = Utc::now();
+
+        // 10% from liquidation on bybit; 50% and no liquidation price elsewhere are fine
+        let polled = [account("bybit", &[("BTCUSDT", 100.0, Some(110.0))]), account("binance", &[("BTCUSDT", 100.0, Some(50.0)), ("ETHUSDT", 100.0, None)])];
+        let (alerts, blocked) = monitor.review(&polled, now);
+        assert_eq!(blocked, ["bybit"]);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].level, AlertLevel::Critical);
+
+        // Still endangered: blocked without a second alert
+        let (alerts, blocked) = monitor.review(&polled, now);
+        assert!(alerts.is_empty());
+        assert_eq!(blocked, ["bybit"]);
+
+        // A failed read keeps the block
+        let failed = [VenueAccount { venue: "bybit".to_string(), error: Some("timeout".to_string()), ..VenueAccount::default() }];
+        let (alerts, blocked) = monitor.review(&failed, now);
+        assert!(alerts.is_empty());
+        assert_eq!(blocked, ["bybit"]);
+
+        let (alerts, blocked) = monitor.review(&[account("bybit", &[("BTCUSDT", 100.0, Some(130.0))])], now);
+        assert!(blocked.is_empty());
+        assert_eq!(alerts.iter().map(|a| a.level).collect::<Vec<_>>(), [AlertLevel::Info]);
+    }
+}
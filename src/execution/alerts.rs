@@ -0,0 +1,27 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+pub const ALERTS_CHANNEL: &str = "trade:alerts";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AlertLevel { Info, Warning, Critical }
+
+/// Something an operator should look at, published on `trade:alerts`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Alert {
+    pub level: AlertLevel,
+    pub source: String,
+    pub message: String,
+    pub ts: DateTime<Utc>,
+}
+
+impl Alert {
+    pub fn new(level: AlertLevel, source: &str, message: String, ts: DateTime<Utc>) -> Self {
+        Self { level, source: source.to_string(), message, ts }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self.level { AlertLevel::Info => "ℹ️", AlertLevel::Warning => "⚠️", AlertLevel::Critical => "🚨" }
+    }
+}
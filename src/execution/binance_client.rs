@@ -0,0 +1,676 @@
+use super::exchange::{check_client_order_id, hmac_sha256_hex, num, to_step, to_tick, Balance, ExchangeClient, ExchangeError, FundingIncome, OrderRequest, OrderState, OrderStatus, TimeInForce, VenuePosition};
+use crate::ingestors::signals::Side;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use reqwest::Method;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{interval, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+const DEFAULT_REST_URL: &str = "https://fapi.binance.com";
+const DEFAULT_WS_URL: &str = "wss://fstream.binance.com";
+const RECV_WINDOW_MS: u64 = 5000;
+/// listenKeys expire after 60 minutes without a keepalive.
+const LISTEN_KEY_KEEPALIVE_SECS: u64 = 30 * 60;
+const TIME_SYNC_SECS: u64 = 5 * 60;
+const INCOME_PAGE: usize = 1000;
+/// Commission-asset prices (BNB) are looked up at most this often.
+const FEE_ASSET_PRICE_SECS: i64 = 60;
+
+// --- 🔑 CREDENTIALS ---
+
+pub struct BinanceCredentials {
+    pub api_key: String,
+    pub secret: String,
+}
+
+impl BinanceCredentials {
+    /// `BINANCE_API_KEY` / `BINANCE_API_SECRET`, if both are set.
+    pub fn from_env() -> Option<Self> {
+        Some(Self { api_key: std::env::var("BINANCE_API_KEY").ok()?, secret: std::env::var("BINANCE_API_SECRET").ok()? })
+    }
+
+    fn sign(&self, query: &str) -> String {
+        hmac_sha256_hex(&self.secret, query)
+    }
+}
+
+/// Quantity step and price tick from `exchangeInfo`.
+#[derive(Debug, Clone, Copy)]
+struct SymbolFilters {
+    step: f64,
+    tick: f64,
+}
+
+fn parse_status(raw: &str) -> OrderStatus {
+    match raw {
+        "NEW" => OrderStatus::New,
+        "PARTIALLY_FILLED" => OrderStatus::PartiallyFilled,
+        "FILLED" => OrderStatus::Filled,
+        "CANCELED" => OrderStatus::Canceled,
+        "REJECTED" => OrderStatus::Rejected,
+        _ => OrderStatus::Expired, // EXPIRED, EXPIRED_IN_MATCH
+    }
+}
+
+/// Maps a Binance `{code, msg}` error body to our error type.
+fn map_error(http: u16, body: &Value) -> ExchangeError {
+    let code = body["code"].as_i64().unwrap_or(0);
+    let msg = body["msg"].as_str().unwrap_or("").to_string();
+    match (http, code) {
+        (429 | 418, _) | (_, -1003) => ExchangeError::RateLimited,
+        (_, -2013 | -2011) => ExchangeError::NotFound,
+        (_, -1022 | -2014 | -2015) | (401, _) => ExchangeError::Auth(msg),
+        (500..=599, _) => ExchangeError::Network(format!("{} {}", http, msg)),
+        _ => ExchangeError::Rejected(format!("{} {}", code, msg)),
+    }
+}
+
+// --- 🏦 CLIENT ---
+
+/// USDⓈ-M futures: signed REST for trading and a listenKey stream for order updates.
+///
+/// `BINANCE_FAPI_URL` / `BINANCE_FSTREAM_URL` override the endpoints, e.g. to point at a
+/// local mock server or the testnet.
+pub struct BinanceFutures {
+    http: reqwest::Client,
+    rest_url: String,
+    ws_url: String,
+    creds: BinanceCredentials,
+    /// Server time minus local time, refreshed periodically.
+    time_offset_ms: AtomicI64,
+    filters: Mutex<HashMap<String, SymbolFilters>>,
+    /// Latest order state per client order ID as pushed by the user-data stream, with the
+    /// stream session that pushed it.
+    stream_orders: Mutex<HashMap<String, (u64, OrderState)>>,
+    stream_live: AtomicBool,
+    /// Bumped on every (re)connect. Updates sent while the socket was down are lost, so an open
+    /// order last heard of in an earlier session is fetched over REST instead.
+    stream_session: AtomicU64,
+    /// Last USDT price and when it was fetched, per commission asset that is not the quote.
+    fee_asset_prices: Mutex<HashMap<String, (DateTime<Utc>, f64)>>,
+}
+
+impl BinanceFutures {
+    pub fn new(creds: BinanceCredentials) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            rest_url: std::env::var("BINANCE_FAPI_URL").unwrap_or_else(|_| DEFAULT_REST_URL.to_string()),
+            ws_url: std::env::var("BINANCE_FSTREAM_URL").unwrap_or_else(|_| DEFAULT_WS_URL.to_string()),
+            creds,
+            time_offset_ms: AtomicI64::new(0),
+            filters: Mutex::new(HashMap::new()),
+            stream_orders: Mutex::new(HashMap::new()),
+            stream_live: AtomicBool::new(false),
+            stream_session: AtomicU64::new(0),
+            fee_asset_prices: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Syncs the clock and loads symbol filters; call once before trading.
+    pub async fn connect(self: &Arc<Self>) -> Result<(), ExchangeError> {
+        self.sync_time().await?;
+        self.load_filters().await?;
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut timer = interval(Duration::from_secs(TIME_SYNC_SECS));
+            loop {
+                timer.tick().await;
+                if let Err(e) = client.sync_time().await { println!("⚠️ [Binance] time sync: {}", e); }
+            }
+        });
+        self.spawn_user_stream();
+        Ok(())
+    }
+
+    /// Estimates the server clock offset from `/fapi/v1/time`, halving the round trip.
+    pub async fn sync_time(&self) -> Result<(), ExchangeError> {
+        let sent = Utc::now().timestamp_millis();
+        let body = self.public(&format!("{}/fapi/v1/time", self.rest_url)).await?;
+        let received = Utc::now().timestamp_millis();
+        let server = body["serverTime"].as_i64().ok_or_else(|| ExchangeError::Other("no serverTime".to_string()))?;
+        self.time_offset_ms.store(server - (sent + received) / 2, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn load_filters(&self) -> Result<(), ExchangeError> {
+        let body = self.public(&format!("{}/fapi/v1/exchangeInfo", self.rest_url)).await?;
+        let mut filters = HashMap::new();
+        for symbol in body["symbols"].as_array().into_iter().flatten() {
+            let Some(name) = symbol["symbol"].as_str() else { continue };
+            let mut entry = SymbolFilters { step: 0.0, tick: 0.0 };
+            for filter in symbol["filters"].as_array().into_iter().flatten() {
+                match filter["filterType"].as_str() {
+                    Some("LOT_SIZE") => entry.step = num(&filter["stepSize"]),
+                    Some("PRICE_FILTER") => entry.tick = num(&filter["tickSize"]),
+                    _ => {}
+                }
+            }
+            filters.insert(name.to_string(), entry);
+        }
+        *self.filters.lock().unwrap() = filters;
+        Ok(())
+    }
+
+    /// USDT value of one unit of `asset`, cached for a minute. A failed lookup falls back to the
+    /// last price seen.
+    async fn fee_asset_price(&self, asset: &str) -> Option<f64> {
+        let cached = self.fee_asset_prices.lock().unwrap().get(asset).copied();
+        if let Some((at, price)) = cached {
+            if (Utc::now() - at).num_seconds() < FEE_ASSET_PRICE_SECS { return Some(price); }
+        }
+        let url = format!("{}/fapi/v1/ticker/price?symbol={}USDT", self.rest_url, asset);
+        match self.public(&url).await.map(|body| num(&body["price"])) {
+            Ok(price) if price > 0.0 => {
+                self.fee_asset_prices.lock().unwrap().insert(asset.to_string(), (Utc::now(), price));
+                Some(price)
+            }
+            Ok(_) => cached.map(|(_, price)| price),
+            Err(e) => {
+                println!("⚠️ [Binance] {} price: {}", asset, e);
+                cached.map(|(_, price)| price)
+            }
+        }
+    }
+
+    async fn public(&self, url: &str) -> Result<Value, ExchangeError> {
+        let resp = self.http.get(url).send().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        let status = resp.status().as_u16();
+        let body: Value = resp.json().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        if status >= 400 { return Err(map_error(status, &body)); }
+        Ok(body)
+    }
+
+    /// Sends a SIGNED request; everything travels in the query string, as Binance allows for
+    /// every verb. A clock-drift rejection (-1021) triggers one resync and retry.
+    async fn signed(&self, method: Method, path: &str, params: &[(&str, String)]) -> Result<Value, ExchangeError> {
+        for attempt in 0..2 {
+            let timestamp = Utc::now().timestamp_millis() + self.time_offset_ms.load(Ordering::Relaxed);
+            let mut query: Vec<String> = params.iter().map(|(k, v)| format!("{}={}", k, v)).collect();
+            query.push(format!("recvWindow={}", RECV_WINDOW_MS));
+            query.push(format!("timestamp={}", timestamp));
+            let query = query.join("&");
+            let url = format!("{}{}?{}&signature={}", self.rest_url, path, query, self.creds.sign(&query));
+
+            let resp = self.http.request(method.clone(), &url)
+                .header("X-MBX-APIKEY", &self.creds.api_key)
+                .send().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+            let status = resp.status().as_u16();
+            let body: Value = resp.json().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+            if status < 400 { return Ok(body); }
+            if body["code"].as_i64() == Some(-1021) && attempt == 0 {
+                self.sync_time().await?;
+                continue;
+            }
+            return Err(map_error(status, &body));
+        }
+        Err(ExchangeError::Other("unreachable retry loop".to_string()))
+    }
+
+    fn order_from_rest(&self, body: &Value) -> OrderState {
+        let cid = body["clientOrderId"].as_str().unwrap_or_default().to_string();
+        // REST never reports commission; the stream does, so keep whatever it has seen
+        let fee_paid = self.stream_orders.lock().unwrap().get(&cid).map(|(_, o)| o.fee_paid).unwrap_or(0.0);
+        let price = num(&body["price"]);
+        OrderState {
+            venue: "binance".to_string(),
+            client_order_id: cid,
+            exchange_order_id: body["orderId"].as_i64().map(|id| id.to_string()).unwrap_or_default(),
+            symbol: body["symbol"].as_str().unwrap_or_default().to_string(),
+            side: if body["side"].as_str() == Some("BUY") { Side::Buy } else { Side::Sell },
+            qty: num(&body["origQty"]),
+            price: (price > 0.0).then_some(price),
+            filled_qty: num(&body["executedQty"]),
+            avg_fill_price: num(&body["avgPrice"]),
+            fee_paid,
+            status: parse_status(body["status"].as_str().unwrap_or_default()),
+            updated_at: body["updateTime"].as_i64().and_then(DateTime::from_timestamp_millis).unwrap_or_else(Utc::now),
+        }
+    }
+
+    // --- 📡 USER DATA STREAM ---
+
+    async fn listen_key(&self, method: Method) -> Result<String, ExchangeError> {
+        let resp = self.http.request(method, format!("{}/fapi/v1/listenKey", self.rest_url))
+            .header("X-MBX-APIKEY", &self.creds.api_key)
+            .send().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        let status = resp.status().as_u16();
+        let body: Value = resp.json().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        if status >= 400 { return Err(map_error(status, &body)); }
+        Ok(body["listenKey"].as_str().unwrap_or_default().to_string())
+    }
+
+    /// Keeps a listenKey stream open, folding ORDER_TRADE_UPDATEs into the order cache.
+    fn spawn_user_stream(self: &Arc<Self>) {
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let key = match client.listen_key(Method::POST).await {
+                    Ok(key) => key,
+                    Err(e) => {
+                        println!("⚠️ [Binance] listenKey: {}", e);
+                        tokio::time::sleep(Duration::from_secs(5)).await;
+                        continue;
+                    }
+                };
+                let Ok((ws, _)) = connect_async(format!("{}/ws/{}", client.ws_url, key)).await else {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                };
+                println!("🔐 [Binance] User-data stream connected");
+                client.stream_session.fetch_add(1, Ordering::Relaxed);
+                client.stream_live.store(true, Ordering::Relaxed);
+                let (mut write, mut read) = ws.split();
+                let mut keepalive = interval(Duration::from_secs(LISTEN_KEY_KEEPALIVE_SECS));
+                keepalive.tick().await;
+
+                loop {
+                    tokio::select! {
+                        msg = read.next() => match msg {
+                            Some(Ok(Message::Text(text))) => {
+                                let Ok(event) = serde_json::from_str::<Value>(&text) else { continue };
+                                match event["e"].as_str() {
+                                    Some("ORDER_TRADE_UPDATE") => client.on_order_update(&event["o"]).await,
+                                    Some("listenKeyExpired") => break,
+                                    _ => {}
+                                }
+                            }
+                            Some(Ok(Message::Ping(payload))) => { let _ = write.send(Message::Pong(payload)).await; }
+                            Some(Ok(_)) => {}
+                            _ => break,
+                        },
+                        _ = keepalive.tick() => {
+                            if let Err(e) = client.listen_key(Method::PUT).await { println!("⚠️ [Binance] keepalive: {}", e); }
+                        }
+                    }
+                }
+                client.stream_live.store(false, Ordering::Relaxed);
+                println!("🔌 [Binance] User-data stream dropped, reconnecting");
+            }
+        });
+    }
+
+    async fn on_order_update(&self, o: &Value) {
+        let cid = o["c"].as_str().unwrap_or_default().to_string();
+        let symbol = o["s"].as_str().unwrap_or_default().to_string();
+        // `n` is the commission of this trade only, in asset `N`. Paying in BNB for the discount
+        // makes it a BNB amount, which is booked at BNB's price like every other quote-currency fee
+        let commission = num(&o["n"]);
+        let fee = match o["N"].as_str() {
+            Some(asset) if commission != 0.0 && !symbol.ends_with(asset) => match self.fee_asset_price(asset).await {
+                Some(price) => commission * price,
+                None => {
+                    println!("⚠️ [Binance] No {} price, leaving {} {} of fees off {}", asset, commission, asset, cid);
+                    0.0
+                }
+            },
+            _ => commission,
+        };
+        let mut orders = self.stream_orders.lock().unwrap();
+        let fee_so_far = orders.get(&cid).map(|(_, s)| s.fee_paid).unwrap_or(0.0);
+        let price = num(&o["p"]);
+        let state = OrderState {
+            venue: "binance".to_string(),
+            client_order_id: cid.clone(),
+            exchange_order_id: o["i"].as_i64().map(|id| id.to_string()).unwrap_or_default(),
+            symbol,
+            side: if o["S"].as_str() == Some("BUY") { Side::Buy } else { Side::Sell },
+            qty: num(&o["q"]),
+            price: (price > 0.0).then_some(price),
+            filled_qty: num(&o["z"]),
+            avg_fill_price: num(&o["ap"]),
+            fee_paid: fee_so_far + fee,
+            status: parse_status(o["X"].as_str().unwrap_or_default()),
+            updated_at: o["T"].as_i64().and_then(DateTime::from_timestamp_millis).unwrap_or_else(Utc::now),
+        };
+        orders.insert(cid, (self.stream_session.load(Ordering::Relaxed), state));
+    }
+
+    /// The cached state, if the stream can vouch for it: pushed this session, or already final.
+    fn cached_order(&self, client_order_id: &str) -> Option<OrderState> {
+        if !self.stream_live.load(Ordering::Relaxed) { return None; }
+        let session = self.stream_session.load(Ordering::Relaxed);
+        let orders = self.stream_orders.lock().unwrap();
+        let (seen_in, state) = orders.get(client_order_id)?;
+        (*seen_in == session || state.status.is_terminal()).then(|| state.clone())
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for BinanceFutures {
+    fn venue(&self) -> &str {
+        "binance"
+    }
+
+    async fn place_order(&self, req: &OrderRequest) -> Result<OrderState, ExchangeError> {
+        check_client_order_id(&req.client_order_id)?;
+        let filters = self.filters.lock().unwrap().get(&req.symbol).copied();
+        let filters = filters.ok_or_else(|| ExchangeError::Rejected(format!("unknown symbol {}", req.symbol)))?;
+        let mut params = vec![
+            ("symbol", req.symbol.clone()),
+            ("side", if req.side == Side::Buy { "BUY" } else { "SELL" }.to_string()),
+            ("quantity", to_step(req.qty, filters.step)),
+            ("newClientOrderId", req.client_order_id.clone()),
+            ("newOrderRespType", "RESULT".to_string()),
+        ];
+        match req.price {
+            Some(price) => {
+                params.push(("type", "LIMIT".to_string()));
+                params.push(("price", to_tick(price, filters.tick, req.side)));
+                params.push(("timeInForce", match req.tif { TimeInForce::Gtc => "GTC", TimeInForce::PostOnly => "GTX", TimeInForce::Ioc => "IOC" }.to_string()));
+            }
+            None => params.push(("type", "MARKET".to_string())),
+        }
+        if req.reduce_only { params.push(("reduceOnly", "true".to_string())); }
+
+        let body = self.signed(Method::POST, "/fapi/v1/order", &params).await?;
+        Ok(self.order_from_rest(&body))
+    }
+
+    async fn cancel_order(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let params = [("symbol", symbol.to_string()), ("origClientOrderId", client_order_id.to_string())];
+        let body = self.signed(Method::DELETE, "/fapi/v1/order", &params).await?;
+        Ok(self.order_from_rest(&body))
+    }
+
+    async fn order_status(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        // While the stream is up it is both fresher and free of rate limits
+        if let Some(state) = self.cached_order(client_order_id) { return Ok(state); }
+        let params = [("symbol", symbol.to_string()), ("origClientOrderId", client_order_id.to_string())];
+        let body = self.signed(Method::GET, "/fapi/v1/order", &params).await?;
+        Ok(self.order_from_rest(&body))
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ExchangeError> {
+        let body = self.signed(Method::GET, "/fapi/v2/positionRisk", &[]).await?;
+        Ok(body.as_array().into_iter().flatten().filter(|p| num(&p["positionAmt"]) != 0.0).map(|p| {
+            let liquidation = num(&p["liquidationPrice"]);
+            VenuePosition {
+                venue: "binance".to_string(),
+                symbol: p["symbol"].as_str().unwrap_or_default().to_string(),
+                qty: num(&p["positionAmt"]),
+                entry_price: num(&p["entryPrice"]),
+                unrealized_pnl: num(&p["unRealizedProfit"]),
+                liquidation_price: (liquidation > 0.0).then_some(liquidation),
+            }
+        }).collect())
+    }
+
+    async fn balance(&self) -> Result<Balance, ExchangeError> {
+        let body = self.signed(Method::GET, "/fapi/v2/balance", &[]).await?;
+        let usdt = body.as_array().into_iter().flatten().find(|b| b["asset"].as_str() == Some("USDT"))
+            .ok_or_else(|| ExchangeError::Other("no USDT balance".to_string()))?;
+        let equity = num(&usdt["balance"]) + num(&usdt["crossUnPnl"]);
+        let available = num(&usdt["availableBalance"]);
+        Ok(Balance { equity, available, margin_used: (equity - available).max(0.0) })
+    }
+
+    /// `/fapi/v1/income` books the amount only; rate and position size are not reported.
+    async fn funding_history(&self, since: DateTime<Utc>) -> Result<Vec<FundingIncome>, ExchangeError> {
+        let mut incomes = Vec::new();
+        let mut start = since.timestamp_millis();
+        loop {
+            let params = [
+                ("incomeType", "FUNDING_FEE".to_string()),
+                ("startTime", start.to_string()),
+                ("limit", INCOME_PAGE.to_string()),
+            ];
+            let body = self.signed(Method::GET, "/fapi/v1/income", &params).await?;
+            let page = body.as_array().cloned().unwrap_or_default();
+            for item in &page {
+                let time = item["time"].as_i64().unwrap_or(0);
+                start = start.max(time + 1);
+                incomes.push(FundingIncome {
+                    venue: "binance".to_string(),
+                    symbol: item["symbol"].as_str().unwrap_or_default().to_string(),
+                    amount: num(&item["income"]),
+                    ts: DateTime::from_timestamp_millis(time).unwrap_or(since),
+                    rate: None,
+                    qty: None,
+                });
+            }
+            if page.len() < INCOME_PAGE { break; }
+        }
+        Ok(incomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::mock_server::{eventually, MockHttp, MockWs};
+    use serde_json::json;
+    use std::sync::atomic::AtomicUsize;
+
+    fn client(http: &MockHttp, ws_url: &str) -> Arc<BinanceFutures> {
+        let client = BinanceFutures {
+            rest_url: http.url.clone(),
+            ws_url: ws_url.to_string(),
+            ..BinanceFutures::new(BinanceCredentials { api_key: "key".to_string(), secret: "secret".to_string() })
+        };
+        client.filters.lock().unwrap().insert("BTCUSDT".to_string(), SymbolFilters { step: 0.001, tick: 0.1 });
+        Arc::new(client)
+    }
+
+    fn rest_order(cid: &str, status: &str, executed: &str) -> Value {
+        json!({
+            "orderId": 42, "clientOrderId": cid, "symbol": "BTCUSDT", "side": "SELL", "origQty": "0.010",
+            "price": "60000.5", "executedQty": executed, "avgPrice": "60000.5", "status": status, "updateTime": 1_700_000_000_000i64,
+        })
+    }
+
+    fn trade_update(cid: &str, status: &str, filled: &str, fee: &str) -> Value {
+        json!({"e": "ORDER_TRADE_UPDATE", "o": {
+            "s": "BTCUSDT", "c": cid, "S": "SELL", "q": "0.010", "p": "60000.5", "X": status, "i": 42,
+            "z": filled, "ap": "60000.5", "n": fee, "T": 1_700_000_000_000i64,
+        }})
+    }
+
+    fn limit_sell(cid: &str) -> OrderRequest {
+        OrderRequest {
+            client_order_id: cid.to_string(), symbol: "BTCUSDT".to_string(), side: Side::Sell,
+            qty: 0.0109, price: Some(60000.41), tif: TimeInForce::PostOnly, reduce_only: true,
+        }
+    }
+
+    #[test]
+    fn signatures_match_the_documented_example() {
+        let secret = "NhqPtmdSJYdKjVHjA7PZj4Mge3R5YNiP1e3UZjInClVN65XAbvqqM6A7H5fATj0j";
+        let query = "symbol=LTCBTC&side=BUY&type=LIMIT&timeInForce=GTC&quantity=1&price=0.1&recvWindow=5000&timestamp=1499827319559";
+        let creds = BinanceCredentials { api_key: String::new(), secret: secret.to_string() };
+        assert_eq!(creds.sign(query), "c8db56825ae71d6d79447849e617115f4a920fa2acdcab2b053c4b2838bd6b71");
+    }
+
+    #[tokio::test]
+    async fn orders_are_signed_over_the_query_string() {
+        let http = MockHttp::start(|_| (200, rest_order("cid-1", "NEW", "0"))).await;
+        let client = client(&http, "ws://unused");
+        let state = client.place_order(&limit_sell("cid-1")).await.unwrap();
+
+        let sent = &http.hits("POST", "/fapi/v1/order")[0];
+        assert_eq!(sent.header("X-MBX-APIKEY"), Some("key"));
+        let (payload, signature) = sent.query.rsplit_once("&signature=").unwrap();
+        assert_eq!(signature, hmac_sha256_hex("secret", payload));
+        for (key, value) in [
+            ("symbol", "BTCUSDT"), ("side", "SELL"), ("quantity", "0.010"), ("newClientOrderId", "cid-1"),
+            ("type", "LIMIT"), ("price", "60000.5"), ("timeInForce", "GTX"), ("reduceOnly", "true"), ("recvWindow", "5000"),
+        ] {
+            assert_eq!(sent.param(key), Some(value), "{}", key);
+        }
+        assert!(sent.param("timestamp").is_some());
+
+        assert_eq!(state.exchange_order_id, "42");
+        assert_eq!(state.side, Side::Sell);
+        assert_eq!(state.qty, 0.01);
+        assert_eq!(state.price, Some(60000.5));
+        assert_eq!(state.status, OrderStatus::New);
+    }
+
+    #[tokio::test]
+    async fn clock_drift_resyncs_and_retries_once() {
+        let server_time = Utc::now().timestamp_millis() + 60_000;
+        let gets = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&gets);
+        let http = MockHttp::start(move |r| match (r.method.as_str(), r.path.as_str()) {
+            ("GET", "/fapi/v1/time") => (200, json!({"serverTime": server_time})),
+            ("GET", "/fapi/v1/order") if counter.fetch_add(1, Ordering::Relaxed) == 0 => (400, json!({"code": -1021, "msg": "Timestamp outside of the recvWindow."})),
+            ("GET", "/fapi/v1/order") => (200, rest_order("cid-1", "FILLED", "0.010")),
+            _ => (400, json!({"code": -1021, "msg": "Timestamp outside of the recvWindow."})),
+        }).await;
+        let client = client(&http, "ws://unused");
+
+        let state = client.order_status("BTCUSDT", "cid-1").await.unwrap();
+        assert_eq!(state.status, OrderStatus::Filled);
+        let attempts = http.hits("GET", "/fapi/v1/order");
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(http.hits("GET", "/fapi/v1/time").len(), 1);
+        let stamp = |i: usize| attempts[i].param("timestamp").unwrap().parse::<i64>().unwrap();
+        assert!(stamp(1) - stamp(0) > 50_000, "retry must use the resynced clock");
+
+        // A second rejection is not retried again
+        let err = client.cancel_order("BTCUSDT", "cid-1").await.unwrap_err();
+        assert!(matches!(err, ExchangeError::Rejected(msg) if msg.starts_with("-1021")));
+        assert_eq!(http.hits("DELETE", "/fapi/v1/order").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn cancels_positions_and_balances_parse() {
+        let http = MockHttp::start(|r| match r.path.as_str() {
+            "/fapi/v1/order" => (200, rest_order("cid-1", "CANCELED", "0.004")),
+            "/fapi/v2/positionRisk" => (200, json!([
+                {"symbol": "BTCUSDT", "positionAmt": "-0.010", "entryPrice": "60000.0", "unRealizedProfit": "-1.5", "liquidationPrice": "80000"},
+                {"symbol": "ETHUSDT", "positionAmt": "0.000", "entryPrice": "0.0", "unRealizedProfit": "0", "liquidationPrice": "0"},
+                {"symbol": "SOLUSDT", "positionAmt": "2", "entryPrice": "150", "unRealizedProfit": "3", "liquidationPrice": "0"},
+            ])),
+            "/fapi/v2/balance" => (200, json!([
+                {"asset": "BNB", "balance": "5", "crossUnPnl": "0", "availableBalance": "5"},
+                {"asset": "USDT", "balance": "1000", "crossUnPnl": "-20", "availableBalance": "700"},
+            ])),
+            _ => (404, json!({"code": -1, "msg": "unexpected"})),
+        }).await;
+        let client = client(&http, "ws://unused");
+
+        let canceled = client.cancel_order("BTCUSDT", "cid-1").await.unwrap();
+        assert_eq!(canceled.status, OrderStatus::Canceled);
+        assert_eq!(canceled.filled_qty, 0.004);
+        assert_eq!(http.hits("DELETE", "/fapi/v1/order")[0].param("origClientOrderId"), Some("cid-1"));
+
+        let positions = client.positions().await.unwrap();
+        assert_eq!(positions.len(), 2, "flat symbols are skipped");
+        assert_eq!((positions[0].symbol.as_str(), positions[0].qty, positions[0].entry_price), ("BTCUSDT", -0.01, 60000.0));
+        assert_eq!((positions[0].unrealized_pnl, positions[0].liquidation_price), (-1.5, Some(80000.0)));
+        assert_eq!((positions[1].qty, positions[1].liquidation_price), (2.0, None));
+
+        let balance = client.balance().await.unwrap();
+        assert_eq!((balance.equity, balance.available, balance.margin_used), (980.0, 700.0, 280.0));
+    }
+
+    #[tokio::test]
+    async fn error_bodies_map_to_exchange_errors() {
+        let cases = [
+            (429, json!({"code": -1003, "msg": "Too many requests"})),
+            (418, json!({"code": -1003, "msg": "IP banned"})),
+            (400, json!({"code": -2013, "msg": "Order does not exist."})),
+            (400, json!({"code": -2011, "msg": "Unknown order sent."})),
+            (401, json!({"code": -2015, "msg": "Invalid API-key"})),
+            (400, json!({"code": -1022, "msg": "Signature invalid"})),
+            (503, json!({"code": -1001, "msg": "Internal error"})),
+            (400, json!({"code": -5022, "msg": "Post Only order will be rejected"})),
+        ];
+        let labels: Vec<String> = cases.iter().map(|(http, body)| match map_error(*http, body) {
+            ExchangeError::RateLimited => "rate".to_string(),
+            ExchangeError::NotFound => "missing".to_string(),
+            ExchangeError::Auth(_) => "auth".to_string(),
+            ExchangeError::Network(_) => "network".to_string(),
+            ExchangeError::Rejected(msg) => msg,
+            ExchangeError::Other(msg) => msg,
+        }).collect();
+        assert_eq!(labels, ["rate", "rate", "missing", "missing", "auth", "auth", "network", "-5022 Post Only order will be rejected"]);
+
+        // Same mapping on the wire, and IDs the venue would refuse never leave the process
+        let http = MockHttp::start(|_| (400, json!({"code": -2013, "msg": "Order does not exist."}))).await;
+        let client = client(&http, "ws://unused");
+        assert!(matches!(client.order_status("BTCUSDT", "cid-1").await, Err(ExchangeError::NotFound)));
+        let overlong = limit_sell(&"x".repeat(37));
+        assert!(matches!(client.place_order(&overlong).await, Err(ExchangeError::Rejected(_))));
+        assert!(http.hits("POST", "/fapi/v1/order").is_empty());
+    }
+
+    #[tokio::test]
+    async fn user_stream_caches_updates_until_a_reconnect() {
+        let http = MockHttp::start(|r| match (r.method.as_str(), r.path.as_str()) {
+            ("POST", "/fapi/v1/listenKey") => (200, json!({"listenKey": "lk-1"})),
+            ("PUT", "/fapi/v1/listenKey") => (200, json!({})),
+            ("GET", "/fapi/v1/order") => (200, rest_order("cid-open", "PARTIALLY_FILLED", "0.006")),
+            _ => (404, json!({"code": -1, "msg": "unexpected"})),
+        }).await;
+        let mut ws = MockWs::start().await;
+        let client = client(&http, &ws.url);
+        client.spawn_user_stream();
+
+        let conn = ws.accept().await;
+        assert_eq!(conn.path, "/ws/lk-1");
+        let create = &http.hits("POST", "/fapi/v1/listenKey")[0];
+        assert_eq!(create.header("X-MBX-APIKEY"), Some("key"));
+        assert!(create.param("signature").is_none(), "listenKey calls are keyed, not signed");
+
+        conn.send(trade_update("cid-open", "PARTIALLY_FILLED", "0.002", "0.012"));
+        conn.send(trade_update("cid-open", "PARTIALLY_FILLED", "0.004", "0.012"));
+        conn.send(trade_update("cid-done", "FILLED", "0.010", "0.03"));
+        eventually(|| client.cached_order("cid-done").is_some()).await;
+
+        let open = client.order_status("BTCUSDT", "cid-open").await.unwrap();
+        assert_eq!((open.filled_qty, open.fee_paid, open.status), (0.004, 0.024, OrderStatus::PartiallyFilled));
+        assert!(http.hits("GET", "/fapi/v1/order").is_empty(), "served from the stream");
+
+        client.listen_key(Method::PUT).await.unwrap();
+        assert_eq!(http.hits("PUT", "/fapi/v1/listenKey")[0].header("X-MBX-APIKEY"), Some("key"));
+
+        // Updates sent while the socket was down are lost: open orders go back to REST
+        drop(conn);
+        let _conn = ws.accept().await;
+        eventually(|| client.stream_session.load(Ordering::Relaxed) == 2 && client.stream_live.load(Ordering::Relaxed)).await;
+        assert_eq!(http.hits("POST", "/fapi/v1/listenKey").len(), 2);
+
+        let done = client.order_status("BTCUSDT", "cid-done").await.unwrap();
+        assert_eq!(done.status, OrderStatus::Filled);
+        assert!(http.hits("GET", "/fapi/v1/order").is_empty(), "final states stay cached");
+        let open = client.order_status("BTCUSDT", "cid-open").await.unwrap();
+        assert_eq!(http.hits("GET", "/fapi/v1/order").len(), 1);
+        assert_eq!((open.filled_qty, open.fee_paid), (0.006, 0.024));
+    }
+
+    #[tokio::test]
+    async fn bnb_commissions_are_booked_at_the_bnb_price() {
+        let http = MockHttp::start(|r| match (r.method.as_str(), r.path.as_str()) {
+            ("POST", "/fapi/v1/listenKey") => (200, json!({"listenKey": "lk-1"})),
+            ("GET", "/fapi/v1/ticker/price") => (200, json!({"symbol": "BNBUSDT", "price": "600.0"})),
+            _ => (404, json!({"code": -1, "msg": "unexpected"})),
+        }).await;
+        let mut ws = MockWs::start().await;
+        let client = client(&http, &ws.url);
+        client.spawn_user_stream();
+        let conn = ws.accept().await;
+
+        let in_asset = |update: Value, asset: &str| {
+            let mut update = update;
+            update["o"]["N"] = json!(asset);
+            update
+        };
+        conn.send(in_asset(trade_update("cid-bnb", "PARTIALLY_FILLED", "0.005", "0.00005"), "BNB"));
+        conn.send(in_asset(trade_update("cid-bnb", "FILLED", "0.010", "0.00005"), "BNB"));
+        conn.send(in_asset(trade_update("cid-usdt", "FILLED", "0.010", "0.024"), "USDT"));
+        eventually(|| client.cached_order("cid-usdt").is_some()).await;
+
+        let bnb = client.cached_order("cid-bnb").unwrap();
+        assert!((bnb.fee_paid - 0.06).abs() < 1e-12, "0.0001 BNB at $600, not 0.0001 USDT");
+        assert_eq!(client.cached_order("cid-usdt").unwrap().fee_paid, 0.024);
+        let lookups = http.hits("GET", "/fapi/v1/ticker/price");
+        assert_eq!(lookups.len(), 1, "the price is cached between trades");
+        assert_eq!(lookups[0].param("symbol"), Some("BNBUSDT"));
+    }
+}
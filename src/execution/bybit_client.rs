@@ -0,0 +1,750 @@
+use super::exchange::{check_client_order_id, hmac_sha256_hex, num, to_step, to_tick, Balance, ExchangeClient, ExchangeError, FundingIncome, OrderRequest, OrderState, OrderStatus, TimeInForce, VenuePosition};
+use crate::ingestors::signals::Side;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{SinkExt, StreamExt};
+use reqwest::Method;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{interval, Duration};
+use tokio_tungstenite::{connect_async, tungstenite::protocol::Message};
+
+const DEFAULT_REST_URL: &str = "https://api.bybit.com";
+const DEFAULT_PRIVATE_WS_URL: &str = "wss://stream.bybit.com/v5/private";
+const RECV_WINDOW_MS: u64 = 5000;
+/// Bybit drops private sockets that stay silent for 20s.
+const WS_PING_SECS: u64 = 20;
+const TIME_SYNC_SECS: u64 = 5 * 60;
+const PRIVATE_TOPICS: [&str; 4] = ["order", "execution", "position", "wallet"];
+
+// --- 🔑 CREDENTIALS ---
+
+pub struct BybitCredentials {
+    pub api_key: String,
+    pub secret: String,
+}
+
+impl BybitCredentials {
+    /// `BYBIT_API_KEY` / `BYBIT_API_SECRET`, if both are set.
+    pub fn from_env() -> Option<Self> {
+        Some(Self { api_key: std::env::var("BYBIT_API_KEY").ok()?, secret: std::env::var("BYBIT_API_SECRET").ok()? })
+    }
+}
+
+/// Quantity step and price tick from `instruments-info`.
+#[derive(Debug, Clone, Copy)]
+struct SymbolFilters {
+    step: f64,
+    tick: f64,
+}
+
+fn parse_side(raw: &Value) -> Side {
+    if raw.as_str() == Some("Buy") { Side::Buy } else { Side::Sell }
+}
+
+fn parse_status(raw: &str) -> OrderStatus {
+    match raw {
+        "New" | "Created" | "Untriggered" => OrderStatus::New,
+        "PartiallyFilled" => OrderStatus::PartiallyFilled,
+        "Filled" => OrderStatus::Filled,
+        "Rejected" => OrderStatus::Rejected,
+        "Cancelled" | "PartiallyFilledCanceled" => OrderStatus::Canceled,
+        _ => OrderStatus::Expired, // Deactivated, Triggered
+    }
+}
+
+fn millis(raw: &Value) -> DateTime<Utc> {
+    raw.as_str().and_then(|s| s.parse().ok()).and_then(DateTime::from_timestamp_millis).unwrap_or_else(Utc::now)
+}
+
+/// Maps an HTTP status plus v5 `retCode` to our error type.
+fn map_error(http: u16, body: &Value) -> ExchangeError {
+    let code = body["retCode"].as_i64().unwrap_or(0);
+    let msg = body["retMsg"].as_str().unwrap_or("").to_string();
+    match (http, code) {
+        // 403 is how Bybit reports an IP over its request limit
+        (403 | 429, _) | (_, 10006 | 10018) => ExchangeError::RateLimited,
+        (_, 110001 | 110008 | 110010) => ExchangeError::NotFound,
+        (401, _) | (_, 10003 | 10004 | 10005 | 10007 | 10009 | 33004) => ExchangeError::Auth(msg),
+        (500..=599, _) | (_, 10000 | 10016) => ExchangeError::Network(format!("{} {}", code, msg)),
+        _ => ExchangeError::Rejected(format!("{} {}", code, msg)),
+    }
+}
+
+fn order_state(o: &Value) -> OrderState {
+    let price = num(&o["price"]);
+    OrderState {
+        venue: "bybit".to_string(),
+        client_order_id: o["orderLinkId"].as_str().unwrap_or_default().to_string(),
+        exchange_order_id: o["orderId"].as_str().unwrap_or_default().to_string(),
+        symbol: o["symbol"].as_str().unwrap_or_default().to_string(),
+        side: parse_side(&o["side"]),
+        qty: num(&o["qty"]),
+        price: (price > 0.0).then_some(price),
+        filled_qty: num(&o["cumExecQty"]),
+        avg_fill_price: num(&o["avgPrice"]),
+        fee_paid: num(&o["cumExecFee"]),
+        status: parse_status(o["orderStatus"].as_str().unwrap_or_default()),
+        updated_at: millis(&o["updatedTime"]),
+    }
+}
+
+fn position(p: &Value) -> VenuePosition {
+    let size = num(&p["size"]);
+    let liquidation = num(&p["liqPrice"]);
+    VenuePosition {
+        venue: "bybit".to_string(),
+        symbol: p["symbol"].as_str().unwrap_or_default().to_string(),
+        qty: if p["side"].as_str() == Some("Sell") { -size } else { size },
+        entry_price: num(if p["entryPrice"].is_null() { &p["avgPrice"] } else { &p["entryPrice"] }),
+        unrealized_pnl: num(&p["unrealisedPnl"]),
+        liquidation_price: (liquidation > 0.0).then_some(liquidation),
+    }
+}
+
+/// Unified-account totals, from either the REST wallet or the `wallet` topic.
+fn wallet_balance(account: &Value) -> Balance {
+    Balance {
+        equity: num(&account["totalEquity"]),
+        available: num(&account["totalAvailableBalance"]),
+        margin_used: num(&account["totalInitialMargin"]),
+    }
+}
+
+/// What the private stream has told us, served instead of REST while the socket is up.
+#[derive(Default)]
+struct StreamCache {
+    orders: HashMap<String, OrderState>,
+    /// Seeded from REST on first use, then kept current by the `position` topic.
+    positions: Option<HashMap<String, VenuePosition>>,
+    balance: Option<Balance>,
+}
+
+// --- 🏦 CLIENT ---
+
+/// v5 linear perpetuals on a unified account: signed REST for trading and the private
+/// socket for order, execution, position and wallet updates.
+///
+/// `BYBIT_API_URL` / `BYBIT_PRIVATE_WS_URL` override the endpoints, e.g. to point at a
+/// local mock server or the testnet.
+pub struct BybitLinear {
+    http: reqwest::Client,
+    rest_url: String,
+    ws_url: String,
+    creds: BybitCredentials,
+    /// Server time minus local time, refreshed periodically.
+    time_offset_ms: AtomicI64,
+    filters: Mutex<HashMap<String, SymbolFilters>>,
+    cache: Mutex<StreamCache>,
+    stream_live: AtomicBool,
+}
+
+impl BybitLinear {
+    pub fn new(creds: BybitCredentials) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            rest_url: std::env::var("BYBIT_API_URL").unwrap_or_else(|_| DEFAULT_REST_URL.to_string()),
+            ws_url: std::env::var("BYBIT_PRIVATE_WS_URL").unwrap_or_else(|_| DEFAULT_PRIVATE_WS_URL.to_string()),
+            creds,
+            time_offset_ms: AtomicI64::new(0),
+            filters: Mutex::new(HashMap::new()),
+            cache: Mutex::new(StreamCache::default()),
+            stream_live: AtomicBool::new(false),
+        }
+    }
+
+    /// Syncs the clock and loads symbol filters; call once before trading.
+    pub async fn connect(self: &Arc<Self>) -> Result<(), ExchangeError> {
+        self.sync_time().await?;
+        self.load_filters().await?;
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut timer = interval(Duration::from_secs(TIME_SYNC_SECS));
+            loop {
+                timer.tick().await;
+                if let Err(e) = client.sync_time().await { println!("⚠️ [Bybit] time sync: {}", e); }
+            }
+        });
+        self.spawn_private_stream();
+        Ok(())
+    }
+
+    /// Estimates the server clock offset from `/v5/market/time`, halving the round trip.
+    pub async fn sync_time(&self) -> Result<(), ExchangeError> {
+        let sent = Utc::now().timestamp_millis();
+        let body = self.public("/v5/market/time", "").await?;
+        let received = Utc::now().timestamp_millis();
+        let server = body["time"].as_i64().ok_or_else(|| ExchangeError::Other("no server time".to_string()))?;
+        self.time_offset_ms.store(server - (sent + received) / 2, Ordering::Relaxed);
+        Ok(())
+    }
+
+    async fn load_filters(&self) -> Result<(), ExchangeError> {
+        let mut filters = HashMap::new();
+        let mut cursor = String::new();
+        loop {
+            let query = format!("category=linear&limit=1000&cursor={}", cursor);
+            let body = self.public("/v5/market/instruments-info", &query).await?;
+            for symbol in body["result"]["list"].as_array().into_iter().flatten() {
+                let Some(name) = symbol["symbol"].as_str() else { continue };
+                filters.insert(name.to_string(), SymbolFilters {
+                    step: num(&symbol["lotSizeFilter"]["qtyStep"]),
+                    tick: num(&symbol["priceFilter"]["tickSize"]),
+                });
+            }
+            match body["result"]["nextPageCursor"].as_str() {
+                Some(next) if !next.is_empty() => cursor = next.to_string(),
+                _ => break,
+            }
+        }
+        *self.filters.lock().unwrap() = filters;
+        Ok(())
+    }
+
+    async fn public(&self, path: &str, query: &str) -> Result<Value, ExchangeError> {
+        let resp = self.http.get(format!("{}{}?{}", self.rest_url, path, query))
+            .send().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        Self::read(resp).await
+    }
+
+    /// v5 reports most failures as HTTP 200 with a non-zero `retCode`.
+    async fn read(resp: reqwest::Response) -> Result<Value, ExchangeError> {
+        let status = resp.status().as_u16();
+        let body: Value = resp.json().await.unwrap_or(Value::Null);
+        if status >= 400 || body["retCode"].as_i64().unwrap_or(0) != 0 { return Err(map_error(status, &body)); }
+        Ok(body)
+    }
+
+    /// Signs `timestamp + key + recvWindow + payload`, where the payload is the query string
+    /// for GETs and the exact JSON body for POSTs. A timestamp rejection (10002) triggers one
+    /// resync and retry.
+    async fn signed(&self, method: Method, path: &str, query: &str, body: Option<&Value>) -> Result<Value, ExchangeError> {
+        let payload = body.map(|b| b.to_string()).unwrap_or_else(|| query.to_string());
+        for attempt in 0..2 {
+            let timestamp = Utc::now().timestamp_millis() + self.time_offset_ms.load(Ordering::Relaxed);
+            let prehash = format!("{}{}{}{}", timestamp, self.creds.api_key, RECV_WINDOW_MS, payload);
+            let mut request = self.http.request(method.clone(), format!("{}{}?{}", self.rest_url, path, query))
+                .header("X-BAPI-API-KEY", &self.creds.api_key)
+                .header("X-BAPI-TIMESTAMP", timestamp.to_string())
+                .header("X-BAPI-RECV-WINDOW", RECV_WINDOW_MS.to_string())
+                .header("X-BAPI-SIGN", hmac_sha256_hex(&self.creds.secret, &prehash));
+            if body.is_some() {
+                request = request.header("Content-Type", "application/json").body(payload.clone());
+            }
+            let resp = request.send().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+            let status = resp.status().as_u16();
+            let json: Value = resp.json().await.unwrap_or(Value::Null);
+            let code = json["retCode"].as_i64().unwrap_or(0);
+            if status < 400 && code == 0 { return Ok(json); }
+            if code == 10002 && attempt == 0 {
+                self.sync_time().await?;
+                continue;
+            }
+            return Err(map_error(status, &json));
+        }
+        Err(ExchangeError::Other("unreachable retry loop".to_string()))
+    }
+
+    async fn fetch_order(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let query = format!("category=linear&symbol={}&orderLinkId={}", symbol, client_order_id);
+        let body = self.signed(Method::GET, "/v5/order/realtime", &query, None).await?;
+        body["result"]["list"].as_array().and_then(|list| list.first()).map(order_state).ok_or(ExchangeError::NotFound)
+    }
+
+    /// Create/amend/cancel only acknowledge; the stream (or a query) has the real state.
+    async fn acknowledged(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        if let Some(state) = self.cached_order(client_order_id) { return Ok(state); }
+        self.fetch_order(symbol, client_order_id).await
+    }
+
+    fn cached_order(&self, client_order_id: &str) -> Option<OrderState> {
+        if !self.stream_live.load(Ordering::Relaxed) { return None; }
+        self.cache.lock().unwrap().orders.get(client_order_id).cloned()
+    }
+
+    // --- 📡 PRIVATE STREAM ---
+
+    /// Keeps the private socket authenticated and subscribed, folding updates into the cache.
+    fn spawn_private_stream(self: &Arc<Self>) {
+        let client = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let Ok((ws, _)) = connect_async(client.ws_url.as_str()).await else {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                };
+                let (mut write, mut read) = ws.split();
+                let expires = Utc::now().timestamp_millis() + client.time_offset_ms.load(Ordering::Relaxed) + 10_000;
+                let signature = hmac_sha256_hex(&client.creds.secret, &format!("GET/realtime{}", expires));
+                let auth = json!({"op": "auth", "args": [client.creds.api_key, expires, signature]});
+                let subscribe = json!({"op": "subscribe", "args": PRIVATE_TOPICS});
+                if write.send(Message::Text(auth.to_string().into())).await.is_err()
+                    || write.send(Message::Text(subscribe.to_string().into())).await.is_err() {
+                    continue;
+                }
+                let mut ping = interval(Duration::from_secs(WS_PING_SECS));
+
+                loop {
+                    tokio::select! {
+                        msg = read.next() => match msg {
+                            Some(Ok(Message::Text(text))) => {
+                                let Ok(event) = serde_json::from_str::<Value>(&text) else { continue };
+                                if !client.on_message(&event) { break; }
+                            }
+                            Some(Ok(_)) => {}
+                            _ => break,
+                        },
+                        _ = ping.tick() => {
+                            if write.send(Message::Text(json!({"op": "ping"}).to_string().into())).await.is_err() { break; }
+                        }
+                    }
+                }
+                client.stream_live.store(false, Ordering::Relaxed);
+                // The topics only push changes, so whatever we missed must come from REST. Final order
+                // states cannot change any more and are kept.
+                {
+                    let mut cache = client.cache.lock().unwrap();
+                    cache.orders.retain(|_, order| order.status.is_terminal());
+                    cache.positions = None;
+                    cache.balance = None;
+                }
+                println!("🔌 [Bybit] Private stream dropped, reconnecting");
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            }
+        });
+    }
+
+    /// Returns false when the socket should be dropped (failed auth).
+    fn on_message(&self, event: &Value) -> bool {
+        match event["op"].as_str() {
+            Some("auth") => {
+                if event["success"].as_bool() != Some(true) {
+                    println!("❌ [Bybit] Private stream auth failed: {}", event["ret_msg"]);
+                    return false;
+                }
+                println!("🔐 [Bybit] Private stream connected");
+                self.stream_live.store(true, Ordering::Relaxed);
+                return true;
+            }
+            Some(_) => return true, // subscribe acks and pongs
+            None => {}
+        }
+
+        let data = event["data"].as_array().into_iter().flatten();
+        let mut cache = self.cache.lock().unwrap();
+        match event["topic"].as_str() {
+            Some("order") => {
+                for o in data.filter(|o| o["category"].as_str() == Some("linear")) {
+                    let state = order_state(o);
+                    cache.orders.insert(state.client_order_id.clone(), state);
+                }
+            }
+            Some("execution") => {
+                // Executions can beat the order update; advance the cached order so polling sees them
+                for e in data.filter(|e| e["category"].as_str() == Some("linear")) {
+                    let Some(order) = cache.orders.get_mut(e["orderLinkId"].as_str().unwrap_or_default()) else { continue };
+                    let cumulative = num(&e["orderQty"]) - num(&e["leavesQty"]);
+                    let qty = num(&e["execQty"]);
+                    if cumulative <= order.filled_qty + 1e-12 || qty <= 0.0 { continue; }
+                    order.avg_fill_price = (order.avg_fill_price * order.filled_qty + num(&e["execPrice"]) * qty) / (order.filled_qty + qty);
+                    order.filled_qty = cumulative;
+                    order.fee_paid += num(&e["execFee"]);
+                    order.status = if num(&e["leavesQty"]) <= 0.0 { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+                    order.updated_at = millis(&e["execTime"]);
+                }
+            }
+            Some("position") => {
+                let Some(positions) = cache.positions.as_mut() else { return true };
+                for p in data.filter(|p| p["category"].as_str() == Some("linear")) {
+                    let position = position(p);
+                    if position.qty == 0.0 {
+                        positions.remove(&position.symbol);
+                    } else {
+                        positions.insert(position.symbol.clone(), position);
+                    }
+                }
+            }
+            Some("wallet") => {
+                if let Some(account) = event["data"].as_array().and_then(|d| d.first()) {
+                    cache.balance = Some(wallet_balance(account));
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for BybitLinear {
+    fn venue(&self) -> &str {
+        "bybit"
+    }
+
+    async fn place_order(&self, req: &OrderRequest) -> Result<OrderState, ExchangeError> {
+        check_client_order_id(&req.client_order_id)?;
+        let filters = self.filters.lock().unwrap().get(&req.symbol).copied();
+        let filters = filters.ok_or_else(|| ExchangeError::Rejected(format!("unknown symbol {}", req.symbol)))?;
+        let qty = to_step(req.qty, filters.step);
+        let price = req.price.map(|price| to_tick(price, filters.tick, req.side));
+        let mut body = json!({
+            "category": "linear",
+            "symbol": req.symbol,
+            "side": if req.side == Side::Buy { "Buy" } else { "Sell" },
+            "qty": qty,
+            "orderLinkId": req.client_order_id,
+            "reduceOnly": req.reduce_only,
+        });
+        match &price {
+            Some(price) => {
+                body["orderType"] = json!("Limit");
+                body["price"] = json!(price);
+                body["timeInForce"] = json!(match req.tif { TimeInForce::Gtc => "GTC", TimeInForce::PostOnly => "PostOnly", TimeInForce::Ioc => "IOC" });
+            }
+            None => body["orderType"] = json!("Market"),
+        }
+
+        let ack = self.signed(Method::POST, "/v5/order/create", "", Some(&body)).await?;
+        // The create response carries only the IDs; report the order as accepted
+        Ok(OrderState {
+            venue: "bybit".to_string(),
+            client_order_id: req.client_order_id.clone(),
+            exchange_order_id: ack["result"]["orderId"].as_str().unwrap_or_default().to_string(),
+            symbol: req.symbol.clone(),
+            side: req.side,
+            qty: qty.parse().unwrap_or(req.qty),
+            price: price.and_then(|p| p.parse().ok()),
+            filled_qty: 0.0,
+            avg_fill_price: 0.0,
+            fee_paid: 0.0,
+            status: OrderStatus::New,
+            updated_at: Utc::now(),
+        })
+    }
+
+    async fn cancel_order(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let body = json!({"category": "linear", "symbol": symbol, "orderLinkId": client_order_id});
+        self.signed(Method::POST, "/v5/order/cancel", "", Some(&body)).await?;
+        // The stream may not have seen fills that beat the cancel, so read the order back. If the
+        // venue has not settled it yet either, it stays open and polling picks up the final state.
+        self.fetch_order(symbol, client_order_id).await
+    }
+
+    async fn amend_order(&self, symbol: &str, client_order_id: &str, qty: Option<f64>, price: Option<f64>) -> Result<OrderState, ExchangeError> {
+        let filters = self.filters.lock().unwrap().get(symbol).copied();
+        let filters = filters.ok_or_else(|| ExchangeError::Rejected(format!("unknown symbol {}", symbol)))?;
+        let mut body = json!({"category": "linear", "symbol": symbol, "orderLinkId": client_order_id});
+        if let Some(qty) = qty { body["qty"] = json!(to_step(qty, filters.step)); }
+        if let Some(price) = price {
+            let side = self.acknowledged(symbol, client_order_id).await?.side;
+            body["price"] = json!(to_tick(price, filters.tick, side));
+        }
+        self.signed(Method::POST, "/v5/order/amend", "", Some(&body)).await?;
+        self.fetch_order(symbol, client_order_id).await
+    }
+
+    async fn order_status(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        self.acknowledged(symbol, client_order_id).await
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ExchangeError> {
+        let live = self.stream_live.load(Ordering::Relaxed);
+        if live {
+            if let Some(positions) = &self.cache.lock().unwrap().positions { return Ok(positions.values().cloned().collect()); }
+        }
+        let body = self.signed(Method::GET, "/v5/position/list", "category=linear&settleCoin=USDT", None).await?;
+        let positions: Vec<VenuePosition> = body["result"]["list"].as_array().into_iter().flatten().map(position).filter(|p| p.qty != 0.0).collect();
+        if live {
+            self.cache.lock().unwrap().positions = Some(positions.iter().map(|p| (p.symbol.clone(), p.clone())).collect());
+        }
+        Ok(positions)
+    }
+
+    async fn balance(&self) -> Result<Balance, ExchangeError> {
+        if self.stream_live.load(Ordering::Relaxed) {
+            if let Some(balance) = self.cache.lock().unwrap().balance.clone() { return Ok(balance); }
+        }
+        let body = self.signed(Method::GET, "/v5/account/wallet-balance", "accountType=UNIFIED", None).await?;
+        body["result"]["list"].as_array().and_then(|list| list.first()).map(wallet_balance)
+            .ok_or_else(|| ExchangeError::Other("no unified account".to_string()))
+    }
+
+    /// Settlements from the unified account's transaction log. `funding` is what the account paid,
+    /// so receipts come back negative; `size` is unsigned with the side alongside.
+    async fn funding_history(&self, since: DateTime<Utc>) -> Result<Vec<FundingIncome>, ExchangeError> {
+        let mut incomes = Vec::new();
+        let mut cursor = String::new();
+        loop {
+            let mut query = format!("accountType=UNIFIED&category=linear&type=SETTLEMENT&startTime={}&limit=50", since.timestamp_millis());
+            if !cursor.is_empty() { query.push_str(&format!("&cursor={}", cursor)); }
+            let body = self.signed(Method::GET, "/v5/account/transaction-log", &query, None).await?;
+            for item in body["result"]["list"].as_array().into_iter().flatten() {
+                let size = num(&item["size"]);
+                incomes.push(FundingIncome {
+                    venue: "bybit".to_string(),
+                    symbol: item["symbol"].as_str().unwrap_or_default().to_string(),
+                    amount: -num(&item["funding"]),
+                    ts: millis(&item["transactionTime"]),
+                    rate: Some(num(&item["feeRate"])),
+                    qty: Some(if parse_side(&item["side"]) == Side::Buy { size } else { -size }),
+                });
+            }
+            cursor = body["result"]["nextPageCursor"].as_str().unwrap_or_default().to_string();
+            if cursor.is_empty() { break; }
+        }
+        incomes.sort_by_key(|income| income.ts);
+        Ok(incomes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::mock_server::{eventually, MockHttp, MockWs, Request};
+    use std::sync::atomic::AtomicUsize;
+
+    fn client(http: &MockHttp, ws_url: &str) -> Arc<BybitLinear> {
+        let client = BybitLinear {
+            rest_url: http.url.clone(),
+            ws_url: ws_url.to_string(),
+            ..BybitLinear::new(BybitCredentials { api_key: "key".to_string(), secret: "secret".to_string() })
+        };
+        client.filters.lock().unwrap().insert("BTCUSDT".to_string(), SymbolFilters { step: 0.001, tick: 0.1 });
+        Arc::new(client)
+    }
+
+    fn ok(result: Value) -> (u16, Value) {
+        (200, json!({"retCode": 0, "retMsg": "OK", "result": result}))
+    }
+
+    fn rest_order(cid: &str, status: &str, filled: &str) -> Value {
+        json!({"list": [{
+            "orderLinkId": cid, "orderId": "oid-1", "symbol": "BTCUSDT", "side": "Buy", "qty": "0.010", "price": "59999.9",
+            "cumExecQty": filled, "avgPrice": "59999.9", "cumExecFee": "0.006", "orderStatus": status, "updatedTime": "1700000000000",
+        }]})
+    }
+
+    /// The signature Bybit expects over `timestamp + key + recvWindow + payload`.
+    fn expected_sign(request: &Request, payload: &str) -> String {
+        let prehash = format!("{}key{}{}", request.header("X-BAPI-TIMESTAMP").unwrap(), request.header("X-BAPI-RECV-WINDOW").unwrap(), payload);
+        hmac_sha256_hex("secret", &prehash)
+    }
+
+    #[tokio::test]
+    async fn gets_sign_the_query_and_posts_sign_the_body() {
+        let http = MockHttp::start(|r| match r.path.as_str() {
+            "/v5/order/create" => ok(json!({"orderId": "oid-1", "orderLinkId": "cid-1"})),
+            "/v5/position/list" => ok(json!({"list": []})),
+            _ => (404, Value::Null),
+        }).await;
+        let client = client(&http, "ws://unused");
+
+        client.positions().await.unwrap();
+        let get = &http.hits("GET", "/v5/position/list")[0];
+        assert_eq!(get.query, "category=linear&settleCoin=USDT");
+        assert_eq!(get.header("X-BAPI-API-KEY"), Some("key"));
+        assert_eq!(get.header("X-BAPI-RECV-WINDOW"), Some("5000"));
+        assert_eq!(get.header("X-BAPI-SIGN"), Some(expected_sign(get, &get.query).as_str()));
+
+        let req = OrderRequest {
+            client_order_id: "cid-1".to_string(), symbol: "BTCUSDT".to_string(), side: Side::Buy,
+            qty: 0.0109, price: Some(59999.97), tif: TimeInForce::PostOnly, reduce_only: false,
+        };
+        client.place_order(&req).await.unwrap();
+        let post = &http.hits("POST", "/v5/order/create")[0];
+        assert_eq!(post.header("Content-Type"), Some("application/json"));
+        assert!(post.query.is_empty());
+        assert_eq!(post.header("X-BAPI-SIGN"), Some(expected_sign(post, &post.body).as_str()));
+        assert_ne!(post.header("X-BAPI-SIGN"), Some(expected_sign(post, "").as_str()));
+    }
+
+    #[tokio::test]
+    async fn timestamp_rejections_resync_and_retry_once() {
+        let server_time = Utc::now().timestamp_millis() + 60_000;
+        let reads = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&reads);
+        let stale = json!({"retCode": 10002, "retMsg": "invalid request, please check your server timestamp"});
+        let http = MockHttp::start(move |r| match r.path.as_str() {
+            "/v5/market/time" => (200, json!({"retCode": 0, "time": server_time})),
+            "/v5/account/wallet-balance" if counter.fetch_add(1, Ordering::Relaxed) == 0 => (200, stale.clone()),
+            "/v5/account/wallet-balance" => ok(json!({"list": [{"totalEquity": "1000", "totalAvailableBalance": "700", "totalInitialMargin": "300"}]})),
+            _ => (200, stale.clone()),
+        }).await;
+        let client = client(&http, "ws://unused");
+
+        let balance = client.balance().await.unwrap();
+        assert_eq!((balance.equity, balance.available, balance.margin_used), (1000.0, 700.0, 300.0));
+        let attempts = http.hits("GET", "/v5/account/wallet-balance");
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(http.hits("GET", "/v5/market/time").len(), 1);
+        let stamp = |i: usize| attempts[i].header("X-BAPI-TIMESTAMP").unwrap().parse::<i64>().unwrap();
+        assert!(stamp(1) - stamp(0) > 50_000, "retry must use the resynced clock");
+
+        // A second rejection is not retried again
+        let err = client.cancel_order("BTCUSDT", "cid-1").await.unwrap_err();
+        assert!(matches!(err, ExchangeError::Rejected(msg) if msg.starts_with("10002")));
+        assert_eq!(http.hits("POST", "/v5/order/cancel").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn create_amend_and_cancel_round_trip() {
+        let http = MockHttp::start(|r| match r.path.as_str() {
+            "/v5/order/create" => ok(json!({"orderId": "oid-1", "orderLinkId": "cid-1"})),
+            "/v5/order/amend" | "/v5/order/cancel" => ok(json!({"orderId": "oid-1", "orderLinkId": "cid-1"})),
+            "/v5/order/realtime" => ok(rest_order("cid-1", "PartiallyFilled", "0.004")),
+            _ => (404, Value::Null),
+        }).await;
+        let client = client(&http, "ws://unused");
+
+        let req = OrderRequest {
+            client_order_id: "cid-1".to_string(), symbol: "BTCUSDT".to_string(), side: Side::Buy,
+            qty: 0.0109, price: Some(59999.97), tif: TimeInForce::PostOnly, reduce_only: true,
+        };
+        let placed = client.place_order(&req).await.unwrap();
+        let body: Value = serde_json::from_str(&http.hits("POST", "/v5/order/create")[0].body).unwrap();
+        assert_eq!(body, json!({
+            "category": "linear", "symbol": "BTCUSDT", "side": "Buy", "qty": "0.010", "orderLinkId": "cid-1",
+            "reduceOnly": true, "orderType": "Limit", "price": "59999.9", "timeInForce": "PostOnly",
+        }));
+        assert_eq!((placed.exchange_order_id.as_str(), placed.qty, placed.price, placed.status), ("oid-1", 0.01, Some(59999.9), OrderStatus::New));
+
+        let amended = client.amend_order("BTCUSDT", "cid-1", Some(0.0201), Some(60000.04)).await.unwrap();
+        let body: Value = serde_json::from_str(&http.hits("POST", "/v5/order/amend")[0].body).unwrap();
+        assert_eq!(body, json!({"category": "linear", "symbol": "BTCUSDT", "orderLinkId": "cid-1", "qty": "0.020", "price": "60000.0"}));
+        assert_eq!((amended.filled_qty, amended.fee_paid, amended.status), (0.004, 0.006, OrderStatus::PartiallyFilled));
+        assert_eq!(http.hits("GET", "/v5/order/realtime")[0].param("orderLinkId"), Some("cid-1"));
+
+        let canceled = client.cancel_order("BTCUSDT", "cid-1").await.unwrap();
+        let body: Value = serde_json::from_str(&http.hits("POST", "/v5/order/cancel")[0].body).unwrap();
+        assert_eq!(body, json!({"category": "linear", "symbol": "BTCUSDT", "orderLinkId": "cid-1"}));
+        // The query lags the cancel, as it can on the venue: the order stays open for polling to settle
+        assert_eq!((canceled.filled_qty, canceled.status), (0.004, OrderStatus::PartiallyFilled));
+    }
+
+    #[tokio::test]
+    async fn cancels_read_fills_back_over_rest() {
+        let http = MockHttp::start(|r| match r.path.as_str() {
+            "/v5/order/cancel" => ok(json!({"orderId": "oid-1", "orderLinkId": "cid-1"})),
+            "/v5/order/realtime" => ok(rest_order("cid-1", "PartiallyFilledCanceled", "0.004")),
+            _ => (404, Value::Null),
+        }).await;
+        let client = client(&http, "ws://unused");
+        // The stream last saw the order untouched; a fill landed just before the cancel
+        let stale = order_state(&rest_order("cid-1", "New", "0")["list"][0]);
+        client.cache.lock().unwrap().orders.insert("cid-1".to_string(), stale);
+        client.stream_live.store(true, Ordering::Relaxed);
+
+        let canceled = client.cancel_order("BTCUSDT", "cid-1").await.unwrap();
+        assert_eq!((canceled.filled_qty, canceled.status), (0.004, OrderStatus::Canceled));
+        assert_eq!(http.hits("GET", "/v5/order/realtime").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn ret_codes_map_to_exchange_errors() {
+        let cases = [
+            (403, json!({})),
+            (200, json!({"retCode": 10006, "retMsg": "Too many visits"})),
+            (200, json!({"retCode": 110001, "retMsg": "order not exists or too late to cancel"})),
+            (401, json!({})),
+            (200, json!({"retCode": 10004, "retMsg": "error sign"})),
+            (200, json!({"retCode": 10016, "retMsg": "server error"})),
+            (502, json!({})),
+            (200, json!({"retCode": 110007, "retMsg": "ab not enough for new order"})),
+        ];
+        let labels: Vec<String> = cases.iter().map(|(http, body)| match map_error(*http, body) {
+            ExchangeError::RateLimited => "rate".to_string(),
+            ExchangeError::NotFound => "missing".to_string(),
+            ExchangeError::Auth(_) => "auth".to_string(),
+            ExchangeError::Network(_) => "network".to_string(),
+            ExchangeError::Rejected(msg) => msg,
+            ExchangeError::Other(msg) => msg,
+        }).collect();
+        assert_eq!(labels, ["rate", "rate", "missing", "auth", "auth", "network", "network", "110007 ab not enough for new order"]);
+
+        // Failures arrive as HTTP 200 with a non-zero retCode
+        let http = MockHttp::start(|_| (200, json!({"retCode": 110001, "retMsg": "order not exists"}))).await;
+        let client = client(&http, "ws://unused");
+        assert!(matches!(client.order_status("BTCUSDT", "cid-1").await, Err(ExchangeError::NotFound)));
+        let overlong = OrderRequest {
+            client_order_id: "x".repeat(37), symbol: "BTCUSDT".to_string(), side: Side::Buy,
+            qty: 0.01, price: None, tif: TimeInForce::Ioc, reduce_only: false,
+        };
+        assert!(matches!(client.place_order(&overlong).await, Err(ExchangeError::Rejected(_))));
+        assert!(http.hits("POST", "/v5/order/create").is_empty());
+    }
+
+    #[tokio::test]
+    async fn private_topics_keep_the_cache_until_a_reconnect() {
+        let http = MockHttp::start(|r| match r.path.as_str() {
+            "/v5/position/list" => ok(json!({"list": [
+                {"symbol": "BTCUSDT", "side": "Sell", "size": "0.010", "avgPrice": "60000", "unrealisedPnl": "-1.5", "liqPrice": "80000"},
+            ]})),
+            "/v5/account/wallet-balance" => ok(json!({"list": [{"totalEquity": "1000", "totalAvailableBalance": "700", "totalInitialMargin": "300"}]})),
+            "/v5/order/realtime" => ok(rest_order("cid-open", "PartiallyFilled", "0.006")),
+            _ => (404, Value::Null),
+        }).await;
+        let mut ws = MockWs::start().await;
+        let client = client(&http, &ws.url);
+        client.spawn_private_stream();
+
+        let mut conn = ws.accept().await;
+        let auth = conn.recv().await;
+        assert_eq!((auth["op"].as_str(), auth["args"][0].as_str()), (Some("auth"), Some("key")));
+        let expires = auth["args"][1].as_i64().unwrap();
+        assert_eq!(auth["args"][2].as_str(), Some(hmac_sha256_hex("secret", &format!("GET/realtime{}", expires)).as_str()));
+        assert_eq!(conn.recv().await, json!({"op": "subscribe", "args": ["order", "execution", "position", "wallet"]}));
+        conn.send(json!({"op": "auth", "success": true, "ret_msg": ""}));
+        eventually(|| client.stream_live.load(Ordering::Relaxed)).await;
+
+        // Positions are seeded from REST once, then follow the topic
+        assert_eq!(client.positions().await.unwrap()[0].qty, -0.01);
+        let order = |cid: &str, status: &str, filled: &str, category: &str| json!({
+            "category": category, "orderLinkId": cid, "orderId": "oid-1", "symbol": "BTCUSDT", "side": "Buy", "qty": "0.010",
+            "price": "59999.9", "cumExecQty": filled, "avgPrice": "59999.9", "cumExecFee": "0.002", "orderStatus": status, "updatedTime": "1700000000000",
+        });
+        conn.send(json!({"topic": "order", "data": [order("cid-open", "New", "0", "linear"), order("cid-spot", "New", "0", "spot")]}));
+        conn.send(json!({"topic": "execution", "data": [{
+            "category": "linear", "orderLinkId": "cid-open", "orderQty": "0.010", "leavesQty": "0.006",
+            "execQty": "0.004", "execPrice": "59999.9", "execFee": "0.0024", "execTime": "1700000001000",
+        }]}));
+        conn.send(json!({"topic": "order", "data": [order("cid-done", "Filled", "0.010", "linear")]}));
+        conn.send(json!({"topic": "position", "data": [
+            {"category": "linear", "symbol": "BTCUSDT", "side": "", "size": "0", "entryPrice": "0", "unrealisedPnl": "0", "liqPrice": ""},
+            {"category": "linear", "symbol": "ETHUSDT", "side": "Buy", "size": "0.5", "entryPrice": "3000", "unrealisedPnl": "2", "liqPrice": "1500"},
+        ]}));
+        conn.send(json!({"topic": "wallet", "data": [{"totalEquity": "1010", "totalAvailableBalance": "600", "totalInitialMargin": "410"}]}));
+        eventually(|| client.cache.lock().unwrap().balance.is_some()).await;
+
+        let open = client.order_status("BTCUSDT", "cid-open").await.unwrap();
+        assert_eq!((open.filled_qty, open.avg_fill_price, open.status), (0.004, 59999.9, OrderStatus::PartiallyFilled));
+        assert!((open.fee_paid - 0.0044).abs() < 1e-12, "execution fees add to the order's");
+        assert!(client.cached_order("cid-spot").is_none(), "other categories are ignored");
+        let positions = client.positions().await.unwrap();
+        assert_eq!(positions.len(), 1);
+        assert_eq!((positions[0].symbol.as_str(), positions[0].qty, positions[0].entry_price, positions[0].liquidation_price), ("ETHUSDT", 0.5, 3000.0, Some(1500.0)));
+        let balance = client.balance().await.unwrap();
+        assert_eq!((balance.equity, balance.available, balance.margin_used), (1010.0, 600.0, 410.0));
+        assert_eq!(http.hits("GET", "/v5/position/list").len(), 1);
+        assert!(http.hits("GET", "/v5/account/wallet-balance").is_empty());
+        assert!(http.hits("GET", "/v5/order/realtime").is_empty());
+
+        // Whatever was pushed while the socket was down is lost: all but final orders go back to REST
+        drop(conn);
+        eventually(|| !client.stream_live.load(Ordering::Relaxed)).await;
+        let mut conn = ws.accept().await;
+        conn.recv().await;
+        conn.send(json!({"op": "auth", "success": true, "ret_msg": ""}));
+        eventually(|| client.stream_live.load(Ordering::Relaxed)).await;
+
+        assert_eq!(client.order_status("BTCUSDT", "cid-done").await.unwrap().status, OrderStatus::Filled);
+        assert!(http.hits("GET", "/v5/order/realtime").is_empty());
+        assert_eq!(client.order_status("BTCUSDT", "cid-open").await.unwrap().filled_qty, 0.006);
+        assert_eq!(http.hits("GET", "/v5/order/realtime").len(), 1);
+        assert_eq!(client.positions().await.unwrap()[0].symbol, "BTCUSDT");
+        assert_eq!(http.hits("GET", "/v5/position/list").len(), 2);
+        assert_eq!(client.balance().await.unwrap().equity, 1000.0);
+        assert_eq!(http.hits("GET", "/v5/account/wallet-balance").len(), 1);
+    }
+}
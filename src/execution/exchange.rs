@@ -0,0 +1,262 @@
+use crate::ingestors::signals::Side;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::Sha256;
+use std::fmt;
+use uuid::Uuid;
+
+// --- 📦 ORDER TYPES ---
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeInForce { Gtc, PostOnly, Ioc }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderRequest {
+    /// Ours, unique per order; venues echo it back so retries stay idempotent.
+    pub client_order_id: String,
+    /// Venue-native instrument name.
+    pub symbol: String,
+    pub side: Side,
+    pub qty: f64,
+    /// `None` sends a market order.
+    pub price: Option<f64>,
+    pub tif: TimeInForce,
+    pub reduce_only: bool,
+}
+
+/// Longest client order ID Binance (`newClientOrderId`) and Bybit (`orderLinkId`) accept.
+pub const MAX_CLIENT_ORDER_ID_LEN: usize = 36;
+
+/// Client order ID for an order of leg `leg` of a signal: the signal ID in base36 (25 chars), the leg and,
+/// for every order after a leg's first, a tag and counter (`q1`, `h2`, `r1`, ...). Fits
+/// `MAX_CLIENT_ORDER_ID_LEN` for single-digit legs and counters below ten million.
+pub fn client_order_id(signal_id: Uuid, leg: usize, retry: Option<(char, u32)>) -> String {
+    const DIGITS: &[u8; 36] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+    let mut value = signal_id.as_u128();
+    let mut base36 = [b'0'; 25];
+    for digit in base36.iter_mut().rev() {
+        *digit = DIGITS[(value % 36) as usize];
+        value /= 36;
+    }
+    let base = format!("{}-{}", String::from_utf8_lossy(&base36), leg);
+    match retry {
+        Some((tag, n)) => format!("{}-{}{}", base, tag, n),
+        None => base,
+    }
+}
+
+/// Refuses IDs the venue would reject anyway, before spending a request on them.
+pub(crate) fn check_client_order_id(client_order_id: &str) -> Result<(), ExchangeError> {
+    if client_order_id.is_empty() || client_order_id.len() > MAX_CLIENT_ORDER_ID_LEN {
+        return Err(ExchangeError::Rejected(format!(
+            "client order ID {} is {} chars; the venue takes 1 to {}", client_order_id, client_order_id.len(), MAX_CLIENT_ORDER_ID_LEN,
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OrderStatus { New, PartiallyFilled, Filled, Canceled, Rejected, Expired }
+
+impl OrderStatus {
+    pub fn is_terminal(self) -> bool {
+        matches!(self, OrderStatus::Filled | OrderStatus::Canceled | OrderStatus::Rejected | OrderStatus::Expired)
+    }
+}
+
+/// Venue's view of one order. Fill figures are cumulative.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderState {
+    pub venue: String,
+    pub client_order_id: String,
+    pub exchange_order_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: f64,
+    pub price: Option<f64>,
+    pub filled_qty: f64,
+    pub avg_fill_price: f64,
+    /// Quote-currency fees paid so far; negative for rebates.
+    pub fee_paid: f64,
+    pub status: OrderStatus,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One execution, derived from the change between two `OrderState`s.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Fill {
+    pub venue: String,
+    pub client_order_id: String,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: f64,
+    pub price: f64,
+    pub fee: f64,
+    pub ts: DateTime<Utc>,
+}
+
+impl Fill {
+    /// What filled between `old` and `new`, if anything.
+    pub fn between(old: &OrderState, new: &OrderState) -> Option<Self> {
+        let qty = new.filled_qty - old.filled_qty;
+        if qty <= 1e-12 { return None; }
+        let price = (new.avg_fill_price * new.filled_qty - old.avg_fill_price * old.filled_qty) / qty;
+        Some(Self {
+            venue: new.venue.clone(),
+            client_order_id: new.client_order_id.clone(),
+            symbol: new.symbol.clone(),
+            side: new.side,
+            qty,
+            price,
+            fee: new.fee_paid - old.fee_paid,
+            ts: new.updated_at,
+        })
+    }
+}
+
+/// Signed position on one venue (positive = long).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VenuePosition {
+    pub venue: String,
+    pub symbol: String,
+    pub qty: f64,
+    pub entry_price: f64,
+    pub unrealized_pnl: f64,
+    pub liquidation_price: Option<f64>,
+}
+
+/// One funding settlement as the venue booked it. Positive amount = received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingIncome {
+    pub venue: String,
+    pub symbol: String,
+    pub amount: f64,
+    pub ts: DateTime<Utc>,
+    /// Settled rate, where the venue reports it alongside the payment.
+    pub rate: Option<f64>,
+    /// Signed position the payment was computed on, where reported.
+    pub qty: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Balance {
+    pub equity: f64,
+    pub available: f64,
+    pub margin_used: f64,
+}
+
+// --- ⚠️ ERRORS ---
+
+#[derive(Debug)]
+pub enum ExchangeError {
+    Rejected(String),
+    NotFound,
+    RateLimited,
+    Auth(String),
+    Network(String),
+    Other(String),
+}
+
+impl fmt::Display for ExchangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExchangeError::Rejected(msg) => write!(f, "rejected: {}", msg),
+            ExchangeError::NotFound => write!(f, "order not found"),
+            ExchangeError::RateLimited => write!(f, "rate limited"),
+            ExchangeError::Auth(msg) => write!(f, "auth: {}", msg),
+            ExchangeError::Network(msg) => write!(f, "network: {}", msg),
+            ExchangeError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ExchangeError {}
+
+// --- 🧮 VENUE HELPERS ---
+
+/// Hex HMAC-SHA256, the request signature Binance and Bybit both use.
+pub(crate) fn hmac_sha256_hex(secret: &str, payload: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC takes any key length");
+    mac.update(payload.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+/// Venues send numbers as strings; anything unparseable reads as zero.
+pub(crate) fn num(value: &Value) -> f64 {
+    match value {
+        Value::String(s) => s.parse().unwrap_or(0.0),
+        other => other.as_f64().unwrap_or(0.0),
+    }
+}
+
+/// Rounds `value` down to a multiple of `step` and prints it without float noise.
+pub(crate) fn to_step(value: f64, step: f64) -> String {
+    if step <= 0.0 { return value.to_string(); }
+    let decimals = (-step.log10()).ceil().max(0.0) as usize;
+    format!("{:.*}", decimals, (value / step + 1e-9).floor() * step)
+}
+
+/// Limit price on the tick grid: buys round down and sells up, so rounding never makes
+/// the order more aggressive than asked.
+pub(crate) fn to_tick(price: f64, tick: f64, side: Side) -> String {
+    let rounded = if side == Side::Buy || tick <= 0.0 { price } else { (price / tick - 1e-9).ceil() * tick };
+    to_step(rounded, tick)
+}
+
+// --- 🔌 CLIENT TRAIT ---
+
+/// Everything the executor needs from a venue. One implementation per exchange, plus the mock.
+#[async_trait]
+pub trait ExchangeClient: Send + Sync {
+    fn venue(&self) -> &str;
+    async fn place_order(&self, req: &OrderRequest) -> Result<OrderState, ExchangeError>;
+    async fn cancel_order(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError>;
+    /// Changes size and/or price of a resting order in place, keeping its client order ID.
+    async fn amend_order(&self, symbol: &str, client_order_id: &str, qty: Option<f64>, price: Option<f64>) -> Result<OrderState, ExchangeError> {
+        let _ = (symbol, client_order_id, qty, price);
+        Err(ExchangeError::Rejected(format!("{} does not support amend", self.venue())))
+    }
+    async fn order_status(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError>;
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ExchangeError>;
+    async fn balance(&self) -> Result<Balance, ExchangeError>;
+    /// Funding settled on the account since `since`, oldest first.
+    async fn funding_history(&self, since: DateTime<Utc>) -> Result<Vec<FundingIncome>, ExchangeError> {
+        let _ = since;
+        Err(ExchangeError::Rejected(format!("{} does not report funding income", self.venue())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_order_ids_fit_binance_and_bybit() {
+        let id = Uuid::from_u128(u128::MAX);
+        assert_eq!(client_order_id(id, 1, None).len(), 27);
+        for tag in ['q', 't', 'h', 'r', 'x'] {
+            let cid = client_order_id(id, 9, Some((tag, 9_999_999)));
+            assert!(cid.len() <= MAX_CLIENT_ORDER_ID_LEN, "{} is {} chars", cid, cid.len());
+            assert!(check_client_order_id(&cid).is_ok());
+        }
+        assert_eq!(client_order_id(Uuid::nil(), 0, Some(('q', 1))), "0000000000000000000000000-0-q1");
+    }
+
+    #[test]
+    fn client_order_ids_stay_unique_per_signal() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_ne!(client_order_id(a, 0, None), client_order_id(b, 0, None));
+        assert_ne!(client_order_id(a, 0, None), client_order_id(a, 1, None));
+        assert_ne!(client_order_id(a, 0, Some(('q', 1))), client_order_id(a, 0, Some(('t', 1))));
+    }
+
+    #[test]
+    fn overlong_client_order_ids_are_refused() {
+        let legacy = format!("{}-0-q1", Uuid::new_v4().simple());
+        assert!(matches!(check_client_order_id(&legacy), Err(ExchangeError::Rejected(_))));
+        assert!(check_client_order_id("").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use super::account_monitor::AccountMonitorConfig;
+use super::funding_recon::FundingReconConfig;
+use super::hybrid::HybridConfig;
+use super::leg_risk::LegRiskConfig;
+use super::order_manager::OrderManagerConfig;
+use super::risk::RiskLimits;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_CONFIG_PATH: &str = "config/execution.json";
+
+/// Executor knobs that can be changed without a rebuild.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionConfig {
+    pub leg_risk: LegRiskConfig,
+    pub orders: OrderManagerConfig,
+    pub hybrid: HybridConfig,
+    pub risk: RiskLimits,
+    pub funding_recon: FundingReconConfig,
+    pub accounts: AccountMonitorConfig,
+}
+
+impl ExecutionConfig {
+    /// Reads `EXECUTION_CONFIG` (or `config/execution.json`); missing fields keep their defaults.
+    pub fn load() -> Self {
+        let path = std::env::var("EXECUTION_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        let Ok(raw) = std::fs::read_to_string(&path) else { return Self::default() };
+        let config: Self = serde_json::from_str(&raw).unwrap_or_else(|e| {
+            println!("⚠️ [Execution] Ignoring {}: {}", path, e);
+            Self::default()
+        });
+        config.check();
+        config
+    }
+
+    /// Warns about settings that quietly disable each other.
+    fn check(&self) {
+        let window = self.orders.escalation_window_ms();
+        if self.orders.enabled && self.leg_risk.max_legged_ms <= window {
+            println!(
+                "⚠️ [Execution] leg_risk.max_legged_ms ({}) is not above order_timeout_ms x (max_replaces + 1) ({}); leg risk will repair legs before they escalate",
+                self.leg_risk.max_legged_ms, window,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_leg_tolerance_outlasts_order_escalation() {
+        let config = ExecutionConfig::default();
+        assert!(config.leg_risk.max_legged_ms > config.orders.escalation_window_ms());
+    }
+}
@@ -0,0 +1,1032 @@
+use super::account_monitor::{self, AccountMonitor};
+use super::alerts::{Alert, AlertLevel, ALERTS_CHANNEL};
+use super::exchange::{client_order_id, ExchangeClient, Fill, OrderRequest, OrderState, OrderStatus, TimeInForce};
+use super::execution_config::ExecutionConfig;
+use super::funding_recon::{self, FundingRecon};
+use super::hybrid::{HybridConfig, HybridPlan};
+use super::leg_risk::{LegDecision, LegGroup, LegRiskConfig, RepairPlan};
+use super::ledger::Ledger;
+use super::order_manager::{target_price, LifecycleState, ManagedOrder, OrderAction, OrderManagerConfig, Quote, ORDER_HISTORY_CHANNEL};
+use super::risk::{RiskBook, RiskLimits};
+use crate::ingestors::control::{ControlCommand, KillSwitch, CONTROL_CHANNEL};
+use crate::ingestors::fees::FeeModel;
+use crate::ingestors::market_store::{spawn_redis_feed, MarketState, MarketStore};
+use crate::ingestors::signals::{coin_symbol, Side, SignalKind, TradeSignal, SIGNAL_CHANNEL};
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use uuid::Uuid;
+
+pub const ORDERS_CHANNEL: &str = "trade:orders";
+pub const FILLS_CHANNEL: &str = "trade:fills";
+pub const POSITIONS_CHANNEL: &str = "trade:positions";
+
+pub const POLL_MS: u64 = 250;
+/// Signal IDs remembered for dedupe.
+const SEEN_CAPACITY: usize = 10_000;
+const MAX_SYMBOLS: usize = 512;
+
+pub type Clients = HashMap<String, Arc<dyn ExchangeClient>>;
+
+/// Net position the executor believes it holds on one venue, published after every fill.
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionUpdate {
+    pub venue: String,
+    pub symbol: String,
+    pub qty: f64,
+    pub ts: DateTime<Utc>,
+}
+
+/// A fill tagged with the signal and pair that caused it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutedFill {
+    pub signal_id: Uuid,
+    pub pair_id: String,
+    pub fill: Fill,
+    /// The signal's price for this leg when the fill works it in the signal's direction;
+    /// `None` for unwinds and flattens.
+    pub reference_price: Option<f64>,
+}
+
+/// An order we placed and have not yet seen reach a terminal state.
+struct WorkingOrder {
+    signal_id: Uuid,
+    pair_id: String,
+    /// Index of the signal leg this order works (repairs included).
+    leg: usize,
+    /// Price the signal wanted for this leg; see `ExecutedFill::reference_price`.
+    reference: Option<f64>,
+    state: OrderState,
+}
+
+pub struct Executor {
+    clients: Clients,
+    seen: HashSet<Uuid>,
+    seen_order: VecDeque<Uuid>,
+    working: HashMap<String, WorkingOrder>,
+    positions: HashMap<(String, String), f64>,
+    /// Signals whose legs are still being matched up, by signal ID.
+    groups: HashMap<Uuid, LegGroup>,
+    leg_risk: LegRiskConfig,
+    /// Limit legs being quoted, by (signal ID, leg).
+    managed: HashMap<(Uuid, usize), ManagedOrder>,
+    orders: OrderManagerConfig,
+    /// Books to quote against; without them limit legs rest at the signal's limit.
+    books: Option<Arc<MarketStore>>,
+    hybrid: HybridConfig,
+    /// Opens worked as one maker leg plus taker hedges, by signal ID.
+    hybrids: HashMap<Uuid, HybridPlan>,
+    /// Maker fills waiting for their hedge, as (signal ID, qty).
+    pending_hedges: Vec<(Uuid, f64)>,
+    fees: FeeModel,
+    risk: RiskBook,
+    /// Engaged kill switch; opens are refused while it is set.
+    kill: Option<KillSwitch>,
+    /// Flattens sent so far; numbers their client order IDs.
+    flattens: u32,
+    /// `None` when running offline (backtests); nothing is published then.
+    bus: Option<redis::aio::MultiplexedConnection>,
+    /// Optional copy of every fill for in-process accounting.
+    fill_tap: Option<mpsc::UnboundedSender<ExecutedFill>>,
+    /// Suppresses the per-order and per-fill log lines; errors are always printed.
+    quiet: bool,
+}
+
+impl Executor {
+    pub fn new(clients: Clients, bus: redis::aio::MultiplexedConnection) -> Self {
+        Self { bus: Some(bus), ..Self::offline(clients) }
+    }
+
+    /// An executor that never touches Redis; pair it with a fill tap to see what happened.
+    pub fn offline(clients: Clients) -> Self {
+        Self {
+            clients,
+            seen: HashSet::new(),
+            seen_order: VecDeque::new(),
+            working: HashMap::new(),
+            positions: HashMap::new(),
+            groups: HashMap::new(),
+            leg_risk: LegRiskConfig::default(),
+            managed: HashMap::new(),
+            orders: OrderManagerConfig::default(),
+            books: None,
+            hybrid: HybridConfig::default(),
+            hybrids: HashMap::new(),
+            pending_hedges: Vec::new(),
+            fees: FeeModel::default(),
+            risk: RiskBook::new(RiskLimits::default()),
+            kill: None,
+            flattens: 0,
+            bus: None,
+            fill_tap: None,
+            quiet: false,
+        }
+    }
+
+    pub fn with_fill_tap(mut self, tap: mpsc::UnboundedSender<ExecutedFill>) -> Self {
+        self.fill_tap = Some(tap);
+        self
+    }
+
+    pub fn with_config(mut self, config: &ExecutionConfig) -> Self {
+        self.leg_risk = config.leg_risk;
+        self.orders = config.orders;
+        self.hybrid = config.hybrid;
+        self.risk = RiskBook::new(config.risk);
+        self
+    }
+
+    /// Picks up what the ledger says is still held, for the risk limits and position updates.
+    pub fn with_ledger(mut self, ledger: &Ledger) -> Self {
+        let now = Utc::now();
+        let legs = ledger.open_legs();
+        for leg in &legs {
+            *self.positions.entry((leg.venue.clone(), leg.symbol.clone())).or_insert(0.0) += leg.qty;
+        }
+        let realised = ledger.realised_on(now.date_naive());
+        self.risk.seed(&legs, realised, now);
+        if !legs.is_empty() || realised != 0.0 {
+            println!("📒 [Risk] Resuming with {} open leg(s) from the ledger, {:+.2} realised today", legs.len(), realised);
+        }
+        self
+    }
+
+    pub fn with_fees(mut self, fees: &FeeModel) -> Self {
+        self.fees = fees.clone();
+        self
+    }
+
+    pub fn with_books(mut self, books: Arc<MarketStore>) -> Self {
+        self.books = Some(books);
+        self
+    }
+
+    /// Refuses opens with a leg on any of `venues` until the next call.
+    pub fn block_venues(&mut self, venues: &[String]) {
+        self.risk.block_venues(venues);
+    }
+
+    pub fn quiet(mut self) -> Self {
+        self.quiet = true;
+        self
+    }
+
+    /// Returns false if the ID was already handled.
+    fn remember(&mut self, id: Uuid) -> bool {
+        if !self.seen.insert(id) { return false; }
+        self.seen_order.push_back(id);
+        if self.seen_order.len() > SEEN_CAPACITY {
+            if let Some(old) = self.seen_order.pop_front() { self.seen.remove(&old); }
+        }
+        true
+    }
+
+    async fn publish<T: Serialize>(&mut self, channel: &str, payload: &T) {
+        let Some(bus) = self.bus.as_mut() else { return };
+        if let Ok(raw) = serde_json::to_string(payload) {
+            let _: () = bus.publish(channel, raw).await.unwrap_or(());
+        }
+    }
+
+    async fn alert(&mut self, level: AlertLevel, message: String, now: DateTime<Utc>) {
+        let alert = Alert::new(level, "executor", message, now);
+        println!("{} [Executor] {}", alert.icon(), alert.message);
+        self.publish(ALERTS_CHANNEL, &alert).await;
+    }
+
+    /// Entry point for raw bus messages.
+    pub async fn handle_payload(&mut self, payload: &str) {
+        match TradeSignal::from_json(payload) {
+            Some(signal) => self.handle_signal(signal).await,
+            None => println!("⚠️ [Executor] Ignoring unreadable signal: {}", payload),
+        }
+    }
+
+    pub async fn handle_signal(&mut self, signal: TradeSignal) {
+        self.execute(signal, Utc::now()).await;
+    }
+
+    /// Turns a signal into one order per leg and sends them all at once. `now` is the
+    /// wall clock live and the replay clock in backtests.
+    pub async fn execute(&mut self, signal: TradeSignal, now: DateTime<Utc>) {
+        if !self.remember(signal.id) { return; }
+        if signal.is_stale(now) {
+            println!("⌛ [Executor] Dropping stale {:?} {} ({})", signal.kind, signal.pair_id, signal.id);
+            return;
+        }
+        // Closes always go through; they only ever reduce risk
+        if signal.kind == SignalKind::OpenLimit {
+            if let Some(kill) = &self.kill {
+                println!("🛑 [Risk] Kill switch engaged ({}), dropping open {} ({})", kill.reason, signal.pair_id, signal.id);
+                return;
+            }
+            let mark = self.marks();
+            if let Err(breach) = self.risk.check_open(&signal, now, &mark) {
+                self.alert(AlertLevel::Warning, format!("{} open refused by {:?} limit ({})", signal.pair_id, breach, signal.id), now).await;
+                return;
+            }
+        }
+
+        // Never half-execute a pair because one venue is not wired up, so
+        // every leg's client is resolved before any state is touched
+        let mut clients = Vec::with_capacity(signal.legs.len());
+        for leg in &signal.legs {
+            let Some(client) = self.clients.get(&leg.venue) else {
+                println!("❌ [Executor] No client for {}, skipping {}", leg.venue, signal.id);
+                return;
+            };
+            clients.push(Arc::clone(client));
+        }
+        let hybrid = self.plan_hybrid(&signal);
+        let mut orders = Vec::new();
+        for ((idx, leg), client) in signal.legs.iter().enumerate().zip(clients) {
+            // The hedge leg waits for the maker to fill
+            if hybrid.as_ref().is_some_and(|plan| plan.hedge_leg == idx) { continue; }
+            let mut request = OrderRequest {
+                client_order_id: client_order_id(signal.id, idx, None),
+                symbol: leg.symbol.clone(),
+                side: leg.side,
+                qty: leg.size,
+                price: leg.limit_price,
+                tif: if leg.limit_price.is_some() { TimeInForce::Gtc } else { TimeInForce::Ioc },
+                reduce_only: signal.kind == SignalKind::CloseLimit,
+            };
+            // Managed limit legs start as a post-only quote at the touch
+            if let (true, Some(limit)) = (self.orders.enabled, leg.limit_price) {
+                let mut managed = ManagedOrder::new(&signal, idx, now);
+                managed.price = target_price(leg.side, self.quote(&leg.venue, &leg.symbol), limit, &self.orders);
+                managed.client_order_id = managed.next_client_order_id('q');
+                managed.working = true;
+                managed.record(now, LifecycleState::Placed, "");
+                request.price = Some(managed.price);
+                request.tif = TimeInForce::PostOnly;
+                self.managed.insert((signal.id, idx), managed);
+            }
+            orders.push((idx, client, request));
+        }
+
+        if !self.quiet { println!("📨 [Executor] {:?} {} -> {} order(s)", signal.kind, signal.pair_id, orders.len()); }
+        let mark = self.marks();
+        self.risk.reserve(&signal, &mark);
+        self.groups.insert(signal.id, LegGroup::new(&signal));
+        if let Some(plan) = hybrid { self.hybrids.insert(signal.id, plan); }
+        let results = futures::future::join_all(orders.iter().map(|(_, client, req)| client.place_order(req))).await;
+        for ((leg, client, req), result) in orders.iter().zip(results) {
+            let leg = *leg;
+            match result {
+                Ok(state) => self.track(signal.id, &signal.pair_id, (leg, signal.legs[leg].limit_price), state).await,
+                Err(e) => {
+                    println!("❌ [Executor] {} {} rejected: {}", client.venue(), req.client_order_id, e);
+                    if let Some(managed) = self.managed.get_mut(&(signal.id, leg)) {
+                        managed.working = false;
+                        managed.record(now, LifecycleState::Rejected, e.to_string());
+                    }
+                }
+            }
+        }
+        self.flush_hedges(now).await;
+        self.manage_leg_risk(now).await;
+        self.check_risk(now).await;
+    }
+
+    /// Records a fresh order and emits whatever it already filled.
+    async fn track(&mut self, signal_id: Uuid, pair_id: &str, (leg, reference): (usize, Option<f64>), state: OrderState) {
+        let blank = OrderState { filled_qty: 0.0, avg_fill_price: 0.0, fee_paid: 0.0, ..state.clone() };
+        let working = WorkingOrder { signal_id, pair_id: pair_id.to_string(), leg, reference, state: blank };
+        self.risk.record_order(state.updated_at);
+        self.working.insert(state.client_order_id.clone(), working);
+        self.apply(state).await;
+    }
+
+    /// Folds a new venue snapshot into the working order, publishing the order, any fill and the position.
+    async fn apply(&mut self, new: OrderState) {
+        let Some(working) = self.working.get_mut(&new.client_order_id) else { return };
+        let fill = Fill::between(&working.state, &new);
+        let changed = fill.is_some() || working.state.status != new.status;
+        let filled = fill.as_ref().map(|f| f.qty);
+        working.state = new.clone();
+        let (signal_id, pair_id, leg, reference) = (working.signal_id, working.pair_id.clone(), working.leg, working.reference);
+        if new.status.is_terminal() { self.working.remove(&new.client_order_id); }
+
+        if changed { self.publish(ORDERS_CHANNEL, &new).await; }
+        if let Some(fill) = fill {
+            if !self.quiet {
+                println!("✅ [Executor] {} {:?} {} {:.6} @ {:.4} (signal {})", fill.venue, fill.side, fill.symbol, fill.qty, fill.price, signal_id);
+            }
+            if let Some(group) = self.groups.get_mut(&signal_id) { group.record_fill(leg, &fill); }
+            self.risk.record_fill(&pair_id, (signal_id, leg), &fill);
+            let signed = if fill.side == Side::Buy { fill.qty } else { -fill.qty };
+            let key = (fill.venue.clone(), fill.symbol.clone());
+            let qty = *self.positions.entry(key).and_modify(|q| *q += signed).or_insert(signed);
+            let update = PositionUpdate { venue: fill.venue.clone(), symbol: fill.symbol.clone(), qty, ts: fill.ts };
+            self.publish(FILLS_CHANNEL, &fill).await;
+            self.publish(POSITIONS_CHANNEL, &update).await;
+            if self.hybrids.get(&signal_id).is_some_and(|plan| plan.maker_leg == leg) {
+                self.pending_hedges.push((signal_id, fill.qty));
+            }
+            if let Some(tap) = &self.fill_tap { let _ = tap.send(ExecutedFill { signal_id, pair_id, fill, reference_price: reference }); }
+        }
+        self.on_managed_update(signal_id, leg, &new, filled).await;
+    }
+
+    /// Refreshes every working order from its venue, then checks leg risk.
+    pub async fn poll_orders(&mut self, now: DateTime<Utc>) {
+        let pending: Vec<(String, String, String)> = self.working.values()
+            .map(|w| (w.state.venue.clone(), w.state.symbol.clone(), w.state.client_order_id.clone()))
+            .collect();
+        for (venue, symbol, cid) in pending {
+            let Some(client) = self.clients.get(&venue).cloned() else { continue };
+            match client.order_status(&symbol, &cid).await {
+                Ok(state) => self.apply(state).await,
+                Err(e) => println!("⚠️ [Executor] status {} {}: {}", venue, cid, e),
+            }
+        }
+        self.flush_hedges(now).await;
+        self.manage_orders(now).await;
+        self.flush_hedges(now).await;
+        self.manage_leg_risk(now).await;
+        self.check_risk(now).await;
+    }
+
+    /// Book moved: requote whatever needs it without waiting for the next poll.
+    pub async fn on_book(&mut self, now: DateTime<Utc>) {
+        if self.managed.is_empty() { return; }
+        self.manage_orders(now).await;
+        self.flush_hedges(now).await;
+    }
+
+    // --- 🎯 LIMIT ORDER LIFECYCLE ---
+
+    fn book(&self, venue: &str, symbol: &str) -> Option<Arc<MarketState>> {
+        snapshot(self.books.as_ref(), venue, symbol)
+    }
+
+    fn quote(&self, venue: &str, symbol: &str) -> Option<Quote> {
+        self.book(venue, symbol).and_then(|book| Quote::from_book(&book))
+    }
+
+    /// Keeps a managed leg's bookkeeping in step with its current order.
+    async fn on_managed_update(&mut self, signal_id: Uuid, leg: usize, state: &OrderState, filled: Option<f64>) {
+        let Some(managed) = self.managed.get_mut(&(signal_id, leg)) else { return };
+        if managed.client_order_id != state.client_order_id { return; }
+        if let Some(qty) = filled { managed.history.filled += qty; }
+        let was_working = managed.working;
+        if state.status.is_terminal() { managed.working = false; }
+
+        if managed.is_complete() {
+            managed.record(state.updated_at, LifecycleState::Filled, "");
+            self.finish_managed((signal_id, leg)).await;
+            return;
+        }
+        match state.status {
+            OrderStatus::PartiallyFilled if filled.is_some() => managed.record(state.updated_at, LifecycleState::PartiallyFilled, ""),
+            OrderStatus::Rejected => managed.record(state.updated_at, LifecycleState::Rejected, "refused by venue"),
+            // Our own cancels clear `working` first, so this one came from the venue
+            OrderStatus::Canceled | OrderStatus::Expired if was_working => {
+                let note = if managed.escalated { "IOC remainder cancelled" } else { "cancelled by venue" };
+                managed.record(state.updated_at, LifecycleState::Canceled, note);
+            }
+            _ => {}
+        }
+    }
+
+    async fn finish_managed(&mut self, key: (Uuid, usize)) {
+        let Some(managed) = self.managed.remove(&key) else { return };
+        self.risk.release_leg(key.0, key.1);
+        let history = managed.history;
+        if !self.quiet {
+            println!("🧾 [Orders] {} leg {} {:?}: {:.6}/{:.6} after {} requote(s), {} replace(s)",
+                history.pair_id, history.leg, history.final_state, history.filled, history.qty, managed.requotes, managed.replaces);
+        }
+        self.publish(ORDER_HISTORY_CHANNEL, &history).await;
+    }
+
+    /// Reviews every managed leg against its book.
+    async fn manage_orders(&mut self, now: DateTime<Utc>) {
+        let keys: Vec<(Uuid, usize)> = self.managed.keys().copied().collect();
+        for key in keys {
+            let Some(managed) = self.managed.get(&key) else { continue };
+            let quote = self.quote(&managed.history.venue, &managed.history.symbol);
+            match managed.review(&self.orders, quote, now) {
+                OrderAction::Hold => {}
+                OrderAction::Requote(price) | OrderAction::Replace(price) if !self.hedge_clears(key, price) => {
+                    self.abandon(key, "hedge no longer profitable", now).await;
+                }
+                OrderAction::Requote(price) => self.requote(key, price, false, now).await,
+                OrderAction::Replace(price) => self.requote(key, price, true, now).await,
+                OrderAction::Escalate(price) => self.escalate(key, price, now).await,
+                OrderAction::Abandon(reason) => self.abandon(key, reason, now).await,
+            }
+        }
+    }
+
+    /// Cancels the leg's live order, if any. False if the venue would not confirm the cancel.
+    async fn cancel_managed(&mut self, key: (Uuid, usize)) -> bool {
+        let Some(managed) = self.managed.get_mut(&key) else { return false };
+        if !managed.working { return true; }
+        managed.working = false;
+        let (venue, symbol, cid) = (managed.history.venue.clone(), managed.history.symbol.clone(), managed.client_order_id.clone());
+        let Some(client) = self.clients.get(&venue).cloned() else { return false };
+        match client.cancel_order(&symbol, &cid).await {
+            Ok(state) => {
+                self.apply(state).await;
+                true
+            }
+            Err(e) => {
+                println!("⚠️ [Orders] cancel {} {}: {}", venue, cid, e);
+                if let Some(managed) = self.managed.get_mut(&key) { managed.working = true; }
+                false
+            }
+        }
+    }
+
+    /// Sends the next order of a managed leg and starts tracking it.
+    async fn send_managed(&mut self, key: (Uuid, usize), request: OrderRequest, now: DateTime<Utc>) {
+        let Some(managed) = self.managed.get(&key) else { return };
+        let (pair_id, limit) = (managed.history.pair_id.clone(), managed.history.limit_price);
+        let Some(client) = self.clients.get(&managed.history.venue).cloned() else { return };
+        match client.place_order(&request).await {
+            Ok(state) => self.track(key.0, &pair_id, (key.1, Some(limit)), state).await,
+            Err(e) => {
+                if let Some(managed) = self.managed.get_mut(&key) {
+                    managed.working = false;
+                    managed.record(now, LifecycleState::Rejected, e.to_string());
+                }
+            }
+        }
+    }
+
+    /// Moves the quote: amends in place where the venue allows it, otherwise cancels and
+    /// re-places. `replace` marks a timeout, which also restarts the order's clock.
+    async fn requote(&mut self, key: (Uuid, usize), price: f64, replace: bool, now: DateTime<Utc>) {
+        let Some(managed) = self.managed.get(&key) else { return };
+        if managed.working && !replace {
+            let (venue, symbol, cid) = (managed.history.venue.clone(), managed.history.symbol.clone(), managed.client_order_id.clone());
+            let Some(client) = self.clients.get(&venue).cloned() else { return };
+            if let Ok(state) = client.amend_order(&symbol, &cid, None, Some(price)).await {
+                if let Some(managed) = self.managed.get_mut(&key) {
+                    managed.price = price;
+                    managed.quoted_at = now;
+                    managed.requotes += 1;
+                    managed.record(now, LifecycleState::Requoted, "amended");
+                }
+                self.apply(state).await;
+                return;
+            }
+        }
+        if !self.cancel_managed(key).await { return; }
+
+        // The cancel may have raced a fill that completed the leg
+        let Some(managed) = self.managed.get_mut(&key) else { return };
+        let cid = managed.next_client_order_id('q');
+        managed.client_order_id = cid.clone();
+        managed.price = price;
+        managed.quoted_at = now;
+        managed.working = true;
+        if replace {
+            managed.replaces += 1;
+            managed.placed_at = now;
+            managed.record(now, LifecycleState::Replaced, "timed out");
+        } else {
+            managed.requotes += 1;
+            managed.record(now, LifecycleState::Requoted, "");
+        }
+        let request = OrderRequest {
+            client_order_id: cid,
+            symbol: managed.history.symbol.clone(),
+            side: managed.history.side,
+            qty: managed.remaining(),
+            price: Some(price),
+            tif: TimeInForce::PostOnly,
+            reduce_only: managed.reduce_only,
+        };
+        self.send_managed(key, request, now).await;
+    }
+
+    /// Out of patience but the edge is still there: take the rest with an IOC at the limit.
+    async fn escalate(&mut self, key: (Uuid, usize), price: f64, now: DateTime<Utc>) {
+        if !self.cancel_managed(key).await { return; }
+        let Some(managed) = self.managed.get_mut(&key) else { return };
+        let cid = managed.next_client_order_id('t');
+        managed.client_order_id = cid.clone();
+        managed.price = price;
+        managed.escalated = true;
+        managed.working = true;
+        managed.record(now, LifecycleState::Escalated, "crossing the spread");
+        let request = OrderRequest {
+            client_order_id: cid,
+            symbol: managed.history.symbol.clone(),
+            side: managed.history.side,
+            qty: managed.remaining(),
+            price: Some(price),
+            tif: TimeInForce::Ioc,
+            reduce_only: managed.reduce_only,
+        };
+        self.send_managed(key, request, now).await;
+    }
+
+    async fn abandon(&mut self, key: (Uuid, usize), reason: &str, now: DateTime<Utc>) {
+        if !self.cancel_managed(key).await { return; }
+        let Some(managed) = self.managed.get_mut(&key) else { return };
+        managed.record(now, LifecycleState::Abandoned, reason);
+        self.finish_managed(key).await;
+    }
+
+    // --- ⚖️ MAKER/TAKER HYBRID ---
+
+    /// Picks the maker leg for an open and checks the taker hedge still pays at the current book.
+    /// `None` leaves the signal to be worked on both legs as usual.
+    fn plan_hybrid(&self, signal: &TradeSignal) -> Option<HybridPlan> {
+        if !self.hybrid.enabled || !self.orders.enabled { return None; }
+        let plan = HybridPlan::new(signal, &self.fees)?;
+        let (maker, hedge) = (&signal.legs[plan.maker_leg], &signal.legs[plan.hedge_leg]);
+        let price = target_price(maker.side, self.quote(&maker.venue, &maker.symbol), maker.limit_price?, &self.orders);
+        let edge = self.book(&hedge.venue, &hedge.symbol).and_then(|book| plan.edge(&self.fees, price, &book));
+        match edge {
+            Some(edge) if edge * 10_000.0 >= self.hybrid.min_edge_bps => {
+                if !self.quiet {
+                    println!("⚖️ [Hybrid] {} maker on {} @ {:.4}, hedge on {} clears {:.1}bps", signal.pair_id, maker.venue, price, hedge.venue, edge * 10_000.0);
+                }
+                Some(plan)
+            }
+            _ => {
+                println!("⚖️ [Hybrid] {} hedge on {} does not clear, working both legs", signal.pair_id, hedge.venue);
+                None
+            }
+        }
+    }
+
+    /// Whether a hybrid maker leg may go out at `price`. Non-hybrid legs always may.
+    fn hedge_clears(&self, (signal_id, leg): (Uuid, usize), price: f64) -> bool {
+        let Some(plan) = self.hybrids.get(&signal_id).filter(|plan| plan.maker_leg == leg) else { return true };
+        let hedge = &plan.signal.legs[plan.hedge_leg];
+        self.book(&hedge.venue, &hedge.symbol)
+            .and_then(|book| plan.edge(&self.fees, price, &book))
+            .is_some_and(|edge| edge * 10_000.0 >= self.hybrid.min_edge_bps)
+    }
+
+    /// Sends a taker IOC on the hedge venue for every maker fill seen since the last flush.
+    async fn flush_hedges(&mut self, now: DateTime<Utc>) {
+        for (signal_id, qty) in std::mem::take(&mut self.pending_hedges) {
+            let Some(plan) = self.hybrids.get_mut(&signal_id) else { continue };
+            plan.hedges += 1;
+            let (hedge_leg, pair_id) = (plan.hedge_leg, plan.signal.pair_id.clone());
+            let leg = &plan.signal.legs[hedge_leg];
+            let limit = leg.limit_price;
+            let Some(client) = self.clients.get(&leg.venue).cloned() else { continue };
+            let request = OrderRequest {
+                client_order_id: client_order_id(signal_id, hedge_leg, Some(('h', plan.hedges))),
+                symbol: leg.symbol.clone(),
+                side: leg.side,
+                qty,
+                price: leg.limit_price,
+                tif: TimeInForce::Ioc,
+                reduce_only: false,
+            };
+            match client.place_order(&request).await {
+                Ok(state) => self.track(signal_id, &pair_id, (hedge_leg, limit), state).await,
+                // Leg risk picks up whatever the hedge leaves behind
+                Err(e) => {
+                    self.alert(AlertLevel::Warning, format!("{} hedge on {} failed: {}", pair_id, client.venue(), e), now).await;
+                }
+            }
+        }
+    }
+
+    // --- 🛑 RISK & KILL SWITCH ---
+
+    /// Mid-price marks from the books, for valuing what we hold.
+    fn marks(&self) -> impl Fn(&str, &str) -> Option<f64> {
+        let books = self.books.clone();
+        move |venue, symbol| snapshot(books.as_ref(), venue, symbol).and_then(|book| Quote::from_book(&book)).map(|q| q.mid())
+    }
+
+    /// Picks up a kill switch engaged before this process started.
+    pub async fn restore_kill_switch(&mut self) {
+        let Some(bus) = self.bus.as_mut() else { return };
+        self.kill = KillSwitch::load(bus).await;
+        if let Some(kill) = &self.kill {
+            println!("🛑 [Risk] Kill switch engaged since {} by {}: {}", kill.since, kill.source, kill.reason);
+        }
+    }
+
+    /// Entry point for raw `trade:control` messages.
+    pub async fn handle_control_payload(&mut self, payload: &str) {
+        match ControlCommand::from_json(payload) {
+            Some(command) => self.control(&command, Utc::now()).await,
+            None => println!("⚠️ [Risk] Ignoring unreadable control message: {}", payload),
+        }
+    }
+
+    pub async fn control(&mut self, command: &ControlCommand, now: DateTime<Utc>) {
+        let was_flat = self.kill.as_ref().is_some_and(|k| k.flatten);
+        let next = KillSwitch::apply(self.kill.clone(), command, now);
+        let flatten = next.as_ref().is_some_and(|k| k.flatten) && !was_flat;
+        match (&self.kill, &next, command) {
+            (Some(_), None, ControlCommand::Resume { source }) => {
+                self.alert(AlertLevel::Info, format!("Kill switch released by {}; entries allowed again", source), now).await;
+            }
+            (None, Some(kill), _) => {
+                let action = if kill.flatten { "halting entries and flattening" } else { "halting entries" };
+                self.alert(AlertLevel::Critical, format!("Kill switch engaged by {}: {} ({})", kill.source, kill.reason, action), now).await;
+            }
+            (Some(_), Some(_), ControlCommand::Kill { source, reason, .. }) if flatten => {
+                self.alert(AlertLevel::Critical, format!("Kill switch escalated to flatten by {}: {}", source, reason), now).await;
+            }
+            _ => {}
+        }
+        self.kill = next;
+        if let Some(bus) = self.bus.as_mut() { KillSwitch::save(self.kill.as_ref(), bus).await; }
+        if flatten { self.flatten(now).await; }
+    }
+
+    /// Trips the kill switch on a breach that should not wait for an operator, and tells everyone else.
+    async fn check_risk(&mut self, now: DateTime<Utc>) {
+        let mark = self.marks();
+        let Some((breach, detail)) = self.risk.check_trips(now, &mark) else { return };
+        let command = ControlCommand::Kill {
+            flatten: self.risk.limits().flatten_on_breach,
+            reason: format!("{:?} limit breached: {}", breach, detail),
+            source: "executor".to_string(),
+        };
+        self.control(&command, now).await;
+        self.publish(CONTROL_CHANNEL, &command).await;
+    }
+
+    /// Pulls every resting order and closes every leg we hold with reduce-only IOCs.
+    async fn flatten(&mut self, now: DateTime<Utc>) {
+        let keys: Vec<(Uuid, usize)> = self.managed.keys().copied().collect();
+        for key in keys { self.abandon(key, "kill switch", now).await; }
+        let resting: Vec<(String, String, String)> = self.working.values()
+            .map(|w| (w.state.venue.clone(), w.state.symbol.clone(), w.state.client_order_id.clone()))
+            .collect();
+        for (venue, symbol, cid) in resting {
+            let Some(client) = self.clients.get(&venue).cloned() else { continue };
+            match client.cancel_order(&symbol, &cid).await {
+                Ok(state) => self.apply(state).await,
+                Err(e) => println!("⚠️ [Risk] cancel {} {}: {}", venue, cid, e),
+            }
+        }
+        // Nothing left to pair up; the flatten supersedes leg risk and hedging
+        self.risk.release_all();
+        self.groups.clear();
+        self.hybrids.clear();
+        self.pending_hedges.clear();
+
+        self.flattens += 1;
+        let exposures: Vec<_> = self.risk.exposures().cloned().collect();
+        for exposure in exposures {
+            let Some(client) = self.clients.get(&exposure.venue).cloned() else { continue };
+            let request = OrderRequest {
+                client_order_id: client_order_id(exposure.signal_id, exposure.leg, Some(('x', self.flattens))),
+                symbol: exposure.symbol.clone(),
+                side: if exposure.qty > 0.0 { Side::Sell } else { Side::Buy },
+                qty: exposure.qty.abs(),
+                price: None,
+                tif: TimeInForce::Ioc,
+                reduce_only: true,
+            };
+            match client.place_order(&request).await {
+                Ok(state) => self.track(exposure.signal_id, &exposure.pair_id, (exposure.leg, None), state).await,
+                Err(e) => {
+                    self.alert(AlertLevel::Critical, format!("{} flatten on {} failed: {}", exposure.pair_id, exposure.venue, e), now).await;
+                }
+            }
+        }
+    }
+
+    /// Completes or unwinds any signal that has been left holding one leg for too long.
+    async fn manage_leg_risk(&mut self, now: DateTime<Utc>) {
+        let ids: Vec<Uuid> = self.groups.keys().copied().collect();
+        for id in ids {
+            // A managed leg between quotes has no live order but is not done either
+            let settled = !self.working.values().any(|w| w.signal_id == id) && !self.managed.keys().any(|(sid, _)| *sid == id);
+            let Some(group) = self.groups.get_mut(&id) else { continue };
+            match group.assess(&self.leg_risk, now, settled) {
+                LegDecision::Balanced => {
+                    if settled {
+                        self.groups.remove(&id);
+                        self.hybrids.remove(&id);
+                        self.risk.release(id);
+                    }
+                }
+                LegDecision::Wait => {}
+                LegDecision::Repair(plan) => self.repair(id, plan, now).await,
+                LegDecision::GiveUp { unhedged_usd } => {
+                    let pair_id = group.pair_id.clone();
+                    self.groups.remove(&id);
+                    self.hybrids.remove(&id);
+                    self.risk.release(id);
+                    let legs: Vec<(Uuid, usize)> = self.managed.keys().filter(|(sid, _)| *sid == id).copied().collect();
+                    for key in legs { self.abandon(key, "leg risk gave up", now).await; }
+                    self.alert(AlertLevel::Critical, format!(
+                        "{} ({}) still legged by ${:.0} after {} repair(s); manual intervention needed",
+                        pair_id, id, unhedged_usd, self.leg_risk.max_repairs), now).await;
+                }
+            }
+        }
+    }
+
+    async fn repair(&mut self, signal_id: Uuid, plan: RepairPlan, now: DateTime<Utc>) {
+        let Some(group) = self.groups.get(&signal_id) else { return };
+        let (pair_id, attempt) = (group.pair_id.clone(), group.repairs);
+        self.alert(AlertLevel::Warning, format!(
+            "{} legged by ${:.0}: {:?} attempt {} ({:?} {:.6} on {})",
+            pair_id, plan.unhedged_usd, plan.action, attempt, plan.side, plan.qty, group.legs[plan.leg].venue), now).await;
+
+        // Stop the legs being repaired first so they cannot fill on top of the repair
+        for &leg in &plan.cancel_legs {
+            self.abandon((signal_id, leg), "superseded by leg-risk repair", now).await;
+        }
+        let resting: Vec<(String, String, String)> = self.working.values()
+            .filter(|w| w.signal_id == signal_id && plan.cancel_legs.contains(&w.leg))
+            .map(|w| (w.state.venue.clone(), w.state.symbol.clone(), w.state.client_order_id.clone()))
+            .collect();
+        for (venue, symbol, cid) in resting {
+            let Some(client) = self.clients.get(&venue).cloned() else { continue };
+            match client.cancel_order(&symbol, &cid).await {
+                Ok(state) => self.apply(state).await,
+                Err(e) => println!("⚠️ [Executor] cancel {} {}: {}", venue, cid, e),
+            }
+        }
+
+        // The cancel may have raced a fill; size the repair off what is left
+        let Some(group) = self.groups.get(&signal_id) else { return };
+        let Some((_, _, gap, _)) = group.imbalance() else { return };
+        let leg = &group.legs[plan.leg];
+        // Completing a leg is still measured against its price; an unwind has none
+        let reference = Some(leg.price).filter(|price| plan.side == leg.side && *price > 0.0);
+        let Some(client) = self.clients.get(&leg.venue).cloned() else { return };
+        let request = OrderRequest {
+            client_order_id: client_order_id(signal_id, plan.leg, Some(('r', attempt))),
+            symbol: leg.symbol.clone(),
+            side: plan.side,
+            qty: gap.min(plan.qty),
+            price: None,
+            tif: TimeInForce::Ioc,
+            reduce_only: plan.reduce_only,
+        };
+        match client.place_order(&request).await {
+            Ok(state) => self.track(signal_id, &pair_id, (plan.leg, reference), state).await,
+            Err(e) => {
+                self.alert(AlertLevel::Critical, format!("{} repair order on {} failed: {}", pair_id, client.venue(), e), now).await;
+            }
+        }
+    }
+}
+
+fn snapshot(books: Option<&Arc<MarketStore>>, venue: &str, symbol: &str) -> Option<Arc<MarketState>> {
+    let books = books?;
+    books.snapshot(books.exchange_id(venue)?, books.symbol_id(coin_symbol(venue, symbol))?)
+}
+
+// --- 🚀 MAIN RUNNER ---
+
+pub async fn run(clients: Clients) -> Result<(), Box<dyn std::error::Error>> {
+    let client = redis::Client::open("redis://localhost/")?;
+    let config = ExecutionConfig::load();
+    let venues: Vec<&str> = clients.keys().map(String::as_str).collect();
+    let books = Arc::new(MarketStore::new(&venues, MAX_SYMBOLS));
+    let mut updates = spawn_redis_feed(Arc::clone(&books));
+    let (fill_tx, mut fill_rx) = mpsc::unbounded_channel();
+    let mut ledger = Ledger::from_env("ledger.jsonl");
+    let mut bus = client.get_multiplexed_async_connection().await?;
+    let mut executor = Executor::new(clients.clone(), bus.clone())
+        .with_config(&config)
+        .with_ledger(&ledger)
+        .with_fees(&FeeModel::load())
+        .with_books(books)
+        .with_fill_tap(fill_tx);
+    let mut recon = FundingRecon::new(config.funding_recon);
+    let (recon_tx, mut recon_rx) = mpsc::unbounded_channel();
+    let mut recon_timer = interval(Duration::from_secs(config.funding_recon.interval_secs.max(1)));
+    let mut monitor = AccountMonitor::new(config.accounts);
+    let (accounts_tx, mut accounts_rx) = mpsc::unbounded_channel();
+    let mut accounts_timer = interval(Duration::from_millis(config.accounts.poll_ms.max(1)));
+    accounts_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    executor.restore_kill_switch().await;
+
+    let mut pubsub_conn = client.get_async_pubsub().await?;
+    pubsub_conn.subscribe(&[SIGNAL_CHANNEL, CONTROL_CHANNEL]).await?;
+    let mut stream = pubsub_conn.into_on_message();
+
+    let mut poll_timer = interval(Duration::from_millis(POLL_MS));
+    poll_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    println!("🛠️  Executor listening on {}", SIGNAL_CHANNEL);
+
+    loop {
+        tokio::select! {
+            msg = stream.next() => {
+                let Some(msg) = msg else { break };
+                let payload: String = msg.get_payload().unwrap_or_default();
+                if msg.get_channel_name() == CONTROL_CHANNEL {
+                    executor.handle_control_payload(&payload).await;
+                } else {
+                    executor.handle_payload(&payload).await;
+                }
+            }
+            Some(_) = updates.recv() => executor.on_book(Utc::now()).await,
+            Some(executed) = fill_rx.recv() => ledger.record_fill(&executed),
+            _ = poll_timer.tick() => executor.poll_orders(Utc::now()).await,
+            _ = recon_timer.tick(), if recon.config().enabled => {
+                // Venue history can take a while to page through; trading does not wait for it
+                let since = Utc::now() - chrono::Duration::hours(recon.config().lookback_hours);
+                let held = ledger.instruments().cloned().collect();
+                let (clients, conn, tx) = (clients.clone(), bus.clone(), recon_tx.clone());
+                tokio::spawn(async move { let _ = tx.send(funding_recon::fetch(clients, conn, since, held).await); });
+            }
+            Some(fetched) = recon_rx.recv() => {
+                let report = recon.reconcile(&ledger, &fetched, Utc::now());
+                for payment in &report.unbooked { ledger.record_funding(payment); }
+                recon.publish(&report, &mut bus).await;
+            }
+            _ = accounts_timer.tick(), if monitor.config().enabled => {
+                let (clients, tx) = (clients.clone(), accounts_tx.clone());
+                tokio::spawn(async move { let _ = tx.send(account_monitor::poll(clients).await); });
+            }
+            Some(venues) = accounts_rx.recv() => {
+                let snapshot = monitor.on_poll(venues, Utc::now(), &mut bus).await;
+                executor.block_venues(&snapshot.blocked);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::exchange::ExchangeError;
+    use crate::execution::mock_exchange::{MockExchange, MockFillMode};
+    use crate::ingestors::signals::SignalLeg;
+
+    const QTY: f64 = 0.1;
+
+    /// Binance and Bybit mocks, plus an executor wired to both with a fill tap.
+    fn setup(mode: MockFillMode) -> (Arc<MockExchange>, Arc<MockExchange>, Executor, mpsc::UnboundedReceiver<ExecutedFill>) {
+        let binance = Arc::new(MockExchange::new("binance", mode));
+        let bybit = Arc::new(MockExchange::new("bybit", mode));
+        let clients: Clients = HashMap::from([
+            ("binance".to_string(), Arc::clone(&binance) as Arc<dyn ExchangeClient>),
+            ("bybit".to_string(), Arc::clone(&bybit) as Arc<dyn ExchangeClient>),
+        ]);
+        let (tap, fills) = mpsc::unbounded_channel();
+        (binance, bybit, Executor::offline(clients).with_fill_tap(tap).quiet(), fills)
+    }
+
+    /// Long Binance, short Bybit; closes flip both sides.
+    fn signal(kind: SignalKind, created_at: DateTime<Utc>) -> TradeSignal {
+        let (long, short) = if kind == SignalKind::OpenLimit { (Side::Buy, Side::Sell) } else { (Side::Sell, Side::Buy) };
+        let leg = |venue: &str, side, price| SignalLeg { venue: venue.to_string(), symbol: "BTCUSDT".to_string(), side, size: QTY, limit_price: Some(price) };
+        TradeSignal::new(kind, "BTC-binance-bybit", created_at, 5_000, vec![leg("binance", long, 100_000.0), leg("bybit", short, 100_050.0)])
+    }
+
+    fn drain(fills: &mut mpsc::UnboundedReceiver<ExecutedFill>) -> Vec<ExecutedFill> {
+        std::iter::from_fn(|| fills.try_recv().ok()).collect()
+    }
+
+    fn held(executor: &Executor, venue: &str) -> f64 {
+        executor.positions.get(&(venue.to_string(), "BTCUSDT".to_string())).copied().unwrap_or(0.0)
+    }
+
+    async fn venue_qty(exchange: &MockExchange) -> f64 {
+        exchange.positions().await.unwrap().iter().map(|p| p.qty).sum()
+    }
+
+    #[tokio::test]
+    async fn signals_become_orders_fills_and_positions() {
+        let (binance, bybit, mut executor, mut fills) = setup(MockFillMode::Immediate);
+        let now = Utc::now();
+        let open = signal(SignalKind::OpenLimit, now);
+        executor.execute(open.clone(), now).await;
+
+        let executed = drain(&mut fills);
+        assert_eq!(executed.len(), 2);
+        for (fill, (venue, side, price)) in executed.iter().zip([("binance", Side::Buy, 100_000.0), ("bybit", Side::Sell, 100_050.0)]) {
+            assert_eq!((fill.signal_id, fill.pair_id.as_str()), (open.id, "BTC-binance-bybit"));
+            assert_eq!((fill.fill.venue.as_str(), fill.fill.side, fill.fill.qty, fill.fill.price), (venue, side, QTY, price));
+            assert_eq!(fill.reference_price, Some(price));
+        }
+        assert_eq!((held(&executor, "binance"), held(&executor, "bybit")), (QTY, -QTY));
+        assert_eq!((venue_qty(&binance).await, venue_qty(&bybit).await), (QTY, -QTY));
+        assert!(executor.working.is_empty(), "filled orders are no longer tracked");
+
+        let mut close = signal(SignalKind::CloseLimit, now);
+        close.opens = Some(open.id);
+        executor.execute(close.clone(), now).await;
+        let executed = drain(&mut fills);
+        assert_eq!(executed.len(), 2);
+        assert!(executed.iter().all(|f| f.signal_id == close.id));
+        assert_eq!((held(&executor, "binance"), held(&executor, "bybit")), (0.0, 0.0));
+        assert_eq!((venue_qty(&binance).await, venue_qty(&bybit).await), (0.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn resting_orders_fill_as_they_are_polled() {
+        let (binance, bybit, mut executor, mut fills) = setup(MockFillMode::Never);
+        let now = Utc::now();
+        let open = signal(SignalKind::OpenLimit, now);
+        executor.execute(open.clone(), now).await;
+        assert!(drain(&mut fills).is_empty());
+        assert_eq!(executor.working.len(), 2);
+
+        let (long, short) = (client_order_id(open.id, 0, None), client_order_id(open.id, 1, None));
+        // Under the unhedged limit, so leg risk leaves it alone
+        binance.fill_resting(&long, 0.01).unwrap();
+        executor.poll_orders(now + chrono::Duration::milliseconds(100)).await;
+        let executed = drain(&mut fills);
+        assert_eq!(executed.len(), 1);
+        assert_eq!((executed[0].fill.client_order_id.as_str(), executed[0].fill.qty), (long.as_str(), 0.01));
+        assert_eq!(held(&executor, "binance"), 0.01);
+
+        binance.fill_resting(&long, 1.0).unwrap();
+        bybit.fill_resting(&short, 1.0).unwrap();
+        executor.poll_orders(now + chrono::Duration::milliseconds(200)).await;
+        let executed = drain(&mut fills);
+        assert!((executed.iter().map(|f| f.fill.qty).sum::<f64>() - 0.19).abs() < 1e-12);
+        assert!((held(&executor, "binance") - QTY).abs() < 1e-12);
+        assert_eq!(held(&executor, "bybit"), -QTY);
+        assert!(executor.working.is_empty());
+    }
+
+    #[tokio::test]
+    async fn unwinds_cancel_the_leading_order_too() {
+        let (binance, bybit, mut executor, mut fills) = setup(MockFillMode::Never);
+        let now = Utc::now();
+        let open = signal(SignalKind::OpenLimit, now);
+        executor.execute(open.clone(), now).await;
+        let (long, short) = (client_order_id(open.id, 0, None), client_order_id(open.id, 1, None));
+
+        // Over the unhedged limit, so the open is unwound straight away
+        binance.fill_resting(&long, 0.04).unwrap();
+        binance.set_fill_mode(MockFillMode::Immediate);
+        binance.set_mark("BTCUSDT", 100_000.0);
+        executor.poll_orders(now + chrono::Duration::milliseconds(100)).await;
+        let executed = drain(&mut fills);
+        assert_eq!(executed.iter().map(|f| (f.fill.side, f.fill.qty)).collect::<Vec<_>>(), [(Side::Buy, 0.04), (Side::Sell, 0.04)]);
+        assert!(held(&executor, "binance").abs() < 1e-12);
+
+        let leading = binance.order_status("BTCUSDT", &long).await.unwrap();
+        assert_eq!((leading.status, leading.filled_qty), (OrderStatus::Canceled, 0.04));
+        assert_eq!(bybit.order_status("BTCUSDT", &short).await.unwrap().status, OrderStatus::Canceled);
+        // The venue can no longer fill the rest of the leading order behind the unwind
+        binance.fill_resting(&long, 1.0).unwrap();
+        executor.poll_orders(now + chrono::Duration::milliseconds(200)).await;
+        assert!(drain(&mut fills).is_empty());
+        assert!(venue_qty(&binance).await.abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn repeated_signals_are_executed_once() {
+        let (binance, _, mut executor, mut fills) = setup(MockFillMode::Immediate);
+        let now = Utc::now();
+        let open = signal(SignalKind::OpenLimit, now);
+        executor.execute(open.clone(), now).await;
+        executor.execute(open.clone(), now).await;
+        executor.handle_payload(&open.to_json()).await;
+
+        assert_eq!(drain(&mut fills).len(), 2);
+        assert_eq!(held(&executor, "binance"), QTY);
+        assert_eq!(venue_qty(&binance).await, QTY);
+    }
+
+    #[tokio::test]
+    async fn stale_signals_are_dropped_before_any_order() {
+        let (binance, bybit, mut executor, mut fills) = setup(MockFillMode::Immediate);
+        let now = Utc::now();
+        let stale = signal(SignalKind::OpenLimit, now - chrono::Duration::seconds(10));
+        executor.execute(stale.clone(), now).await;
+        executor.handle_payload(&signal(SignalKind::OpenLimit, Utc::now() - chrono::Duration::seconds(10)).to_json()).await;
+
+        assert!(drain(&mut fills).is_empty());
+        assert!(executor.working.is_empty() && executor.groups.is_empty());
+        assert!(binance.positions().await.unwrap().is_empty() && bybit.positions().await.unwrap().is_empty());
+        let never_sent = binance.order_status("BTCUSDT", &client_order_id(stale.id, 0, None)).await;
+        assert!(matches!(never_sent, Err(ExchangeError::NotFound)));
+
+        // Exactly at expiry still counts as stale
+        let expiring = signal(SignalKind::OpenLimit, now - chrono::Duration::milliseconds(5_000));
+        executor.execute(expiring, now).await;
+        assert!(drain(&mut fills).is_empty());
+    }
+
+    #[tokio::test]
+    async fn signals_with_an_unwired_venue_leave_no_state_behind() {
+        let (binance, _, mut executor, mut fills) = setup(MockFillMode::Immediate);
+        let now = Utc::now();
+        let mut open = signal(SignalKind::OpenLimit, now);
+        open.legs[1].venue = "okx".to_string();
+        executor.execute(open.clone(), now).await;
+
+        assert!(drain(&mut fills).is_empty());
+        assert!(executor.managed.is_empty() && executor.groups.is_empty() && executor.working.is_empty());
+        assert!(binance.positions().await.unwrap().is_empty());
+        assert_eq!(executor.risk.exposures().count(), 0);
+    }
+}
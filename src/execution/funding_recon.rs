@@ -0,0 +1,349 @@
+use super::alerts::{Alert, AlertLevel, ALERTS_CHANNEL};
+use super::exchange::FundingIncome;
+use super::executor::Clients;
+use super::ledger::Ledger;
+use super::paper::FundingPayment;
+use crate::ingestors::funding_rates;
+use crate::ingestors::pnl_model::funding_interval_hours;
+use crate::ingestors::signals::coin_symbol;
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+pub const FUNDING_RECON_CHANNEL: &str = "trade:funding_recon";
+const QTY_EPS: f64 = 1e-9;
+/// Amount differences below this are rounding, whatever the tolerance says.
+const MIN_AMOUNT_GAP_USD: f64 = 0.01;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FundingReconConfig {
+    pub enabled: bool,
+    pub interval_secs: u64,
+    /// How far back each run pulls venue history. Bybit serves at most 24h per request.
+    pub lookback_hours: i64,
+    /// How far a payment may land from its scheduled payout and still count for it.
+    pub match_window_secs: i64,
+    /// Venue-settled rate vs the rate the scanner recorded before the payout.
+    pub rate_tolerance_bps: f64,
+    /// Paid amount vs the ledger's position times the rate, as a share of the expected amount.
+    pub amount_tolerance: f64,
+}
+
+impl Default for FundingReconConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: 900,
+            lookback_hours: 24,
+            match_window_secs: 300,
+            rate_tolerance_bps: 0.5,
+            amount_tolerance: 0.1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DiscrepancyKind {
+    /// The ledger held a position over a payout and the venue booked nothing.
+    Missed,
+    /// The venue booked funding on something the ledger does not hold.
+    Unexpected,
+    /// The venue's position size differs from the ledger's.
+    Size,
+    /// The venue's settled rate differs from the scanner's.
+    Rate,
+    /// The amount (or its sign) differs from position times rate.
+    Amount,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingDiscrepancy {
+    pub kind: DiscrepancyKind,
+    pub venue: String,
+    pub symbol: String,
+    pub payout: DateTime<Utc>,
+    pub detail: String,
+}
+
+/// Venue funding history for one run, each payment with the scanner's rate before it.
+pub struct FetchedFunding {
+    pub since: DateTime<Utc>,
+    pub incomes: Vec<(FundingIncome, Option<f64>)>,
+    /// Venues whose history was read, empty or not.
+    pub read: Vec<String>,
+    /// Venues whose history could not be read, with the error.
+    pub failed: Vec<(String, String)>,
+    /// (venue, venue symbol) -> payout interval in hours, from the rate the scanner last recorded.
+    pub intervals: HashMap<(String, String), u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReconReport {
+    pub ts: DateTime<Utc>,
+    pub since: DateTime<Utc>,
+    /// Venue payments matched to a ledger position.
+    pub matched: usize,
+    pub discrepancies: Vec<FundingDiscrepancy>,
+    pub failed: Vec<(String, String)>,
+    /// Matched payments the ledger has not booked yet.
+    #[serde(skip)]
+    pub unbooked: Vec<FundingPayment>,
+}
+
+/// Pulls funding history from every venue and looks up the rate the scanner had recorded for each
+/// payout, plus the payout interval of every instrument in `held`.
+pub async fn fetch(clients: Clients, mut conn: redis::aio::MultiplexedConnection, since: DateTime<Utc>, held: Vec<(String, String)>) -> FetchedFunding {
+    let mut fetched = FetchedFunding { since, incomes: Vec::new(), read: Vec::new(), failed: Vec::new(), intervals: HashMap::new() };
+    let now = Utc::now();
+    for (venue, symbol) in held {
+        let Some(rate) = funding_rates::rate_at(&mut conn, &venue, coin_symbol(&venue, &symbol), now).await else { continue };
+        fetched.intervals.insert((venue.clone(), symbol), funding_interval_hours(&venue, rate));
+    }
+    for (venue, client) in clients {
+        match client.funding_history(since).await {
+            Ok(incomes) => {
+                for income in incomes {
+                    let rate = funding_rates::rate_at(&mut conn, &venue, coin_symbol(&venue, &income.symbol), income.ts).await;
+                    fetched.incomes.push((income, rate));
+                }
+                fetched.read.push(venue);
+            }
+            Err(e) => fetched.failed.push((venue, e.to_string())),
+        }
+    }
+    fetched
+}
+
+/// Matches venue payments to the ledger and remembers which discrepancies were already reported,
+/// since consecutive runs overlap.
+pub struct FundingRecon {
+    config: FundingReconConfig,
+    reported: HashSet<(DiscrepancyKind, String, String, i64)>,
+}
+
+impl FundingRecon {
+    pub fn new(config: FundingReconConfig) -> Self {
+        Self { config, reported: HashSet::new() }
+    }
+
+    pub fn config(&self) -> &FundingReconConfig {
+        &self.config
+    }
+
+    pub fn reconcile(&self, ledger: &Ledger, fetched: &FetchedFunding, now: DateTime<Utc>) -> ReconReport {
+        let mut report = ReconReport {
+            ts: now,
+            since: fetched.since,
+            matched: 0,
+            discrepancies: Vec::new(),
+            failed: fetched.failed.clone(),
+            unbooked: Vec::new(),
+        };
+        let mut flag = |kind, income: &FundingIncome, detail: String| report.discrepancies.push(FundingDiscrepancy {
+            kind,
+            venue: income.venue.clone(),
+            symbol: income.symbol.clone(),
+            payout: income.ts,
+            detail,
+        });
+
+        for (income, scanner_rate) in &fetched.incomes {
+            let Some((qty, price)) = ledger.held_at(&income.venue, &income.symbol, income.ts).filter(|(qty, _)| qty.abs() > QTY_EPS) else {
+                flag(DiscrepancyKind::Unexpected, income, format!("paid {:+.4} but the ledger holds nothing", income.amount));
+                continue;
+            };
+            if let Some(venue_qty) = income.qty.filter(|venue_qty| (venue_qty - qty).abs() > QTY_EPS.max(qty.abs() * 0.01)) {
+                flag(DiscrepancyKind::Size, income, format!("venue position {} vs ledger {}", venue_qty, qty));
+            }
+            if let (Some(settled), Some(recorded)) = (income.rate, scanner_rate) {
+                if (settled - recorded).abs() * 10_000.0 > self.config.rate_tolerance_bps {
+                    flag(DiscrepancyKind::Rate, income, format!("settled at {:.4}bp, scanner recorded {:.4}bp", settled * 10_000.0, recorded * 10_000.0));
+                }
+            }
+            // Longs pay positive funding; the last fill price stands in for the mark at payout
+            let rate = income.rate.or(*scanner_rate);
+            if let Some(rate) = rate {
+                let expected = -qty * price * rate;
+                let gap = (income.amount - expected).abs();
+                if gap > MIN_AMOUNT_GAP_USD && gap > expected.abs() * self.config.amount_tolerance {
+                    flag(DiscrepancyKind::Amount, income, format!("paid {:+.4}, expected {:+.4} ({} @ {:.2} x {:.4}bp)", income.amount, expected, qty, price, rate * 10_000.0));
+                }
+            }
+            if !ledger.has_funding(&income.venue, &income.symbol, income.ts) {
+                report.unbooked.push(FundingPayment {
+                    venue: income.venue.clone(),
+                    symbol: income.symbol.clone(),
+                    qty,
+                    rate: rate.unwrap_or(0.0),
+                    amount: income.amount,
+                    ts: income.ts,
+                });
+            }
+            report.matched += 1;
+        }
+
+        // Every scheduled payout the ledger was exposed to should have a payment near it
+        let window = Duration::seconds(self.config.match_window_secs);
+        for (venue, symbol) in ledger.instruments() {
+            // A venue we could not read proves nothing either way
+            if !fetched.read.contains(venue) { continue; }
+            // Without a recent sample from the scanner, assume the venue's usual schedule
+            let hours = fetched.intervals.get(&(venue.clone(), symbol.clone())).copied().unwrap_or_else(|| funding_interval_hours(venue, 0.0));
+            let step = Duration::hours(hours as i64);
+            let Ok(mut payout) = fetched.since.duration_trunc(step) else { continue };
+            while payout + window <= now {
+                let held = ledger.held_at(venue, symbol, payout).is_some_and(|(qty, _)| qty.abs() > QTY_EPS);
+                let paid = fetched.incomes.iter()
+                    .any(|(income, _)| &income.venue == venue && &income.symbol == symbol && (income.ts - payout).abs() <= window);
+                if payout >= fetched.since && held && !paid {
+                    report.discrepancies.push(FundingDiscrepancy {
+                        kind: DiscrepancyKind::Missed,
+                        venue: venue.clone(),
+                        symbol: symbol.clone(),
+                        payout,
+                        detail: format!("no payment within {}s of the {} payout", self.config.match_window_secs, payout.format("%H:%M")),
+                    });
+                }
+                payout += step;
+            }
+        }
+        report
+    }
+
+    /// Discrepancies in `report` that no earlier run has reported, marking them as reported.
+    fn fresh<'a>(&mut self, report: &'a ReconReport) -> Vec<&'a FundingDiscrepancy> {
+        report.discrepancies.iter()
+            .filter(|d| self.reported.insert((d.kind, d.venue.clone(), d.symbol.clone(), d.payout.timestamp_millis())))
+            .collect()
+    }
+
+    /// Prints the run, alerts on discrepancies not reported by an earlier run and publishes the report.
+    pub async fn publish(&mut self, report: &ReconReport, conn: &mut redis::aio::MultiplexedConnection) {
+        println!("🔎 [FundingRecon] {} payment(s) matched since {}, {} discrepancy(ies), {} newly booked",
+            report.matched, report.since.format("%Y-%m-%d %H:%M"), report.discrepancies.len(), report.unbooked.len());
+        for (venue, error) in &report.failed {
+            println!("⚠️ [FundingRecon] Could not read {} funding history: {}", venue, error);
+        }
+        for d in self.fresh(report) {
+            let message = format!("{:?} funding on {} {} at {}: {}", d.kind, d.venue, d.symbol, d.payout.format("%Y-%m-%d %H:%M"), d.detail);
+            let alert = Alert::new(AlertLevel::Warning, "funding_recon", message, report.ts);
+            println!("{} [FundingRecon] {}", alert.icon(), alert.message);
+            if let Ok(raw) = serde_json::to_string(&alert) {
+                let _: () = conn.publish(ALERTS_CHANNEL, raw).await.unwrap_or(());
+            }
+        }
+        if let Ok(raw) = serde_json::to_string(report) {
+            let _: () = conn.publish(FUNDING_RECON_CHANNEL, raw).await.unwrap_or(());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::exchange::Fill;
+    use crate::execution::executor::ExecutedFill;
+    use crate::ingestors::signals::Side;
+    use chrono::TimeZone;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, hour, 0, 0).unwrap()
+    }
+
+    /// A ledger that went long 1 Binance BTCUSDT and short 2 Bybit BTCUSDT at 100 at 07:00.
+    fn ledger() -> Ledger {
+        let mut ledger = Ledger::default();
+        for (venue, side, qty) in [("binance", Side::Buy, 1.0), ("bybit", Side::Sell, 2.0)] {
+            ledger.record_fill(&ExecutedFill {
+                signal_id: uuid::Uuid::nil(),
+                pair_id: "BTC-binance-bybit".to_string(),
+                fill: Fill { venue: venue.to_string(), client_order_id: "cid".to_string(), symbol: "BTCUSDT".to_string(), side, qty, price: 100.0, fee: 0.0, ts: at(7) },
+                reference_price: Some(100.0),
+            });
+        }
+        ledger
+    }
+
+    fn income(venue: &str, symbol: &str, hour: u32, (qty, rate, amount): (f64, f64, f64)) -> FundingIncome {
+        FundingIncome { venue: venue.to_string(), symbol: symbol.to_string(), amount, ts: at(hour), rate: Some(rate), qty: Some(qty) }
+    }
+
+    fn fetched(incomes: Vec<(FundingIncome, Option<f64>)>) -> FetchedFunding {
+        FetchedFunding { since: at(6), incomes, read: vec!["binance".to_string(), "bybit".to_string()], failed: Vec::new(), intervals: HashMap::new() }
+    }
+
+    fn kinds(report: &ReconReport) -> Vec<(DiscrepancyKind, &str, &str, u32)> {
+        use chrono::Timelike;
+        let mut kinds: Vec<_> = report.discrepancies.iter().map(|d| (d.kind, d.venue.as_str(), d.symbol.as_str(), d.payout.hour())).collect();
+        kinds.sort_by_key(|k| format!("{:?}", k));
+        kinds
+    }
+
+    #[test]
+    fn payouts_are_classified_against_the_ledger() {
+        let recon = FundingRecon::new(FundingReconConfig::default());
+        let fetched = fetched(vec![
+            // Long 1 at 100 paying 1bp: exactly as expected
+            (income("binance", "BTCUSDT", 8, (1.0, 0.0001, -0.01)), Some(0.0001)),
+            // Wrong size, a rate the scanner never saw and the wrong sign
+            (income("bybit", "BTCUSDT", 8, (-3.0, 0.0003, -5.0)), Some(0.0001)),
+            (income("bybit", "BTCUSDT", 16, (-2.0, 0.0001, 0.02)), Some(0.0001)),
+            (income("bybit", "ETHUSDT", 8, (1.0, 0.0001, 0.5)), None),
+        ]);
+        let report = recon.reconcile(&ledger(), &fetched, at(17));
+
+        assert_eq!(kinds(&report), vec![
+            (DiscrepancyKind::Amount, "bybit", "BTCUSDT", 8),
+            (DiscrepancyKind::Missed, "binance", "BTCUSDT", 16),
+            (DiscrepancyKind::Rate, "bybit", "BTCUSDT", 8),
+            (DiscrepancyKind::Size, "bybit", "BTCUSDT", 8),
+            (DiscrepancyKind::Unexpected, "bybit", "ETHUSDT", 8),
+        ]);
+        assert_eq!(report.matched, 3);
+        assert_eq!(report.unbooked.len(), 3, "none of the matched payments were booked yet");
+    }
+
+    #[test]
+    fn unread_venues_and_booked_payments_are_left_alone() {
+        let recon = FundingRecon::new(FundingReconConfig::default());
+        let mut ledger = ledger();
+        let paid = income("binance", "BTCUSDT", 8, (1.0, 0.0001, -0.01));
+        ledger.record_funding(&FundingPayment { venue: paid.venue.clone(), symbol: paid.symbol.clone(), qty: 1.0, rate: 0.0001, amount: -0.01, ts: paid.ts });
+        let mut fetched = fetched(vec![(paid, Some(0.0001))]);
+        fetched.read.retain(|venue| venue == "binance");
+
+        let report = recon.reconcile(&ledger, &fetched, at(9));
+        assert!(report.discrepancies.is_empty(), "{:?}", report.discrepancies);
+        assert_eq!((report.matched, report.unbooked.len()), (1, 0));
+    }
+
+    #[test]
+    fn missed_payouts_follow_the_recorded_interval() {
+        let recon = FundingRecon::new(FundingReconConfig::default());
+        let mut fetched = fetched(Vec::new());
+        fetched.read.retain(|venue| venue == "binance");
+        let missed = |fetched: &FetchedFunding| recon.reconcile(&ledger(), fetched, at(10)).discrepancies.len();
+        // The usual 8h schedule only has 08:00 in range
+        assert_eq!(missed(&fetched), 1);
+        // An extreme rate moved Binance to hourly payouts: 07:00, 08:00 and 09:00
+        fetched.intervals.insert(("binance".to_string(), "BTCUSDT".to_string()), 1);
+        assert_eq!(missed(&fetched), 3);
+    }
+
+    #[test]
+    fn overlapping_runs_report_each_discrepancy_once() {
+        let mut recon = FundingRecon::new(FundingReconConfig::default());
+        let fetched = fetched(vec![(income("bybit", "ETHUSDT", 8, (1.0, 0.0001, 0.5)), None)]);
+        let first = recon.reconcile(&ledger(), &fetched, at(9));
+        assert_eq!(recon.fresh(&first).len(), 3, "the unexpected ETH payment and both legs' missed 08:00 payouts");
+        let fresh = recon.fresh(&first).len();
+
+        // The next run still sees the same window, plus a new miss at 16:00
+        let second = recon.reconcile(&ledger(), &fetched, at(17));
+        let new: Vec<_> = recon.fresh(&second).into_iter().map(|d| (d.venue.as_str(), d.payout)).collect();
+        assert_eq!((fresh, new.len()), (0, 2));
+        assert!(new.iter().all(|(_, payout)| *payout == at(16)));
+    }
+}
@@ -0,0 +1,113 @@
+use crate::ingestors::fees::{FeeModel, OrderType};
+use crate::ingestors::market_store::MarketState;
+use crate::ingestors::signals::{Side, SignalKind, TradeSignal};
+use crate::ingestors::sizing::calculate_vwap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HybridConfig {
+    /// Work opens as one maker leg hedged by a taker on the other venue. Needs the order manager on.
+    pub enabled: bool,
+    /// Edge the pair must keep, after swapping one leg to taker, before the maker is posted or requoted.
+    pub min_edge_bps: f64,
+}
+
+impl Default for HybridConfig {
+    fn default() -> Self {
+        Self { enabled: false, min_edge_bps: 0.0 }
+    }
+}
+
+/// An open signal being worked maker-on-one-leg, taker-on-the-other.
+#[derive(Debug, Clone)]
+pub struct HybridPlan {
+    pub signal: TradeSignal,
+    pub maker_leg: usize,
+    pub hedge_leg: usize,
+    /// Hedge orders sent so far; numbers their client order IDs.
+    pub hedges: u32,
+}
+
+impl HybridPlan {
+    /// Hybrid only applies to two-leg opens with a limit on both legs.
+    pub fn new(signal: &TradeSignal, fees: &FeeModel) -> Option<Self> {
+        if signal.kind != SignalKind::OpenLimit || signal.legs.len() != 2 { return None; }
+        if signal.legs.iter().any(|leg| leg.limit_price.is_none()) { return None; }
+
+        // Rest where the maker rate is best, counting what the taker costs on the other side
+        let cost = |maker: usize| {
+            fees.rate(&signal.legs[maker].venue, OrderType::Maker) + fees.rate(&signal.legs[1 - maker].venue, OrderType::Taker)
+        };
+        let maker_leg = if cost(1) < cost(0) { 1 } else { 0 };
+        Some(Self { signal: signal.clone(), maker_leg, hedge_leg: 1 - maker_leg, hedges: 0 })
+    }
+
+    /// Expected edge if the maker fills at `maker_price` and the hedge sweeps `hedge_book` for the
+    /// leg's notional, priced like the scanner's. `None` when the book cannot absorb the hedge.
+    ///
+    /// Starts from the signal's own edge and adjusts it for the better/worse entry basis and for
+    /// trading one leg maker and the other taker instead of the fee plan's open order type.
+    pub fn edge(&self, fees: &FeeModel, maker_price: f64, hedge_book: &MarketState) -> Option<f64> {
+        let hedge = &self.signal.legs[self.hedge_leg];
+        let levels = match hedge.side { Side::Buy => &hedge_book.asks, Side::Sell => &hedge_book.bids };
+        let hedge_price = calculate_vwap(levels, hedge.size * hedge.limit_price?)?;
+
+        let sell = self.signal.legs.iter().position(|leg| leg.side == Side::Sell)?;
+        let buy = 1 - sell;
+        let price = |leg: usize| if leg == self.maker_leg { maker_price } else { hedge_price };
+        let basis = |sell_px: f64, buy_px: f64| (sell_px - buy_px) / buy_px;
+        let signal_basis = basis(self.signal.legs[sell].limit_price?, self.signal.legs[buy].limit_price?);
+
+        let venue = |leg: usize| self.signal.legs[leg].venue.as_str();
+        let planned = fees.rate(venue(self.maker_leg), fees.plan.open) + fees.rate(venue(self.hedge_leg), fees.plan.open);
+        let hybrid = fees.rate(venue(self.maker_leg), OrderType::Maker) + fees.rate(venue(self.hedge_leg), OrderType::Taker);
+
+        Some(self.signal.expected_edge + basis(price(sell), price(buy)) - signal_basis - (hybrid - planned))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestors::signals::SignalLeg;
+    use chrono::Utc;
+
+    fn open(kind: SignalKind, venues: &[&str]) -> TradeSignal {
+        let legs = venues.iter().zip([Side::Sell, Side::Buy]).map(|(venue, side)| SignalLeg {
+            venue: venue.to_string(),
+            symbol: "BTCUSDT".to_string(),
+            side,
+            size: 0.01,
+            limit_price: Some(100_000.0),
+        }).collect();
+        TradeSignal::new(kind, "BINANCE-BYBIT-BTC", Utc::now(), 5_000, legs)
+    }
+
+    fn maker(signal: &TradeSignal, fees: &FeeModel) -> Option<String> {
+        HybridPlan::new(signal, fees).map(|plan| signal.legs[plan.maker_leg].venue.clone())
+    }
+
+    #[test]
+    fn the_maker_rests_where_maker_plus_hedge_is_cheapest() {
+        let mut fees = FeeModel::default();
+        // Same maker rates, so the cheaper binance taker hedges: bybit rests whichever leg it is
+        assert_eq!(maker(&open(SignalKind::OpenLimit, &["binance", "bybit"]), &fees).as_deref(), Some("bybit"));
+        assert_eq!(maker(&open(SignalKind::OpenLimit, &["bybit", "binance"]), &fees).as_deref(), Some("bybit"));
+
+        // A maker rebate outweighs the dearer hedge
+        fees.schedules.get_mut("binance").unwrap().maker = -0.0001;
+        assert_eq!(maker(&open(SignalKind::OpenLimit, &["binance", "bybit"]), &fees).as_deref(), Some("binance"));
+    }
+
+    #[test]
+    fn only_two_leg_limit_opens_go_hybrid() {
+        let fees = FeeModel::default();
+        assert!(HybridPlan::new(&open(SignalKind::CloseLimit, &["binance", "bybit"]), &fees).is_none());
+        assert!(HybridPlan::new(&open(SignalKind::OpenLimit, &["binance"]), &fees).is_none());
+
+        let mut unpriced = open(SignalKind::OpenLimit, &["binance", "bybit"]);
+        unpriced.legs[1].limit_price = None;
+        assert!(HybridPlan::new(&unpriced, &fees).is_none());
+    }
+}
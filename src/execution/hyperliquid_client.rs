@@ -0,0 +1,603 @@
+use super::exchange::{num, Balance, ExchangeClient, ExchangeError, FundingIncome, OrderRequest, OrderState, OrderStatus, TimeInForce, VenuePosition};
+use crate::ingestors::signals::Side;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use k256::ecdsa::SigningKey;
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha3::{Digest, Keccak256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+const MAINNET_URL: &str = "https://api.hyperliquid.xyz";
+const TESTNET_URL: &str = "https://api.hyperliquid-testnet.xyz";
+/// Hyperliquid has no market orders; takers are IOC limits this far through the mid.
+const MARKET_SLIPPAGE: f64 = 0.05;
+/// Perp prices carry at most 6 decimals minus the asset's size decimals.
+const MAX_PERP_DECIMALS: i32 = 6;
+
+// --- ✍️ ACTION SIGNING ---
+//
+// An L1 action is msgpack-encoded (field order matters), followed by the nonce and a vault
+// flag, and keccak-hashed into a connection ID. The agent wallet then signs the EIP-712
+// "phantom agent" {source, connectionId} under the Exchange domain (chain 1337).
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Limit {
+    pub tif: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OrderType {
+    Limit(Limit),
+}
+
+/// Order as it goes on the wire.
+#[derive(Debug, Clone, Serialize)]
+pub struct OrderWire {
+    #[serde(rename = "a")]
+    pub asset: u32,
+    #[serde(rename = "b")]
+    pub is_buy: bool,
+    #[serde(rename = "p")]
+    pub limit_px: String,
+    #[serde(rename = "s")]
+    pub sz: String,
+    #[serde(rename = "r")]
+    pub reduce_only: bool,
+    #[serde(rename = "t")]
+    pub order_type: OrderType,
+    #[serde(rename = "c", skip_serializing_if = "Option::is_none")]
+    pub cloid: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelWire {
+    #[serde(rename = "a")]
+    pub asset: u32,
+    #[serde(rename = "o")]
+    pub oid: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CancelCloidWire {
+    pub asset: u32,
+    pub cloid: String,
+}
+
+/// Modifies address an order by exchange ID or by cloid.
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum OrderRef {
+    Oid(u64),
+    Cloid(String),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModifyWire {
+    pub oid: OrderRef,
+    pub order: OrderWire,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Action {
+    Order { orders: Vec<OrderWire>, grouping: String },
+    Cancel { cancels: Vec<CancelWire> },
+    CancelByCloid { cancels: Vec<CancelCloidWire> },
+    BatchModify { modifies: Vec<ModifyWire> },
+}
+
+fn keccak(bytes: &[u8]) -> [u8; 32] {
+    Keccak256::digest(bytes).into()
+}
+
+impl Action {
+    /// keccak256(msgpack(action) ‖ nonce ‖ vault flag).
+    pub fn connection_id(&self, nonce: u64) -> [u8; 32] {
+        let mut bytes = rmp_serde::to_vec_named(self).expect("actions always encode");
+        bytes.extend(nonce.to_be_bytes());
+        bytes.push(0); // no vault
+        keccak(&bytes)
+    }
+}
+
+/// EIP-712 signature, 27/28-style `v`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HlSignature {
+    pub r: [u8; 32],
+    pub s: [u8; 32],
+    pub v: u8,
+}
+
+impl HlSignature {
+    /// r ‖ s ‖ v as hex, the form the SDKs print.
+    pub fn to_hex(&self) -> String {
+        format!("{}{}{:02x}", hex::encode(self.r), hex::encode(self.s), self.v)
+    }
+
+    fn to_json(&self) -> Value {
+        json!({"r": format!("0x{}", hex::encode(self.r)), "s": format!("0x{}", hex::encode(self.s)), "v": self.v})
+    }
+}
+
+/// `/exchange` body; a struct so the action goes out in the same field order it was hashed in.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedAction<'a> {
+    action: &'a Action,
+    nonce: u64,
+    signature: Value,
+    vault_address: Option<String>,
+}
+
+/// Signs the phantom agent for `connection_id`; `source` is "a" on mainnet, "b" on testnet.
+pub fn sign_l1_action(key: &SigningKey, connection_id: [u8; 32], mainnet: bool) -> Result<HlSignature, ExchangeError> {
+    let word = |bytes: &[u8]| keccak(bytes);
+    let domain_type = word(b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)");
+    let mut chain_id = [0u8; 32];
+    chain_id[24..].copy_from_slice(&1337u64.to_be_bytes());
+    let domain = word(&[domain_type, word(b"Exchange"), word(b"1"), chain_id, [0u8; 32]].concat());
+
+    let agent_type = word(b"Agent(string source,bytes32 connectionId)");
+    let source = if mainnet { "a" } else { "b" };
+    let agent = word(&[agent_type, word(source.as_bytes()), connection_id].concat());
+
+    let digest = word(&[&[0x19, 0x01][..], &domain, &agent].concat());
+    let (signature, recovery) = key.sign_prehash_recoverable(&digest).map_err(|e| ExchangeError::Auth(e.to_string()))?;
+    let (r, s) = signature.split_bytes();
+    Ok(HlSignature { r: r.into(), s: s.into(), v: 27 + recovery.to_byte() })
+}
+
+fn parse_key(raw: &str) -> Result<SigningKey, ExchangeError> {
+    let bytes = hex::decode(raw.trim_start_matches("0x")).map_err(|e| ExchangeError::Auth(e.to_string()))?;
+    SigningKey::from_slice(&bytes).map_err(|e| ExchangeError::Auth(e.to_string()))
+}
+
+/// Ethereum address of a key: last 20 bytes of keccak(uncompressed pubkey).
+fn address_of(key: &SigningKey) -> String {
+    let point = key.verifying_key().to_encoded_point(false);
+    format!("0x{}", hex::encode(&keccak(&point.as_bytes()[1..])[12..]))
+}
+
+/// Our client order IDs are free-form; Hyperliquid wants 16 bytes of hex, so derive one.
+pub fn cloid_for(client_order_id: &str) -> String {
+    format!("0x{}", hex::encode(&keccak(client_order_id.as_bytes())[..16]))
+}
+
+/// Eight decimals at most, no trailing zeros: the form the venue hashes.
+fn float_to_wire(x: f64) -> String {
+    let s = format!("{:.8}", x);
+    let s = s.trim_end_matches('0').trim_end_matches('.');
+    if s == "-0" { "0".to_string() } else { s.to_string() }
+}
+
+/// Five significant figures and at most `6 - szDecimals` decimals; integers always pass.
+/// Buys round down and sells up, so rounding never makes the order more aggressive.
+fn wire_price(px: f64, sz_decimals: u32, side: Side) -> String {
+    let magnitude = px.abs().log10().floor() as i32;
+    let decimals = (4 - magnitude).clamp(0, (MAX_PERP_DECIMALS - sz_decimals as i32).max(0));
+    let scale = 10f64.powi(decimals);
+    let scaled = px * scale;
+    let rounded = if side == Side::Buy { (scaled + 1e-9).floor() } else { (scaled - 1e-9).ceil() };
+    float_to_wire(rounded / scale)
+}
+
+fn wire_size(sz: f64, sz_decimals: u32) -> String {
+    let scale = 10f64.powi(sz_decimals as i32);
+    float_to_wire((sz * scale + 1e-9).floor() / scale)
+}
+
+/// Wire order for `req` at `price` (already resolved for market orders).
+fn order_wire(asset: AssetMeta, req: &OrderRequest, price: f64, tif: &str) -> OrderWire {
+    OrderWire {
+        asset: asset.index,
+        is_buy: req.side == Side::Buy,
+        limit_px: wire_price(price, asset.sz_decimals, req.side),
+        sz: wire_size(req.qty, asset.sz_decimals),
+        reduce_only: req.reduce_only,
+        order_type: OrderType::Limit(Limit { tif: tif.to_string() }),
+        cloid: Some(cloid_for(&req.client_order_id)),
+    }
+}
+
+// --- 🔑 CREDENTIALS ---
+
+pub struct HyperliquidCredentials {
+    /// API ("agent") wallet approved by the account; it can trade but not withdraw.
+    pub agent_key: String,
+    /// Master account the agent trades for; info queries are keyed by it.
+    pub account: Option<String>,
+}
+
+impl HyperliquidCredentials {
+    /// `HL_AGENT_KEY`, plus `HL_ACCOUNT_ADDRESS` unless the key is the account's own.
+    pub fn from_env() -> Option<Self> {
+        Some(Self { agent_key: std::env::var("HL_AGENT_KEY").ok()?, account: std::env::var("HL_ACCOUNT_ADDRESS").ok() })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct AssetMeta {
+    index: u32,
+    sz_decimals: u32,
+}
+
+fn map_status(raw: &str, filled: f64) -> OrderStatus {
+    match raw {
+        "open" | "triggered" if filled > 0.0 => OrderStatus::PartiallyFilled,
+        "open" | "triggered" => OrderStatus::New,
+        "filled" => OrderStatus::Filled,
+        s if s.ends_with("anceled") => OrderStatus::Canceled,
+        s if s.ends_with("ejected") => OrderStatus::Rejected,
+        _ => OrderStatus::Expired,
+    }
+}
+
+/// `{"status":"err","response":"..."}` and per-order `{"error": "..."}` messages.
+fn map_error(msg: &str) -> ExchangeError {
+    let lower = msg.to_lowercase();
+    if lower.contains("does not exist") && lower.contains("wallet") {
+        ExchangeError::Auth(msg.to_string())
+    } else if lower.contains("never placed") || lower.contains("unknown oid") {
+        ExchangeError::NotFound
+    } else if lower.contains("rate limit") || lower.contains("too many") {
+        ExchangeError::RateLimited
+    } else {
+        ExchangeError::Rejected(msg.to_string())
+    }
+}
+
+// --- 🏦 CLIENT ---
+
+/// Perps trading through an agent wallet. `HL_TESTNET` switches network; `HL_API_URL`
+/// overrides the endpoint, e.g. to point at a local mock server.
+pub struct HyperliquidExchange {
+    http: reqwest::Client,
+    url: String,
+    mainnet: bool,
+    key: SigningKey,
+    account: String,
+    last_nonce: AtomicU64,
+    assets: Mutex<HashMap<String, AssetMeta>>,
+}
+
+impl HyperliquidExchange {
+    pub fn new(creds: HyperliquidCredentials) -> Result<Self, ExchangeError> {
+        let mainnet = std::env::var("HL_TESTNET").is_err();
+        let default_url = if mainnet { MAINNET_URL } else { TESTNET_URL };
+        let key = parse_key(&creds.agent_key)?;
+        Ok(Self {
+            http: reqwest::Client::new(),
+            url: std::env::var("HL_API_URL").unwrap_or_else(|_| default_url.to_string()),
+            mainnet,
+            account: creds.account.unwrap_or_else(|| address_of(&key)).to_lowercase(),
+            key,
+            last_nonce: AtomicU64::new(0),
+            assets: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Loads asset indices; call once before trading.
+    pub async fn connect(&self) -> Result<(), ExchangeError> {
+        self.load_meta().await?;
+        println!("🔐 [Hyperliquid] Agent {} trading for {}", address_of(&self.key), self.account);
+        Ok(())
+    }
+
+    /// Asset index is the coin's position in `meta.universe`.
+    async fn load_meta(&self) -> Result<(), ExchangeError> {
+        let meta = self.info(json!({"type": "meta"})).await?;
+        let assets = meta["universe"].as_array().into_iter().flatten().enumerate()
+            .filter_map(|(index, asset)| Some((asset["name"].as_str()?.to_string(), AssetMeta {
+                index: index as u32,
+                sz_decimals: asset["szDecimals"].as_u64().unwrap_or(0) as u32,
+            })))
+            .collect();
+        *self.assets.lock().unwrap() = assets;
+        Ok(())
+    }
+
+    fn asset(&self, coin: &str) -> Result<AssetMeta, ExchangeError> {
+        self.assets.lock().unwrap().get(coin).copied().ok_or_else(|| ExchangeError::Rejected(format!("unknown coin {}", coin)))
+    }
+
+    /// Millisecond timestamps, strictly increasing even within one millisecond.
+    fn next_nonce(&self) -> u64 {
+        let now = Utc::now().timestamp_millis() as u64;
+        let prev = self.last_nonce.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |last| Some(now.max(last + 1))).unwrap_or(0);
+        now.max(prev + 1)
+    }
+
+    async fn post<T: Serialize + ?Sized>(&self, path: &str, body: &T) -> Result<Value, ExchangeError> {
+        let resp = self.http.post(format!("{}{}", self.url, path)).json(body)
+            .send().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        let status = resp.status().as_u16();
+        let text = resp.text().await.map_err(|e| ExchangeError::Network(e.to_string()))?;
+        match status {
+            429 => Err(ExchangeError::RateLimited),
+            500..=599 => Err(ExchangeError::Network(format!("{} {}", status, text))),
+            400..=499 => Err(map_error(&text)),
+            _ => serde_json::from_str(&text).map_err(|e| ExchangeError::Other(format!("{}: {}", e, text))),
+        }
+    }
+
+    async fn info(&self, body: Value) -> Result<Value, ExchangeError> {
+        self.post("/info", &body).await
+    }
+
+    /// Signs and submits an action; returns the per-request `statuses`.
+    async fn exchange(&self, action: &Action) -> Result<Vec<Value>, ExchangeError> {
+        let nonce = self.next_nonce();
+        let signature = sign_l1_action(&self.key, action.connection_id(nonce), self.mainnet)?;
+        let body = SignedAction { action, nonce, signature: signature.to_json(), vault_address: None };
+        let resp = self.post("/exchange", &body).await?;
+        if resp["status"].as_str() != Some("ok") {
+            return Err(map_error(resp["response"].as_str().unwrap_or(&resp.to_string())));
+        }
+        Ok(resp["response"]["data"]["statuses"].as_array().cloned().unwrap_or_default())
+    }
+
+    /// Margin summary and positions for the account.
+    pub async fn clearinghouse_state(&self) -> Result<Value, ExchangeError> {
+        self.info(json!({"type": "clearinghouseState", "user": self.account})).await
+    }
+
+    /// Volume-weighted price and fees of this order's fills.
+    async fn fill_totals(&self, oid: u64) -> Result<(f64, f64), ExchangeError> {
+        let fills = self.info(json!({"type": "userFills", "user": self.account})).await?;
+        let (mut qty, mut notional, mut fee) = (0.0, 0.0, 0.0);
+        for fill in fills.as_array().into_iter().flatten().filter(|f| f["oid"].as_u64() == Some(oid)) {
+            let sz = num(&fill["sz"]);
+            qty += sz;
+            notional += sz * num(&fill["px"]);
+            fee += num(&fill["fee"]);
+        }
+        Ok((if qty > 0.0 { notional / qty } else { 0.0 }, fee))
+    }
+
+    /// Order by cloid with its raw `orderStatus` entry, for modifies that need the old fields.
+    async fn query(&self, client_order_id: &str) -> Result<(OrderState, Value), ExchangeError> {
+        let resp = self.info(json!({"type": "orderStatus", "user": self.account, "oid": cloid_for(client_order_id)})).await?;
+        if resp["status"].as_str() != Some("order") { return Err(ExchangeError::NotFound); }
+        let order = &resp["order"]["order"];
+        let orig = num(&order["origSz"]);
+        let filled = orig - num(&order["sz"]);
+        let oid = order["oid"].as_u64().unwrap_or_default();
+        let (avg_fill_price, fee_paid) = if filled > 0.0 { self.fill_totals(oid).await? } else { (0.0, 0.0) };
+        let state = OrderState {
+            venue: "hyperliquid".to_string(),
+            client_order_id: client_order_id.to_string(),
+            exchange_order_id: oid.to_string(),
+            symbol: order["coin"].as_str().unwrap_or_default().to_string(),
+            side: if order["side"].as_str() == Some("B") { Side::Buy } else { Side::Sell },
+            qty: orig,
+            price: Some(num(&order["limitPx"])),
+            filled_qty: filled,
+            avg_fill_price,
+            fee_paid,
+            status: map_status(resp["order"]["status"].as_str().unwrap_or_default(), filled),
+            updated_at: resp["order"]["statusTimestamp"].as_i64().and_then(DateTime::from_timestamp_millis).unwrap_or_else(Utc::now),
+        };
+        Ok((state, order.clone()))
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for HyperliquidExchange {
+    fn venue(&self) -> &str {
+        "hyperliquid"
+    }
+
+    async fn place_order(&self, req: &OrderRequest) -> Result<OrderState, ExchangeError> {
+        let asset = self.asset(&req.symbol)?;
+        let (price, tif) = match req.price {
+            Some(price) => (price, match req.tif { TimeInForce::Gtc => "Gtc", TimeInForce::PostOnly => "Alo", TimeInForce::Ioc => "Ioc" }),
+            None => {
+                let mids = self.info(json!({"type": "allMids"})).await?;
+                let mid = num(&mids[req.symbol.as_str()]);
+                if mid <= 0.0 { return Err(ExchangeError::Rejected(format!("no mid for {}", req.symbol))); }
+                let through = if req.side == Side::Buy { 1.0 + MARKET_SLIPPAGE } else { 1.0 - MARKET_SLIPPAGE };
+                (mid * through, "Ioc")
+            }
+        };
+        let wire = order_wire(asset, req, price, tif);
+        let qty = wire.sz.parse().unwrap_or(req.qty);
+        let limit = wire.limit_px.parse().unwrap_or(price);
+        let statuses = self.exchange(&Action::Order { orders: vec![wire], grouping: "na".to_string() }).await?;
+        let status = statuses.first().ok_or_else(|| ExchangeError::Other("empty order response".to_string()))?;
+        if let Some(error) = status["error"].as_str() { return Err(map_error(error)); }
+
+        let mut state = OrderState {
+            venue: "hyperliquid".to_string(),
+            client_order_id: req.client_order_id.clone(),
+            exchange_order_id: String::new(),
+            symbol: req.symbol.clone(),
+            side: req.side,
+            qty,
+            price: Some(limit),
+            filled_qty: 0.0,
+            avg_fill_price: 0.0,
+            fee_paid: 0.0,
+            status: OrderStatus::New,
+            updated_at: Utc::now(),
+        };
+        if let Some(oid) = status["resting"]["oid"].as_u64() {
+            state.exchange_order_id = oid.to_string();
+        } else if let Some(oid) = status["filled"]["oid"].as_u64() {
+            // Immediate fills: take fees from the fill log when it has caught up
+            state.exchange_order_id = oid.to_string();
+            state.filled_qty = num(&status["filled"]["totalSz"]);
+            state.avg_fill_price = num(&status["filled"]["avgPx"]);
+            state.status = if state.filled_qty + 1e-12 >= qty { OrderStatus::Filled } else { OrderStatus::Canceled };
+            if let Ok((_, fee)) = self.fill_totals(oid).await { state.fee_paid = fee; }
+        }
+        Ok(state)
+    }
+
+    async fn cancel_order(&self, symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let asset = self.asset(symbol)?;
+        let cancels = vec![CancelCloidWire { asset: asset.index, cloid: cloid_for(client_order_id) }];
+        let statuses = self.exchange(&Action::CancelByCloid { cancels }).await?;
+        if let Some(error) = statuses.first().and_then(|s| s["error"].as_str()) { return Err(map_error(error)); }
+        let (mut state, _) = self.query(client_order_id).await?;
+        if !state.status.is_terminal() { state.status = OrderStatus::Canceled; }
+        Ok(state)
+    }
+
+    async fn amend_order(&self, symbol: &str, client_order_id: &str, qty: Option<f64>, price: Option<f64>) -> Result<OrderState, ExchangeError> {
+        let asset = self.asset(symbol)?;
+        let (current, raw) = self.query(client_order_id).await?;
+        let replacement = OrderRequest {
+            client_order_id: client_order_id.to_string(),
+            symbol: symbol.to_string(),
+            side: current.side,
+            qty: qty.unwrap_or(current.qty - current.filled_qty),
+            price: price.or(current.price),
+            tif: TimeInForce::Gtc,
+            reduce_only: raw["reduceOnly"].as_bool().unwrap_or(false),
+        };
+        let tif = raw["tif"].as_str().unwrap_or("Gtc");
+        let order = order_wire(asset, &replacement, replacement.price.unwrap_or_default(), tif);
+        let modifies = vec![ModifyWire { oid: OrderRef::Cloid(cloid_for(client_order_id)), order }];
+        let statuses = self.exchange(&Action::BatchModify { modifies }).await?;
+        if let Some(error) = statuses.first().and_then(|s| s["error"].as_str()) { return Err(map_error(error)); }
+        Ok(self.query(client_order_id).await?.0)
+    }
+
+    async fn order_status(&self, _symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        Ok(self.query(client_order_id).await?.0)
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ExchangeError> {
+        let state = self.clearinghouse_state().await?;
+        Ok(state["assetPositions"].as_array().into_iter().flatten().map(|p| &p["position"]).filter(|p| num(&p["szi"]) != 0.0).map(|p| {
+            let liquidation = num(&p["liquidationPx"]);
+            VenuePosition {
+                venue: "hyperliquid".to_string(),
+                symbol: p["coin"].as_str().unwrap_or_default().to_string(),
+                qty: num(&p["szi"]),
+                entry_price: num(&p["entryPx"]),
+                unrealized_pnl: num(&p["unrealizedPnl"]),
+                liquidation_price: (liquidation > 0.0).then_some(liquidation),
+            }
+        }).collect())
+    }
+
+    async fn balance(&self) -> Result<Balance, ExchangeError> {
+        let state = self.clearinghouse_state().await?;
+        Ok(Balance {
+            equity: num(&state["marginSummary"]["accountValue"]),
+            available: num(&state["withdrawable"]),
+            margin_used: num(&state["marginSummary"]["totalMarginUsed"]),
+        })
+    }
+
+    async fn funding_history(&self, since: DateTime<Utc>) -> Result<Vec<FundingIncome>, ExchangeError> {
+        let body = self.info(json!({"type": "userFunding", "user": self.account, "startTime": since.timestamp_millis()})).await?;
+        Ok(body.as_array().into_iter().flatten().filter(|item| item["delta"]["type"].as_str() == Some("funding")).map(|item| {
+            let delta = &item["delta"];
+            FundingIncome {
+                venue: "hyperliquid".to_string(),
+                symbol: delta["coin"].as_str().unwrap_or_default().to_string(),
+                amount: num(&delta["usdc"]),
+                ts: item["time"].as_i64().and_then(DateTime::from_timestamp_millis).unwrap_or(since),
+                rate: Some(num(&delta["fundingRate"])),
+                qty: Some(num(&delta["szi"])),
+            }
+        }).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Published Hyperliquid SDK vectors (same key, nonce 1583838): action, mainnet and testnet
+    /// signatures as r ‖ s ‖ v hex.
+    const VECTOR_KEY: &str = "e908f86dbb4d55ac876378565aafeabc187f6690f046459397b17d9b9a19688e";
+    const VECTOR_NONCE: u64 = 1583838;
+
+    fn signing_vectors() -> Vec<(Action, &'static str, &'static str)> {
+        let order = |cloid: Option<&str>| Action::Order {
+            orders: vec![OrderWire {
+                asset: 1,
+                is_buy: true,
+                limit_px: "2000.0".to_string(),
+                sz: "3.5".to_string(),
+                reduce_only: false,
+                order_type: OrderType::Limit(Limit { tif: "Ioc".to_string() }),
+                cloid: cloid.map(str::to_string),
+            }],
+            grouping: "na".to_string(),
+        };
+        vec![
+            (
+                order(None),
+                "77957e58e70f43b6b68581f2dc42011fc384538a2e5b7bf42d5b936f19fbb67360721a8598727230f67080efee48c812a6a4442013fd3b0eed509171bef9f23f1c",
+                "cd0925372ff1ed499e54883e9a6205ecfadec748f80ec463fe2f84f1209648776377961965cb7b12414186b1ea291e95fd512722427efcbcfb3b0b2bcd4d79d01c",
+            ),
+            (
+                order(Some("0x1e60610f0b3d420597c88c1fed2ad5ee")),
+                "d3e894092eb27098077145714630a77bbe3836120ee29df7d935d8510b03a08f456de5ec1be82aa65fc6ecda9ef928b0445e212517a98858cfaa251c4cd7552b1c",
+                "3768349dbb22a7fd770fc9fc50c7b5124a7da342ea579b309f58002ceae49b4357badc7909770919c45d850aabb08474ff2b7b3204ae5b66d9f7375582981f111c",
+            ),
+            (
+                Action::Cancel { cancels: vec![CancelWire { asset: 1, oid: 82382 }] },
+                "02f76cc5b16e0810152fa0e14e7b219f49c361e3325f771544c6f54e157bf9fa17ed0afc11a98596be85d5cd9f86600aad515337318f7ab346e5ccc1b03425d51b",
+                "6ffebadfd48067663390962539fbde76cfa36f53be65abe2ab72c9db6d0db44457720db9d7c4860f142a484f070c84eb4b9694c3a617c83f0d698a27e55fd5e01c",
+            ),
+        ]
+    }
+
+    #[test]
+    fn l1_actions_sign_like_the_reference_sdk() {
+        let key = parse_key(VECTOR_KEY).unwrap();
+        for (action, mainnet, testnet) in signing_vectors() {
+            let connection_id = action.connection_id(VECTOR_NONCE);
+            assert_eq!(sign_l1_action(&key, connection_id, true).unwrap().to_hex(), mainnet, "{:?} on mainnet", action);
+            assert_eq!(sign_l1_action(&key, connection_id, false).unwrap().to_hex(), testnet, "{:?} on testnet", action);
+        }
+    }
+
+    #[test]
+    fn prices_keep_five_significant_figures_and_round_passively() {
+        let cases = [
+            (2000.123, 0, Side::Buy, "2000.1"),
+            (2000.123, 0, Side::Sell, "2000.2"),
+            // Integers pass whatever their length
+            (123456.7, 0, Side::Buy, "123456"),
+            (123456.7, 0, Side::Sell, "123457"),
+            (30000.0, 5, Side::Sell, "30000"),
+            (0.0123456, 2, Side::Buy, "0.0123"),
+            (0.0123456, 2, Side::Sell, "0.0124"),
+            // Decimals are capped at 6 - szDecimals before the significant figures run out
+            (1.23456, 5, Side::Buy, "1.2"),
+            (1.23456, 5, Side::Sell, "1.3"),
+        ];
+        for (px, sz_decimals, side, expected) in cases {
+            assert_eq!(wire_price(px, sz_decimals, side), expected, "{} with {} size decimals, {:?}", px, sz_decimals, side);
+        }
+    }
+
+    #[test]
+    fn sizes_round_down_to_the_asset_decimals() {
+        assert_eq!(wire_size(3.4567, 2), "3.45");
+        assert_eq!(wire_size(0.1 + 0.2, 1), "0.3");
+        assert_eq!(wire_size(0.999, 2), "0.99");
+        assert_eq!(wire_size(5.0, 0), "5");
+        assert_eq!(wire_size(0.0004, 3), "0");
+    }
+
+    #[test]
+    fn cloids_are_the_first_16_bytes_of_the_keccak() {
+        assert_eq!(cloid_for(""), "0xc5d2460186f7233c927e7db2dcc703c0");
+        assert_eq!(cloid_for("abc"), "0x4e03657aea45a94fc7d47ba826c8d667");
+        let cid = "0000000000000000000000000-0-q1";
+        assert_eq!(cloid_for(cid), cloid_for(cid));
+        assert_ne!(cloid_for(cid), cloid_for("0000000000000000000000000-0-q2"));
+        assert_eq!(cloid_for(cid).len(), 34);
+    }
+}
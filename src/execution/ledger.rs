@@ -0,0 +1,420 @@
+use super::executor::ExecutedFill;
+use super::paper::{fold_fill, signed, FundingPayment};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::{BufRead, Write};
+use std::path::PathBuf;
+
+const QTY_EPS: f64 = 1e-12;
+
+/// One line of the ledger file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum LedgerEntry {
+    Fill(ExecutedFill),
+    Funding(FundingPayment),
+}
+
+/// One leg, booked twice: at the prices we got and at the prices the signal asked for.
+#[derive(Debug, Clone, Copy, Default)]
+struct LegBook {
+    /// (signed qty, entry) at actual fill prices.
+    actual: (f64, f64),
+    /// Same quantities at the signal's reference prices; the gap to `actual` is slippage.
+    ideal: (f64, f64),
+}
+
+/// PnL split by source. Slippage is positive when we beat the signal's prices.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Attribution {
+    pub basis: f64,
+    pub slippage: f64,
+    pub funding: f64,
+    pub fees: f64,
+}
+
+impl Attribution {
+    pub fn net(&self) -> f64 {
+        self.basis + self.slippage + self.funding - self.fees
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.basis += other.basis;
+        self.slippage += other.slippage;
+        self.funding += other.funding;
+        self.fees += other.fees;
+    }
+}
+
+/// A pair from its first fill until every leg is flat again.
+#[derive(Debug, Clone)]
+struct PositionAccount {
+    pair_id: String,
+    opened_at: DateTime<Utc>,
+    closed_at: Option<DateTime<Utc>>,
+    /// (venue, venue symbol) -> books.
+    legs: HashMap<(String, String), LegBook>,
+    realised: Attribution,
+}
+
+impl PositionAccount {
+    fn is_flat(&self) -> bool {
+        self.legs.values().all(|leg| leg.actual.0.abs() < QTY_EPS)
+    }
+}
+
+/// Realised and unrealised PnL of one position, by source.
+#[derive(Debug, Clone, Serialize)]
+pub struct PositionPnl {
+    pub pair_id: String,
+    pub opened_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub realised: Attribution,
+    /// Basis and slippage still riding on the open legs, at the current marks.
+    pub unrealised: Attribution,
+    pub net: f64,
+}
+
+/// Realised PnL booked on one UTC day for one pair.
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyPnl {
+    pub date: NaiveDate,
+    pub pair_id: String,
+    #[serde(flatten)]
+    pub pnl: Attribution,
+    pub net: f64,
+}
+
+/// One leg of a position the ledger still holds.
+#[derive(Debug, Clone)]
+pub struct OpenLeg {
+    pub pair_id: String,
+    pub venue: String,
+    pub symbol: String,
+    /// Signed; positive is long.
+    pub qty: f64,
+    pub entry: f64,
+}
+
+/// (time, net qty across pairs, fill price) of one instrument after one fill.
+type Holding = (DateTime<Utc>, f64, f64);
+
+/// Every fill and funding payment, attributed to the position that caused it.
+///
+/// Entries are appended to a JSONL file as they arrive and replayed on open, so the
+/// ledger survives restarts and the file doubles as the raw export.
+#[derive(Default)]
+pub struct Ledger {
+    open: BTreeMap<String, PositionAccount>,
+    closed: Vec<PositionAccount>,
+    daily: BTreeMap<(NaiveDate, String), Attribution>,
+    /// (venue, venue symbol) -> net holding after every fill.
+    held: HashMap<(String, String), Vec<Holding>>,
+    /// (venue, venue symbol, unix ms) of every funding payment booked.
+    funding_paid: HashSet<(String, String, i64)>,
+    file: Option<PathBuf>,
+}
+
+impl Ledger {
+    /// Replays `path` if it exists and appends every new entry to it.
+    pub fn open(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let mut ledger = Self::default();
+        if let Ok(file) = std::fs::File::open(&path) {
+            let mut skipped = 0;
+            for line in std::io::BufReader::new(file).lines().map_while(Result::ok) {
+                match serde_json::from_str::<LedgerEntry>(&line) {
+                    Ok(entry) => ledger.apply(&entry),
+                    Err(_) => skipped += 1,
+                }
+            }
+            if skipped > 0 { println!("⚠️ [Ledger] Skipped {} unreadable line(s) in {}", skipped, path.display()); }
+        }
+        ledger.file = Some(path);
+        ledger
+    }
+
+    /// Appends to `path` without replaying it, for simulated venues that start flat every run.
+    pub fn append_only(path: impl Into<PathBuf>) -> Self {
+        Self { file: Some(path.into()), ..Self::default() }
+    }
+
+    /// Reads `LEDGER_FILE`, falling back to `default_path`.
+    pub fn from_env(default_path: &str) -> Self {
+        Self::open(std::env::var("LEDGER_FILE").unwrap_or_else(|_| default_path.to_string()))
+    }
+
+    pub fn record_fill(&mut self, executed: &ExecutedFill) {
+        self.record(LedgerEntry::Fill(executed.clone()));
+    }
+
+    pub fn record_funding(&mut self, payment: &FundingPayment) {
+        self.record(LedgerEntry::Funding(payment.clone()));
+    }
+
+    fn record(&mut self, entry: LedgerEntry) {
+        self.apply(&entry);
+        let Some(path) = &self.file else { return };
+        let written = std::fs::OpenOptions::new().create(true).append(true).open(path)
+            .and_then(|mut file| writeln!(file, "{}", serde_json::to_string(&entry).unwrap_or_default()));
+        if let Err(e) = written { println!("⚠️ [Ledger] Could not append to {}: {}", path.display(), e); }
+    }
+
+    fn apply(&mut self, entry: &LedgerEntry) {
+        match entry {
+            LedgerEntry::Fill(executed) => self.apply_fill(executed),
+            LedgerEntry::Funding(payment) => self.apply_funding(payment),
+        }
+    }
+
+    fn apply_fill(&mut self, executed: &ExecutedFill) {
+        let fill = &executed.fill;
+        let account = self.open.entry(executed.pair_id.clone()).or_insert_with(|| PositionAccount {
+            pair_id: executed.pair_id.clone(),
+            opened_at: fill.ts,
+            closed_at: None,
+            legs: HashMap::new(),
+            realised: Attribution::default(),
+        });
+        let instrument = (fill.venue.clone(), fill.symbol.clone());
+        let qty = signed(fill.side, fill.qty);
+        let history = self.held.entry(instrument.clone()).or_default();
+        let net = history.last().map(|(_, held, _)| *held).unwrap_or(0.0) + qty;
+        history.push((fill.ts, if net.abs() < QTY_EPS { 0.0 } else { net }, fill.price));
+
+        let leg = account.legs.entry(instrument).or_default();
+        let actual = fold_fill(&mut leg.actual, qty, fill.price);
+        let basis = fold_fill(&mut leg.ideal, qty, executed.reference_price.unwrap_or(fill.price));
+        let booked = Attribution { basis, slippage: actual - basis, funding: 0.0, fees: fill.fee };
+        account.realised.add(&booked);
+        self.daily.entry((fill.ts.date_naive(), executed.pair_id.clone())).or_default().add(&booked);
+
+        if !account.is_flat() { return; }
+        if let Some(mut done) = self.open.remove(&executed.pair_id) {
+            done.closed_at = Some(fill.ts);
+            self.closed.push(done);
+        }
+    }
+
+    /// Splits a venue payout across the open positions holding that instrument, by quantity.
+    fn apply_funding(&mut self, payment: &FundingPayment) {
+        self.funding_paid.insert((payment.venue.clone(), payment.symbol.clone(), payment.ts.timestamp_millis()));
+        if payment.qty.abs() < QTY_EPS { return; }
+        let key = (payment.venue.clone(), payment.symbol.clone());
+        for account in self.open.values_mut() {
+            let Some(leg) = account.legs.get(&key) else { continue };
+            let share = payment.amount * leg.actual.0 / payment.qty;
+            account.realised.funding += share;
+            self.daily.entry((payment.ts.date_naive(), account.pair_id.clone())).or_default().funding += share;
+        }
+    }
+
+    fn position_pnl(account: &PositionAccount, mark: &impl Fn(&str, &str) -> Option<f64>) -> PositionPnl {
+        let mut unrealised = Attribution::default();
+        for ((venue, symbol), leg) in &account.legs {
+            let (qty, entry) = leg.actual;
+            let price = mark(venue, symbol).unwrap_or(entry);
+            let actual = (price - entry) * qty;
+            let basis = (price - leg.ideal.1) * leg.ideal.0;
+            unrealised.basis += basis;
+            unrealised.slippage += actual - basis;
+        }
+        PositionPnl {
+            pair_id: account.pair_id.clone(),
+            opened_at: account.opened_at,
+            closed_at: account.closed_at,
+            realised: account.realised,
+            unrealised,
+            net: account.realised.net() + unrealised.net(),
+        }
+    }
+
+    /// Open positions marked with `mark` (venue, venue symbol -> price), then closed ones.
+    pub fn positions(&self, mark: impl Fn(&str, &str) -> Option<f64>) -> Vec<PositionPnl> {
+        self.open.values().chain(&self.closed).map(|account| Self::position_pnl(account, &mark)).collect()
+    }
+
+    /// Every leg of every open position, pairs in ID order and legs by venue.
+    pub fn open_legs(&self) -> Vec<OpenLeg> {
+        let mut legs = Vec::new();
+        for account in self.open.values() {
+            let mut held: Vec<_> = account.legs.iter().filter(|(_, leg)| leg.actual.0.abs() >= QTY_EPS).collect();
+            held.sort_by(|a, b| a.0.cmp(b.0));
+            legs.extend(held.into_iter().map(|((venue, symbol), leg)| OpenLeg {
+                pair_id: account.pair_id.clone(),
+                venue: venue.clone(),
+                symbol: symbol.clone(),
+                qty: leg.actual.0,
+                entry: leg.actual.1,
+            }));
+        }
+        legs
+    }
+
+    /// Net PnL realised on `date` across every pair, funding and fees included.
+    pub fn realised_on(&self, date: NaiveDate) -> f64 {
+        self.daily.range((date, String::new())..).take_while(|((day, _), _)| *day == date).map(|(_, pnl)| pnl.net()).sum()
+    }
+
+    /// Net quantity held on an instrument at `ts`, with the last fill price before it.
+    pub fn held_at(&self, venue: &str, symbol: &str, ts: DateTime<Utc>) -> Option<(f64, f64)> {
+        let history = self.held.get(&(venue.to_string(), symbol.to_string()))?;
+        let before = history.partition_point(|(at, _, _)| *at <= ts);
+        history[..before].last().map(|(_, qty, price)| (*qty, *price))
+    }
+
+    /// Every (venue, venue symbol) the ledger has ever traded.
+    pub fn instruments(&self) -> impl Iterator<Item = &(String, String)> {
+        self.held.keys()
+    }
+
+    pub fn has_funding(&self, venue: &str, symbol: &str, ts: DateTime<Utc>) -> bool {
+        self.funding_paid.contains(&(venue.to_string(), symbol.to_string(), ts.timestamp_millis()))
+    }
+
+    pub fn daily(&self) -> Vec<DailyPnl> {
+        self.daily.iter()
+            .map(|((date, pair_id), pnl)| DailyPnl { date: *date, pair_id: pair_id.clone(), pnl: *pnl, net: pnl.net() })
+            .collect()
+    }
+}
+
+// --- 🧾 REPORT ---
+
+/// `scanner-rust ledger <file> [daily.csv]`: prints the daily attribution and exports it as CSV.
+pub fn run_report(path: &str, out: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
+    if !std::path::Path::new(path).exists() { return Err(format!("{} not found", path).into()); }
+    let mut ledger = Ledger::open(path);
+    ledger.file = None;
+
+    let daily = ledger.daily();
+    let mut date = None;
+    let mut total = Attribution::default();
+    for row in &daily {
+        if date != Some(row.date) {
+            println!("📅 {}", row.date);
+            date = Some(row.date);
+        }
+        total.add(&row.pnl);
+        println!("   {:<24} net {:>9.2} | basis {:>9.2} | slip {:>8.2} | fund {:>8.2} | fees {:>8.2}",
+            row.pair_id, row.net, row.pnl.basis, row.pnl.slippage, row.pnl.funding, row.pnl.fees);
+    }
+    println!("📒 Total net {:+.2} = basis {:+.2} + slippage {:+.2} + funding {:+.2} - fees {:.2}",
+        total.net(), total.basis, total.slippage, total.funding, total.fees);
+
+    // No live books here; open legs are marked at their entry
+    for position in ledger.positions(|_, _| None).iter().filter(|p| p.closed_at.is_none()) {
+        println!("   still open: {} since {}", position.pair_id, position.opened_at.format("%Y-%m-%d %H:%M:%S"));
+    }
+
+    if let Some(out) = out {
+        let mut csv = String::from("date,pair_id,basis,slippage,funding,fees,net\n");
+        for row in &daily {
+            csv.push_str(&format!("{},{},{:.6},{:.6},{:.6},{:.6},{:.6}\n",
+                row.date, row.pair_id, row.pnl.basis, row.pnl.slippage, row.pnl.funding, row.pnl.fees, row.net));
+        }
+        std::fs::write(out, csv)?;
+        println!("💾 Daily attribution written to {}", out);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::execution::exchange::Fill;
+    use crate::ingestors::signals::Side;
+    use chrono::TimeZone;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 12, 0, 0).unwrap() + chrono::Duration::seconds(secs)
+    }
+
+    fn fill(pair_id: &str, venue: &str, side: Side, qty: f64, (price, reference): (f64, Option<f64>), ts: DateTime<Utc>) -> ExecutedFill {
+        ExecutedFill {
+            signal_id: uuid::Uuid::nil(),
+            pair_id: pair_id.to_string(),
+            fill: Fill { venue: venue.to_string(), client_order_id: "cid".to_string(), symbol: "BTCUSDT".to_string(), side, qty, price, fee: 0.1, ts },
+            reference_price: reference,
+        }
+    }
+
+    fn funding(venue: &str, qty: f64, amount: f64, ts: DateTime<Utc>) -> FundingPayment {
+        FundingPayment { venue: venue.to_string(), symbol: "BTCUSDT".to_string(), qty, rate: 0.0001, amount, ts }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn worse_fills_than_the_signal_asked_for_book_as_slippage() {
+        let mut ledger = Ledger::default();
+        // Paid 0.2 over the signal on the Binance long; Bybit filled at its price
+        ledger.apply_fill(&fill("P", "binance", Side::Buy, 1.0, (100.2, Some(100.0)), at(0)));
+        ledger.apply_fill(&fill("P", "bybit", Side::Sell, 1.0, (101.0, Some(101.0)), at(0)));
+
+        let marks = |venue: &str, _: &str| Some(if venue == "binance" { 101.0 } else { 100.0 });
+        let open = &ledger.positions(marks)[0];
+        assert!(close(open.unrealised.basis, 2.0) && close(open.unrealised.slippage, -0.2));
+        assert!(open.closed_at.is_none());
+
+        ledger.apply_fill(&fill("P", "binance", Side::Sell, 1.0, (100.5, Some(100.5)), at(60)));
+        ledger.apply_fill(&fill("P", "bybit", Side::Buy, 1.0, (100.6, Some(100.6)), at(60)));
+        let done = &ledger.positions(marks)[0];
+        assert_eq!(done.closed_at, Some(at(60)));
+        let pnl = done.realised;
+        assert!(close(pnl.basis, 0.9) && close(pnl.slippage, -0.2) && close(pnl.fees, 0.4));
+        assert!(close(done.unrealised.net(), 0.0));
+        assert!(close(ledger.realised_on(at(0).date_naive()), 0.3));
+        assert!(ledger.open_legs().is_empty());
+    }
+
+    #[test]
+    fn funding_on_a_shared_instrument_is_split_by_quantity() {
+        let mut ledger = Ledger::default();
+        ledger.apply_fill(&fill("P1", "binance", Side::Buy, 1.0, (100.0, Some(100.0)), at(0)));
+        ledger.apply_fill(&fill("P2", "binance", Side::Buy, 3.0, (100.0, Some(100.0)), at(0)));
+        ledger.apply_funding(&funding("binance", 4.0, -4.0, at(3600)));
+
+        let by_pair: HashMap<String, f64> = ledger.positions(|_, _| None).into_iter().map(|p| (p.pair_id, p.realised.funding)).collect();
+        assert!(close(by_pair["P1"], -1.0) && close(by_pair["P2"], -3.0));
+        assert!(ledger.has_funding("binance", "BTCUSDT", at(3600)));
+        assert!(!ledger.has_funding("bybit", "BTCUSDT", at(3600)));
+    }
+
+    #[test]
+    fn holdings_are_kept_per_instrument_across_pairs() {
+        let mut ledger = Ledger::default();
+        ledger.apply_fill(&fill("P1", "binance", Side::Buy, 1.0, (100.0, None), at(10)));
+        ledger.apply_fill(&fill("P2", "binance", Side::Buy, 3.0, (101.0, None), at(20)));
+        ledger.apply_fill(&fill("P1", "binance", Side::Sell, 1.0, (102.0, None), at(30)));
+
+        let held = |secs| ledger.held_at("binance", "BTCUSDT", at(secs));
+        assert_eq!(held(5), None);
+        assert_eq!(held(10), Some((1.0, 100.0)));
+        assert_eq!(held(25), Some((4.0, 101.0)));
+        assert_eq!(held(99), Some((3.0, 102.0)));
+        assert_eq!(ledger.held_at("bybit", "BTCUSDT", at(99)), None);
+    }
+
+    #[test]
+    fn ledgers_replay_from_their_file() {
+        let path = std::env::temp_dir().join(format!("ledger-{}.jsonl", uuid::Uuid::new_v4().simple()));
+        let mut ledger = Ledger::open(&path);
+        ledger.record_fill(&fill("P", "binance", Side::Buy, 2.0, (100.2, Some(100.0)), at(0)));
+        ledger.record_fill(&fill("P", "bybit", Side::Sell, 2.0, (101.0, Some(101.0)), at(0)));
+        ledger.record_funding(&funding("bybit", -2.0, 0.5, at(3600)));
+        std::fs::OpenOptions::new().append(true).open(&path).and_then(|mut file| writeln!(file, "not json")).unwrap();
+
+        let replayed = Ledger::open(&path);
+        let (before, after) = (&ledger.positions(|_, _| None)[0], &replayed.positions(|_, _| None)[0]);
+        assert_eq!((after.pair_id.as_str(), after.opened_at), ("P", at(0)));
+        assert!(close(after.realised.funding, 0.5) && close(after.realised.fees, before.realised.fees));
+        assert_eq!(replayed.open_legs().len(), 2);
+        assert!(replayed.has_funding("bybit", "BTCUSDT", at(3600)));
+        assert!(close(replayed.realised_on(at(0).date_naive()), ledger.realised_on(at(0).date_naive())));
+        let _ = std::fs::remove_file(path);
+    }
+}
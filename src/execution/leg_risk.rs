@@ -0,0 +1,153 @@
+use super::exchange::Fill;
+use crate::ingestors::signals::{Side, SignalKind, TradeSignal};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+const QTY_EPS: f64 = 1e-9;
+
+/// What to do with a pair that is left holding one leg.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LegAction {
+    /// Take liquidity on the lagging leg until it matches the filled one.
+    Complete,
+    /// Flatten the excess on the filled leg.
+    Unwind,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LegRiskConfig {
+    /// Longest we tolerate one leg being ahead of the other before acting. Keep it above the
+    /// order manager's escalation window, or leg risk steps in before a quote ever escalates.
+    pub max_legged_ms: i64,
+    /// Act immediately once the unhedged notional reaches this, whatever the clock says.
+    pub max_unhedged_usd: f64,
+    pub open_action: LegAction,
+    /// Closes are always worth finishing, so completing is the sensible default.
+    pub close_action: LegAction,
+    /// Repair orders per signal before we give up and hand it to a human.
+    pub max_repairs: u32,
+}
+
+impl Default for LegRiskConfig {
+    fn default() -> Self {
+        Self {
+            max_legged_ms: 20_000,
+            max_unhedged_usd: 2500.0,
+            open_action: LegAction::Unwind,
+            close_action: LegAction::Complete,
+            max_repairs: 3,
+        }
+    }
+}
+
+/// How far one leg of a signal has got.
+#[derive(Debug, Clone)]
+pub struct LegProgress {
+    pub venue: String,
+    pub symbol: String,
+    pub side: Side,
+    /// Net quantity done in the leg's own direction; unwinds count against it.
+    pub filled: f64,
+    /// Last fill (or limit) price, for sizing the exposure in USD.
+    pub price: f64,
+}
+
+/// An order that brings a legged signal back to flat.
+#[derive(Debug, Clone)]
+pub struct RepairPlan {
+    pub action: LegAction,
+    /// Legs whose resting orders must be cancelled first so they cannot fill behind our back.
+    pub cancel_legs: Vec<usize>,
+    /// Leg the repair order goes to.
+    pub leg: usize,
+    pub side: Side,
+    pub qty: f64,
+    pub reduce_only: bool,
+    pub unhedged_usd: f64,
+}
+
+#[derive(Debug, Clone)]
+pub enum LegDecision {
+    Balanced,
+    /// Legged, but still inside the tolerances.
+    Wait,
+    Repair(RepairPlan),
+    /// Out of repair attempts with exposure still open.
+    GiveUp { unhedged_usd: f64 },
+}
+
+/// The legs of one signal, tracked from the first order until they match.
+#[derive(Debug, Clone)]
+pub struct LegGroup {
+    pub signal_id: Uuid,
+    pub pair_id: String,
+    pub kind: SignalKind,
+    pub legs: Vec<LegProgress>,
+    pub legged_since: Option<DateTime<Utc>>,
+    pub repairs: u32,
+}
+
+impl LegGroup {
+    pub fn new(signal: &TradeSignal) -> Self {
+        let legs = signal.legs.iter().map(|leg| LegProgress {
+            venue: leg.venue.clone(),
+            symbol: leg.symbol.clone(),
+            side: leg.side,
+            filled: 0.0,
+            price: leg.limit_price.unwrap_or(0.0),
+        }).collect();
+        Self { signal_id: signal.id, pair_id: signal.pair_id.clone(), kind: signal.kind, legs, legged_since: None, repairs: 0 }
+    }
+
+    pub fn record_fill(&mut self, leg: usize, fill: &Fill) {
+        let Some(progress) = self.legs.get_mut(leg) else { return };
+        progress.filled += if fill.side == progress.side { fill.qty } else { -fill.qty };
+        progress.price = fill.price;
+    }
+
+    /// (leading leg, lagging leg, quantity gap, gap in USD).
+    pub fn imbalance(&self) -> Option<(usize, usize, f64, f64)> {
+        let by_filled = |a: &(usize, &LegProgress), b: &(usize, &LegProgress)| a.1.filled.total_cmp(&b.1.filled);
+        let (lead, leader) = self.legs.iter().enumerate().max_by(by_filled)?;
+        let (lag, lagger) = self.legs.iter().enumerate().min_by(by_filled)?;
+        let gap = leader.filled - lagger.filled;
+        if gap <= QTY_EPS { return None; }
+        let price = if leader.price > 0.0 { leader.price } else { lagger.price };
+        Some((lead, lag, gap, gap * price))
+    }
+
+    /// Decides whether to keep waiting or act. `settled` means no order of this signal is
+    /// still working, so nothing will close the gap on its own.
+    pub fn assess(&mut self, config: &LegRiskConfig, now: DateTime<Utc>, settled: bool) -> LegDecision {
+        let Some((lead, lag, gap, unhedged_usd)) = self.imbalance() else {
+            self.legged_since = None;
+            return LegDecision::Balanced;
+        };
+        let since = *self.legged_since.get_or_insert(now);
+        let overdue = (now - since).num_milliseconds() >= config.max_legged_ms;
+        // A settled group acts at once the first time; after a repair it gets the full tolerance
+        let stuck = settled && self.repairs == 0;
+        if !(stuck || overdue || unhedged_usd >= config.max_unhedged_usd) { return LegDecision::Wait; }
+        if self.repairs >= config.max_repairs { return LegDecision::GiveUp { unhedged_usd }; }
+
+        // Restart the clock so a repair gets the same tolerance before the next attempt
+        self.repairs += 1;
+        self.legged_since = Some(now);
+        let action = if self.kind == SignalKind::OpenLimit { config.open_action } else { config.close_action };
+        let plan = match action {
+            LegAction::Complete => RepairPlan {
+                action, cancel_legs: vec![lag], leg: lag, side: self.legs[lag].side, qty: gap,
+                reduce_only: self.kind == SignalKind::CloseLimit, unhedged_usd,
+            },
+            // A partially filled leading order would keep adding to what we are unwinding
+            LegAction::Unwind => RepairPlan {
+                action, cancel_legs: vec![lag, lead], leg: lead, side: self.legs[lead].side.opposite(), qty: gap,
+                reduce_only: self.kind == SignalKind::OpenLimit, unhedged_usd,
+            },
+        };
+        LegDecision::Repair(plan)
+    }
+}
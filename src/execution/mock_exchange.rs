@@ -0,0 +1,178 @@
+use super::exchange::{check_client_order_id, Balance, ExchangeClient, ExchangeError, FundingIncome, OrderRequest, OrderState, OrderStatus, VenuePosition};
+use crate::ingestors::signals::Side;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+/// How the mock treats incoming orders.
+#[derive(Debug, Clone, Copy)]
+pub enum MockFillMode {
+    /// Fill everything at the order's limit (or the mark for market orders).
+    Immediate,
+    /// Fill this share of the quantity, leave the rest resting.
+    Partial(f64),
+    /// Accept and rest forever.
+    Never,
+    /// Reject every order.
+    Reject,
+}
+
+#[derive(Default)]
+struct MockBook {
+    orders: HashMap<String, OrderState>,
+    positions: HashMap<String, (f64, f64)>, // symbol -> (signed qty, entry price)
+    marks: HashMap<String, f64>,
+    cash: f64,
+}
+
+/// In-memory venue for running the executor offline.
+pub struct MockExchange {
+    venue: String,
+    fill_mode: Mutex<MockFillMode>,
+    fee_rate: f64,
+    book: Mutex<MockBook>,
+}
+
+impl MockExchange {
+    pub fn new(venue: &str, fill_mode: MockFillMode) -> Self {
+        Self {
+            venue: venue.to_string(),
+            fill_mode: Mutex::new(fill_mode),
+            fee_rate: 0.0002,
+            book: Mutex::new(MockBook { cash: 10_000.0, ..MockBook::default() }),
+        }
+    }
+
+    pub fn set_fill_mode(&self, mode: MockFillMode) {
+        *self.fill_mode.lock().unwrap() = mode;
+    }
+
+    /// Price used for market orders and unrealised PnL.
+    pub fn set_mark(&self, symbol: &str, price: f64) {
+        self.book.lock().unwrap().marks.insert(symbol.to_string(), price);
+    }
+
+    /// Fills up to `qty` more of a resting order at its limit, as if the book traded through it.
+    pub fn fill_resting(&self, client_order_id: &str, qty: f64) -> Result<OrderState, ExchangeError> {
+        let mut book = self.book.lock().unwrap();
+        let mut order = book.orders.get(client_order_id).cloned().ok_or(ExchangeError::NotFound)?;
+        if order.status.is_terminal() { return Ok(order); }
+        let price = order.price.or_else(|| book.marks.get(&order.symbol).copied()).unwrap_or(0.0);
+        let remaining = order.qty - order.filled_qty;
+        Self::apply_fill(&mut book, &mut order, qty.min(remaining), price, self.fee_rate);
+        book.orders.insert(order.client_order_id.clone(), order.clone());
+        Ok(order)
+    }
+
+    fn apply_fill(book: &mut MockBook, order: &mut OrderState, qty: f64, price: f64, fee_rate: f64) {
+        if qty <= 0.0 { return; }
+        let notional = qty * price;
+        order.avg_fill_price = (order.avg_fill_price * order.filled_qty + notional) / (order.filled_qty + qty);
+        order.filled_qty += qty;
+        order.fee_paid += notional * fee_rate;
+        order.status = if order.filled_qty + 1e-12 >= order.qty { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+        order.updated_at = Utc::now();
+
+        let signed = if order.side == Side::Buy { qty } else { -qty };
+        let (pos_qty, entry) = book.positions.get(&order.symbol).copied().unwrap_or((0.0, 0.0));
+        let new_qty = pos_qty + signed;
+        // Entry only moves when the position grows; reductions realise PnL into cash
+        let new_entry = if pos_qty == 0.0 || pos_qty.signum() == signed.signum() {
+            (entry * pos_qty.abs() + price * qty) / new_qty.abs().max(1e-12)
+        } else {
+            let closed = qty.min(pos_qty.abs());
+            book.cash += closed * (price - entry) * pos_qty.signum();
+            if new_qty.abs() < 1e-12 { 0.0 } else if new_qty.signum() == pos_qty.signum() { entry } else { price }
+        };
+        book.cash -= notional * fee_rate;
+        book.positions.insert(order.symbol.clone(), (new_qty, new_entry));
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for MockExchange {
+    fn venue(&self) -> &str {
+        &self.venue
+    }
+
+    async fn place_order(&self, req: &OrderRequest) -> Result<OrderState, ExchangeError> {
+        check_client_order_id(&req.client_order_id)?;
+        let mode = *self.fill_mode.lock().unwrap();
+        if let MockFillMode::Reject = mode { return Err(ExchangeError::Rejected("mock rejects everything".to_string())); }
+
+        let mut book = self.book.lock().unwrap();
+        if let Some(existing) = book.orders.get(&req.client_order_id) { return Ok(existing.clone()); }
+        let fill_price = req.price.or_else(|| book.marks.get(&req.symbol).copied())
+            .ok_or_else(|| ExchangeError::Rejected(format!("no mark for {}", req.symbol)))?;
+
+        let mut order = OrderState {
+            venue: self.venue.clone(),
+            client_order_id: req.client_order_id.clone(),
+            exchange_order_id: format!("mock-{}", book.orders.len() + 1),
+            symbol: req.symbol.clone(),
+            side: req.side,
+            qty: req.qty,
+            price: req.price,
+            filled_qty: 0.0,
+            avg_fill_price: 0.0,
+            fee_paid: 0.0,
+            status: OrderStatus::New,
+            updated_at: Utc::now(),
+        };
+        let fill_qty = match mode {
+            MockFillMode::Immediate => req.qty,
+            MockFillMode::Partial(share) => req.qty * share.clamp(0.0, 1.0),
+            _ => 0.0,
+        };
+        Self::apply_fill(&mut book, &mut order, fill_qty, fill_price, self.fee_rate);
+        book.orders.insert(order.client_order_id.clone(), order.clone());
+        Ok(order)
+    }
+
+    async fn cancel_order(&self, _symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let mut book = self.book.lock().unwrap();
+        let order = book.orders.get_mut(client_order_id).ok_or(ExchangeError::NotFound)?;
+        if !order.status.is_terminal() {
+            order.status = OrderStatus::Canceled;
+            order.updated_at = Utc::now();
+        }
+        Ok(order.clone())
+    }
+
+    async fn order_status(&self, _symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        self.book.lock().unwrap().orders.get(client_order_id).cloned().ok_or(ExchangeError::NotFound)
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ExchangeError> {
+        let book = self.book.lock().unwrap();
+        Ok(book.positions.iter().filter(|(_, (qty, _))| *qty != 0.0).map(|(symbol, (qty, entry))| {
+            let mark = book.marks.get(symbol).copied().unwrap_or(*entry);
+            VenuePosition {
+                venue: self.venue.clone(),
+                symbol: symbol.clone(),
+                qty: *qty,
+                entry_price: *entry,
+                unrealized_pnl: (mark - entry) * qty,
+                liquidation_price: None,
+            }
+        }).collect())
+    }
+
+    async fn balance(&self) -> Result<Balance, ExchangeError> {
+        let book = self.book.lock().unwrap();
+        let (mut upnl, mut margin) = (0.0, 0.0);
+        for (symbol, (qty, entry)) in &book.positions {
+            let mark = book.marks.get(symbol).copied().unwrap_or(*entry);
+            upnl += (mark - entry) * qty;
+            margin += (qty * mark).abs() / 10.0; // 10x
+        }
+        let equity = book.cash + upnl;
+        Ok(Balance { equity, available: equity - margin, margin_used: margin })
+    }
+
+    /// The mock never settles funding.
+    async fn funding_history(&self, _since: DateTime<Utc>) -> Result<Vec<FundingIncome>, ExchangeError> {
+        Ok(Vec::new())
+    }
+}
@@ -0,0 +1,187 @@
+//! Loopback HTTP and WebSocket servers that stand in for a venue in the client tests.
+
+use futures::{SinkExt, StreamExt};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::{timeout, Duration};
+use tokio_tungstenite::tungstenite::handshake::server::{Callback, ErrorResponse, Request as Handshake, Response};
+use tokio_tungstenite::tungstenite::protocol::Message;
+
+/// How long a test waits on the client before giving up.
+pub const WAIT: Duration = Duration::from_secs(5);
+
+/// One request as the server received it.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    /// Lower-cased names.
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// First value of `key` in the query string.
+    pub fn param(&self, key: &str) -> Option<&str> {
+        self.query.split('&').filter_map(|pair| pair.split_once('=')).find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+}
+
+type Handler = Arc<dyn Fn(&Request) -> (u16, Value) + Send + Sync>;
+
+/// Answers every request with whatever `handler` returns for it and keeps a log of them.
+pub struct MockHttp {
+    pub url: String,
+    log: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockHttp {
+    pub async fn start(handler: impl Fn(&Request) -> (u16, Value) + Send + Sync + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let handler: Handler = Arc::new(handler);
+        let server_log = Arc::clone(&log);
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                tokio::spawn(serve(stream, Arc::clone(&handler), Arc::clone(&server_log)));
+            }
+        });
+        Self { url, log }
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.log.lock().unwrap().clone()
+    }
+
+    /// Requests for `method` on `path`, oldest first.
+    pub fn hits(&self, method: &str, path: &str) -> Vec<Request> {
+        self.requests().into_iter().filter(|r| r.method == method && r.path == path).collect()
+    }
+}
+
+/// Serves keep-alive HTTP/1.1 on one connection until the client hangs up.
+async fn serve(stream: TcpStream, handler: Handler, log: Arc<Mutex<Vec<Request>>>) {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).await.unwrap_or(0) == 0 { return; }
+        let mut parts = line.split_whitespace();
+        let (Some(method), Some(target)) = (parts.next(), parts.next()) else { return };
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let mut request = Request { method: method.to_string(), path: path.to_string(), query: query.to_string(), headers: HashMap::new(), body: String::new() };
+
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header).await.unwrap_or(0) == 0 { return; }
+            let header = header.trim_end();
+            if header.is_empty() { break; }
+            if let Some((name, value)) = header.split_once(':') {
+                request.headers.insert(name.trim().to_ascii_lowercase(), value.trim().to_string());
+            }
+        }
+        let length = request.header("content-length").and_then(|l| l.parse().ok()).unwrap_or(0);
+        let mut body = vec![0; length];
+        if reader.read_exact(&mut body).await.is_err() { return; }
+        request.body = String::from_utf8_lossy(&body).into_owned();
+
+        let (status, reply) = handler(&request);
+        log.lock().unwrap().push(request);
+        let reply = reply.to_string();
+        let response = format!(
+            "HTTP/1.1 {} MOCK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            status, reply.len(), reply,
+        );
+        if reader.get_mut().write_all(response.as_bytes()).await.is_err() { return; }
+    }
+}
+
+/// One accepted socket. Dropping it closes the connection.
+pub struct WsConnection {
+    pub path: String,
+    outgoing: mpsc::UnboundedSender<String>,
+    incoming: mpsc::UnboundedReceiver<String>,
+}
+
+impl WsConnection {
+    pub fn send(&self, message: Value) {
+        self.outgoing.send(message.to_string()).unwrap();
+    }
+
+    /// Next text frame from the client, parsed.
+    pub async fn recv(&mut self) -> Value {
+        let text = timeout(WAIT, self.incoming.recv()).await.expect("client went quiet").expect("client hung up");
+        serde_json::from_str(&text).unwrap()
+    }
+}
+
+/// Notes the path the client asked for during the handshake.
+struct RecordPath<'a>(&'a mut String);
+
+impl Callback for RecordPath<'_> {
+    fn on_request(self, request: &Handshake, response: Response) -> Result<Response, ErrorResponse> {
+        *self.0 = request.uri().path().to_string();
+        Ok(response)
+    }
+}
+
+/// Accepts WebSocket clients and hands each connection to the test.
+pub struct MockWs {
+    pub url: String,
+    connections: mpsc::UnboundedReceiver<WsConnection>,
+}
+
+impl MockWs {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (tx, connections) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let mut path = String::new();
+                let Ok(ws) = tokio_tungstenite::accept_hdr_async(stream, RecordPath(&mut path)).await else { continue };
+                let (outgoing, mut to_client) = mpsc::unbounded_channel::<String>();
+                let (from_client, incoming) = mpsc::unbounded_channel();
+                if tx.send(WsConnection { path, outgoing, incoming }).is_err() { return; }
+                tokio::spawn(async move {
+                    let (mut write, mut read) = ws.split();
+                    loop {
+                        tokio::select! {
+                            message = to_client.recv() => match message {
+                                Some(text) => { if write.send(Message::Text(text.into())).await.is_err() { break; } }
+                                None => { let _ = write.send(Message::Close(None)).await; break; }
+                            },
+                            frame = read.next() => match frame {
+                                Some(Ok(Message::Text(text))) => { let _ = from_client.send(text.to_string()); }
+                                Some(Ok(_)) => {}
+                                _ => break,
+                            },
+                        }
+                    }
+                });
+            }
+        });
+        Self { url, connections }
+    }
+
+    /// Waits for the client to connect.
+    pub async fn accept(&mut self) -> WsConnection {
+        timeout(WAIT, self.connections.recv()).await.expect("client never connected").unwrap()
+    }
+}
+
+/// Polls `check` until it holds, failing the test after `WAIT`.
+pub async fn eventually(mut check: impl FnMut() -> bool) {
+    timeout(WAIT, async {
+        while !check() { tokio::time::sleep(Duration::from_millis(10)).await; }
+    }).await.expect("condition never held");
+}
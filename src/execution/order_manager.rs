@@ -0,0 +1,310 @@
+use super::exchange::client_order_id;
+use crate::ingestors::market_store::MarketState;
+use crate::ingestors::signals::{Side, SignalKind, TradeSignal};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const ORDER_HISTORY_CHANNEL: &str = "trade:order_history";
+
+const QTY_EPS: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrderManagerConfig {
+    /// Work limit legs as post-only quotes at the touch. Off sends the signal's limit as a plain GTC.
+    pub enabled: bool,
+    /// How far behind the touch to quote; 0 joins the best bid/ask.
+    pub quote_offset_bps: f64,
+    /// Requote once the target price is this far from the resting one.
+    pub requote_threshold_bps: f64,
+    pub min_requote_interval_ms: i64,
+    pub max_requotes: u32,
+    /// Cancel and re-place a quote that has rested this long without completing.
+    pub order_timeout_ms: i64,
+    /// Timeouts before we stop re-placing and either escalate or give up.
+    pub max_replaces: u32,
+    /// After the last timeout, cross the spread if the far touch is still inside the signal's limit.
+    pub escalate_to_taker: bool,
+}
+
+impl OrderManagerConfig {
+    /// How long a quote can be worked, through every replace, before it escalates or is abandoned.
+    pub fn escalation_window_ms(&self) -> i64 {
+        self.order_timeout_ms * (i64::from(self.max_replaces) + 1)
+    }
+}
+
+impl Default for OrderManagerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            quote_offset_bps: 0.0,
+            requote_threshold_bps: 2.0,
+            min_requote_interval_ms: 250,
+            max_requotes: 50,
+            order_timeout_ms: 5000,
+            max_replaces: 2,
+            escalate_to_taker: true,
+        }
+    }
+}
+
+/// Top of book for one instrument.
+#[derive(Debug, Clone, Copy)]
+pub struct Quote {
+    pub bid: f64,
+    pub ask: f64,
+}
+
+impl Quote {
+    pub fn from_book(book: &MarketState) -> Option<Self> {
+        Some(Self { bid: book.bids.first()?.0, ask: book.asks.first()?.0 })
+    }
+
+    pub fn mid(&self) -> f64 {
+        (self.bid + self.ask) / 2.0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LifecycleState {
+    Placed,
+    PartiallyFilled,
+    Requoted,
+    Replaced,
+    /// The venue refused the order (post-only cross, margin, ...).
+    Rejected,
+    /// Canceled by someone other than us.
+    Canceled,
+    Escalated,
+    Filled,
+    Abandoned,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderEvent {
+    pub ts: DateTime<Utc>,
+    pub state: LifecycleState,
+    pub client_order_id: String,
+    pub price: Option<f64>,
+    /// Quantity filled across every order of this leg so far.
+    pub filled: f64,
+    pub note: String,
+}
+
+/// Everything that happened to one signal leg, published once it is done.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderHistory {
+    pub signal_id: Uuid,
+    pub pair_id: String,
+    pub leg: usize,
+    pub venue: String,
+    pub symbol: String,
+    pub side: Side,
+    pub qty: f64,
+    /// Worst acceptable price from the signal.
+    pub limit_price: f64,
+    pub filled: f64,
+    pub final_state: LifecycleState,
+    pub events: Vec<OrderEvent>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderAction {
+    Hold,
+    /// Move the quote to this price (amend in place, or cancel and re-place).
+    Requote(f64),
+    /// Timed out: cancel and place a fresh quote here.
+    Replace(f64),
+    /// Cross the spread with an IOC at this price.
+    Escalate(f64),
+    Abandon(&'static str),
+}
+
+/// One signal leg being worked as a sequence of orders.
+#[derive(Debug, Clone)]
+pub struct ManagedOrder {
+    pub history: OrderHistory,
+    pub reduce_only: bool,
+    /// The order currently representing this leg.
+    pub client_order_id: String,
+    pub price: f64,
+    /// Whether that order is still live on the venue.
+    pub working: bool,
+    pub escalated: bool,
+    pub placed_at: DateTime<Utc>,
+    pub quoted_at: DateTime<Utc>,
+    pub requotes: u32,
+    pub replaces: u32,
+    /// Orders sent so far; numbers the client order IDs of replacements.
+    pub attempts: u32,
+}
+
+/// Where to rest: the touch (less the offset), never past the signal's limit.
+pub fn target_price(side: Side, quote: Option<Quote>, limit: f64, config: &OrderManagerConfig) -> f64 {
+    let offset = config.quote_offset_bps / 10_000.0;
+    match (side, quote) {
+        (Side::Buy, Some(q)) => (q.bid * (1.0 - offset)).min(limit),
+        (Side::Sell, Some(q)) => (q.ask * (1.0 + offset)).max(limit),
+        (_, None) => limit,
+    }
+}
+
+impl ManagedOrder {
+    /// Starts managing leg `leg` of `signal`, which must carry a limit price.
+    pub fn new(signal: &TradeSignal, leg: usize, now: DateTime<Utc>) -> Self {
+        let spec = &signal.legs[leg];
+        let limit_price = spec.limit_price.unwrap_or_default();
+        Self {
+            history: OrderHistory {
+                signal_id: signal.id,
+                pair_id: signal.pair_id.clone(),
+                leg,
+                venue: spec.venue.clone(),
+                symbol: spec.symbol.clone(),
+                side: spec.side,
+                qty: spec.size,
+                limit_price,
+                filled: 0.0,
+                final_state: LifecycleState::Placed,
+                events: Vec::new(),
+            },
+            reduce_only: signal.kind == SignalKind::CloseLimit,
+            client_order_id: String::new(),
+            price: limit_price,
+            working: false,
+            escalated: false,
+            placed_at: now,
+            quoted_at: now,
+            requotes: 0,
+            replaces: 0,
+            attempts: 0,
+        }
+    }
+
+    pub fn remaining(&self) -> f64 {
+        (self.history.qty - self.history.filled).max(0.0)
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.remaining() <= QTY_EPS
+    }
+
+    /// Client order ID for the next order of this leg; the first keeps the plain `{signal}-{leg}`.
+    pub fn next_client_order_id(&mut self, suffix: char) -> String {
+        let retry = (self.attempts > 0).then_some((suffix, self.attempts));
+        let cid = client_order_id(self.history.signal_id, self.history.leg, retry);
+        self.attempts += 1;
+        cid
+    }
+
+    pub fn record(&mut self, now: DateTime<Utc>, state: LifecycleState, note: impl Into<String>) {
+        self.history.final_state = state;
+        self.history.events.push(OrderEvent {
+            ts: now,
+            state,
+            client_order_id: self.client_order_id.clone(),
+            price: Some(self.price),
+            filled: self.history.filled,
+            note: note.into(),
+        });
+    }
+
+    /// Far touch still inside our limit, i.e. taking now is no worse than the signal allowed.
+    fn edge_holds(&self, quote: Option<Quote>) -> bool {
+        match (self.history.side, quote) {
+            (Side::Buy, Some(q)) => q.ask <= self.history.limit_price,
+            (Side::Sell, Some(q)) => q.bid >= self.history.limit_price,
+            (_, None) => false,
+        }
+    }
+
+    fn last_resort(&self, config: &OrderManagerConfig, quote: Option<Quote>) -> OrderAction {
+        if config.escalate_to_taker && self.edge_holds(quote) {
+            OrderAction::Escalate(self.history.limit_price)
+        } else {
+            OrderAction::Abandon("edge gone; leaving the rest to leg risk")
+        }
+    }
+
+    /// Decides what to do with the leg given the latest book.
+    pub fn review(&self, config: &OrderManagerConfig, quote: Option<Quote>, now: DateTime<Utc>) -> OrderAction {
+        if self.is_complete() { return OrderAction::Hold; }
+        if self.escalated {
+            return if self.working { OrderAction::Hold } else { OrderAction::Abandon("taker order did not complete") };
+        }
+
+        let target = target_price(self.history.side, quote, self.history.limit_price, config);
+        if (now - self.placed_at).num_milliseconds() >= config.order_timeout_ms {
+            if self.replaces < config.max_replaces { return OrderAction::Replace(target); }
+            return self.last_resort(config, quote);
+        }
+        if self.requotes >= config.max_requotes {
+            return if self.working { OrderAction::Hold } else { self.last_resort(config, quote) };
+        }
+        if (now - self.quoted_at).num_milliseconds() < config.min_requote_interval_ms { return OrderAction::Hold; }
+        // A quote the venue refused or cancelled goes straight back out at the new target
+        if !self.working { return OrderAction::Requote(target); }
+
+        let moved_bps = (target - self.price).abs() / self.price.max(f64::EPSILON) * 10_000.0;
+        if moved_bps >= config.requote_threshold_bps { OrderAction::Requote(target) } else { OrderAction::Hold }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestors::signals::SignalLeg;
+    use chrono::Duration;
+
+    /// A buy limited at 100.5, resting at 100.
+    fn resting(now: DateTime<Utc>) -> ManagedOrder {
+        let leg = SignalLeg { venue: "binance".to_string(), symbol: "BTCUSDT".to_string(), side: Side::Buy, size: 1.0, limit_price: Some(100.5) };
+        let mut order = ManagedOrder::new(&TradeSignal::new(SignalKind::OpenLimit, "BINANCE-BYBIT-BTC", now, 5_000, vec![leg]), 0, now);
+        order.price = 100.0;
+        order.working = true;
+        order
+    }
+
+    fn quote(bid: f64, ask: f64) -> Option<Quote> {
+        Some(Quote { bid, ask })
+    }
+
+    #[test]
+    fn quotes_follow_the_touch_past_the_threshold() {
+        let config = OrderManagerConfig::default();
+        let now = Utc::now();
+        let order = resting(now);
+        let later = now + Duration::milliseconds(1_000);
+
+        assert_eq!(order.review(&config, quote(100.1, 100.2), later), OrderAction::Requote(100.1));
+        // 1bp is under the threshold, and a fresh quote is left alone however far the touch moves
+        assert_eq!(order.review(&config, quote(100.01, 100.2), later), OrderAction::Hold);
+        assert_eq!(order.review(&config, quote(100.1, 100.2), now + Duration::milliseconds(100)), OrderAction::Hold);
+        // Never past the signal's limit
+        assert_eq!(order.review(&config, quote(101.0, 101.1), later), OrderAction::Requote(100.5));
+    }
+
+    #[test]
+    fn timeouts_replace_then_escalate_or_abandon() {
+        let config = OrderManagerConfig::default();
+        let now = Utc::now();
+        let timed_out = now + Duration::milliseconds(config.order_timeout_ms);
+        let mut order = resting(now);
+
+        assert_eq!(order.review(&config, quote(100.1, 100.2), timed_out), OrderAction::Replace(100.1));
+
+        order.replaces = config.max_replaces;
+        assert_eq!(order.review(&config, quote(100.3, 100.4), timed_out), OrderAction::Escalate(100.5));
+        assert!(matches!(order.review(&config, quote(100.5, 100.6), timed_out), OrderAction::Abandon(_)));
+        let passive = OrderManagerConfig { escalate_to_taker: false, ..config };
+        assert!(matches!(order.review(&passive, quote(100.3, 100.4), timed_out), OrderAction::Abandon(_)));
+
+        // A taker order that came back without completing is the end of the line
+        order.escalated = true;
+        order.working = false;
+        assert_eq!(order.review(&config, quote(100.3, 100.4), now), OrderAction::Abandon("taker order did not complete"));
+    }
+}
@@ -0,0 +1,648 @@
+use super::exchange::{Balance, ExchangeClient, ExchangeError, FundingIncome, OrderRequest, OrderState, OrderStatus, TimeInForce, VenuePosition};
+use super::execution_config::ExecutionConfig;
+use super::account_monitor::{self, AccountMonitor};
+use super::executor::{Clients, Executor, POLL_MS};
+use super::funding_recon::{self, FundingRecon};
+use super::ledger::{Ledger, PositionPnl};
+use crate::ingestors::control::CONTROL_CHANNEL;
+use crate::ingestors::fees::{FeeModel, FeeSchedule, OrderType};
+use crate::ingestors::market_store::{spawn_redis_feed, MarketState, MarketStore};
+use crate::ingestors::pnl_model::seconds_to_payout;
+use crate::ingestors::signals::{coin_symbol, Side, SIGNAL_CHANNEL};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::StreamExt;
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+
+pub const PAPER_CHANNEL: &str = "trade:paper";
+
+const MAX_SYMBOLS: usize = 512;
+const REPORT_MS: u64 = 5000;
+const PAPER_LEDGER_FILE: &str = "paper_ledger.jsonl";
+const QTY_EPS: f64 = 1e-12;
+
+/// Knobs for the fill simulator.
+#[derive(Debug, Clone, Copy)]
+pub struct PaperConfig {
+    /// One-way order latency: an order meets the book that is live this long after it was sent.
+    pub latency_ms: u64,
+    /// Share of the displayed size at our price assumed to be ahead of us when we join a level.
+    pub queue_ahead_share: f64,
+    /// Virtual USDT each venue starts with.
+    pub starting_balance: f64,
+    pub leverage: f64,
+}
+
+impl Default for PaperConfig {
+    fn default() -> Self {
+        Self { latency_ms: 50, queue_ahead_share: 1.0, starting_balance: 10_000.0, leverage: 10.0 }
+    }
+}
+
+// --- 🧮 POSITION MATH ---
+
+/// Folds a signed fill into `(qty, entry)` and returns the PnL it realised.
+/// Entry only moves when the position grows; a flip re-enters at the fill price.
+pub(crate) fn fold_fill(position: &mut (f64, f64), signed_qty: f64, price: f64) -> f64 {
+    let (qty, entry) = *position;
+    let new_qty = qty + signed_qty;
+    if qty == 0.0 || qty.signum() == signed_qty.signum() {
+        *position = (new_qty, (entry * qty.abs() + price * signed_qty.abs()) / new_qty.abs().max(QTY_EPS));
+        return 0.0;
+    }
+    let closed = signed_qty.abs().min(qty.abs());
+    let new_entry = if new_qty.abs() < QTY_EPS { 0.0 } else if new_qty.signum() == qty.signum() { entry } else { price };
+    *position = (if new_qty.abs() < QTY_EPS { 0.0 } else { new_qty }, new_entry);
+    closed * (price - entry) * qty.signum()
+}
+
+pub(crate) fn signed(side: Side, qty: f64) -> f64 {
+    if side == Side::Buy { qty } else { -qty }
+}
+
+fn mid(book: &MarketState) -> Option<f64> {
+    match (book.bids.first(), book.asks.first()) {
+        (Some((bid, _)), Some((ask, _))) => Some((bid + ask) / 2.0),
+        _ => None,
+    }
+}
+
+/// Opposite-side levels an order on `side` can trade against, best first, stopping at `limit`.
+fn crossing(book: &MarketState, side: Side, limit: Option<f64>) -> impl Iterator<Item = &(f64, f64)> {
+    let levels = if side == Side::Buy { &book.asks } else { &book.bids };
+    levels.iter().take_while(move |(price, _)| match limit {
+        None => true,
+        Some(limit) if side == Side::Buy => *price <= limit,
+        Some(limit) => *price >= limit,
+    })
+}
+
+/// Displayed size on our own side at exactly `price`.
+fn own_level_qty(book: &MarketState, side: Side, price: f64) -> f64 {
+    let levels = if side == Side::Buy { &book.bids } else { &book.asks };
+    levels.iter().filter(|(p, _)| (p - price).abs() <= price * 1e-9).map(|(_, q)| q).sum()
+}
+
+// --- 🏦 VIRTUAL VENUE ---
+
+/// One funding payout on one venue position. Positive = received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FundingPayment {
+    pub venue: String,
+    pub symbol: String,
+    /// Signed venue position the payout was computed on.
+    pub qty: f64,
+    pub rate: f64,
+    pub amount: f64,
+    pub ts: DateTime<Utc>,
+}
+
+/// Virtual balance sheet for one venue.
+#[derive(Debug, Clone, Serialize)]
+pub struct VenueSummary {
+    pub venue: String,
+    pub cash: f64,
+    pub equity: f64,
+    pub realised: f64,
+    pub unrealised: f64,
+    pub funding: f64,
+    pub fees: f64,
+}
+
+struct PaperOrder {
+    state: OrderState,
+    tif: TimeInForce,
+    /// Displayed quantity still ahead of us at our price.
+    queue_ahead: f64,
+}
+
+struct PaperState {
+    orders: HashMap<String, PaperOrder>,
+    /// Venue symbol -> (signed qty, entry price).
+    positions: HashMap<String, (f64, f64)>,
+    cash: f64,
+    realised: f64,
+    fees: f64,
+    funding: f64,
+    last_accrual: DateTime<Utc>,
+    /// Every payout so far, served back as the venue's funding history.
+    payouts: Vec<FundingPayment>,
+}
+
+/// Simulated venue that fills against the live books in `MarketStore`.
+///
+/// Marketable orders sweep the book after `latency_ms`; the rest join the back of their price
+/// level and only fill once the displayed size ahead of them is gone and the other side trades
+/// into their price.
+pub struct PaperExchange {
+    venue: String,
+    store: Arc<MarketStore>,
+    fees: FeeSchedule,
+    config: PaperConfig,
+    state: Mutex<PaperState>,
+    /// Replay time when driven by a backtest; wall clock otherwise.
+    clock: Mutex<Option<DateTime<Utc>>>,
+}
+
+impl PaperExchange {
+    pub fn new(venue: &str, store: Arc<MarketStore>, fees: FeeSchedule, config: PaperConfig) -> Self {
+        let state = PaperState {
+            orders: HashMap::new(),
+            positions: HashMap::new(),
+            cash: config.starting_balance,
+            realised: 0.0,
+            fees: 0.0,
+            funding: 0.0,
+            last_accrual: Utc::now(),
+            payouts: Vec::new(),
+        };
+        Self { venue: venue.to_string(), store, fees, config, state: Mutex::new(state), clock: Mutex::new(None) }
+    }
+
+    /// Switches to replay time. From then on orders meet the book immediately; the caller
+    /// models latency by delaying submission instead.
+    pub fn set_time(&self, now: DateTime<Utc>) {
+        let mut clock = self.clock.lock().unwrap();
+        if clock.is_none() { self.state.lock().unwrap().last_accrual = now; }
+        *clock = Some(now);
+    }
+
+    fn now(&self) -> DateTime<Utc> {
+        self.clock.lock().unwrap().unwrap_or_else(Utc::now)
+    }
+
+    /// One paper venue per entry in `venues`, indexed like the store's exchange IDs.
+    pub fn for_venues(venues: &[&str], store: &Arc<MarketStore>, fees: &FeeModel, config: PaperConfig) -> Vec<Arc<Self>> {
+        venues.iter().map(|venue| {
+            let schedule = fees.schedule(venue).cloned()
+                .unwrap_or(FeeSchedule { tier: "none".to_string(), maker: 0.0, taker: 0.0, discount: 0.0 });
+            Arc::new(Self::new(venue, Arc::clone(store), schedule, config))
+        }).collect()
+    }
+
+    fn book(&self, symbol: &str) -> Option<Arc<MarketState>> {
+        let ex_id = self.store.exchange_id(&self.venue)?;
+        let sym_id = self.store.symbol_id(coin_symbol(&self.venue, symbol))?;
+        self.store.snapshot(ex_id, sym_id)
+    }
+
+    pub fn mark(&self, symbol: &str) -> Option<f64> {
+        self.book(symbol).and_then(|book| mid(&book))
+    }
+
+    fn apply_fill(&self, state: &mut PaperState, order: &mut OrderState, qty: f64, price: f64, liquidity: OrderType) {
+        if qty <= QTY_EPS { return; }
+        let notional = qty * price;
+        let fee = notional * self.fees.rate(liquidity);
+        order.avg_fill_price = (order.avg_fill_price * order.filled_qty + notional) / (order.filled_qty + qty);
+        order.filled_qty += qty;
+        order.fee_paid += fee;
+        order.status = if order.filled_qty + QTY_EPS >= order.qty { OrderStatus::Filled } else { OrderStatus::PartiallyFilled };
+        order.updated_at = self.now();
+
+        let position = state.positions.entry(order.symbol.clone()).or_insert((0.0, 0.0));
+        let pnl = fold_fill(position, signed(order.side, qty), price);
+        state.realised += pnl;
+        state.cash += pnl - fee;
+        state.fees += fee;
+    }
+
+    /// First contact with the book: take whatever crosses, then rest or cancel the remainder.
+    fn arrive(&self, state: &mut PaperState, order: &mut PaperOrder, book: &MarketState) {
+        let levels: Vec<(f64, f64)> = crossing(book, order.state.side, order.state.price).copied().collect();
+        if order.tif == TimeInForce::PostOnly && !levels.is_empty() {
+            order.state.status = OrderStatus::Rejected;
+            return;
+        }
+
+        let remaining = order.state.qty - order.state.filled_qty;
+        let (mut qty, mut notional) = (0.0, 0.0);
+        for (price, size) in levels {
+            let take = size.min(remaining - qty);
+            if take <= QTY_EPS { break; }
+            qty += take;
+            notional += take * price;
+        }
+        if qty > QTY_EPS { self.apply_fill(state, &mut order.state, qty, notional / qty, OrderType::Taker); }
+
+        if order.state.status.is_terminal() { return; }
+        match order.state.price {
+            Some(price) if order.tif != TimeInForce::Ioc => {
+                order.queue_ahead = own_level_qty(book, order.state.side, price) * self.config.queue_ahead_share;
+            }
+            _ => order.state.status = OrderStatus::Canceled,
+        }
+    }
+
+    /// Re-checks a resting order against a fresh book.
+    fn rest(&self, state: &mut PaperState, order: &mut PaperOrder, book: &MarketState) {
+        let Some(price) = order.state.price else { return };
+        // Shrinking size at our level means the queue ahead traded or cancelled
+        let displayed = own_level_qty(book, order.state.side, price) * self.config.queue_ahead_share;
+        order.queue_ahead = order.queue_ahead.min(displayed);
+
+        // Size strictly through our price would have hit us; size at our price serves the queue first
+        let mut available = 0.0;
+        for (level, size) in crossing(book, order.state.side, Some(price)) {
+            if (level - price).abs() <= price * 1e-9 {
+                let served = size.min(order.queue_ahead);
+                order.queue_ahead -= served;
+                available += size - served;
+            } else {
+                available += size;
+            }
+        }
+        let qty = available.min(order.state.qty - order.state.filled_qty);
+        self.apply_fill(state, &mut order.state, qty, price, OrderType::Maker);
+    }
+
+    /// Matches every resting order on `coin` against its latest book.
+    pub fn on_book(&self, coin: &str) {
+        let mut state = self.state.lock().unwrap();
+        let live: Vec<String> = state.orders.iter()
+            .filter(|(_, o)| !o.state.status.is_terminal() && coin_symbol(&self.venue, &o.state.symbol) == coin)
+            .map(|(cid, _)| cid.clone())
+            .collect();
+        for cid in live {
+            let Some(mut order) = state.orders.remove(&cid) else { continue };
+            if let Some(book) = self.book(&order.state.symbol) { self.rest(&mut state, &mut order, &book); }
+            state.orders.insert(cid, order);
+        }
+    }
+
+    /// Pays or charges funding for every payout time crossed since the last call.
+    pub fn accrue_funding(&self, now: DateTime<Utc>) -> Vec<FundingPayment> {
+        let mut state = self.state.lock().unwrap();
+        let since = std::mem::replace(&mut state.last_accrual, now);
+        let elapsed = (now - since).num_seconds();
+        let held: Vec<(String, f64)> = state.positions.iter()
+            .filter(|(_, (qty, _))| *qty != 0.0)
+            .map(|(symbol, (qty, _))| (symbol.clone(), *qty))
+            .collect();
+
+        let mut payments = Vec::new();
+        for (symbol, qty) in held {
+            let Some(book) = self.book(&symbol) else { continue };
+            if seconds_to_payout(&self.venue, book.funding, since) > elapsed { continue; }
+            let Some(mark) = mid(&book) else { continue };
+            // Longs pay positive funding, shorts receive it
+            let amount = -qty * mark * book.funding;
+            state.cash += amount;
+            state.funding += amount;
+            payments.push(FundingPayment { venue: self.venue.clone(), symbol, qty, rate: book.funding, amount, ts: now });
+        }
+        state.payouts.extend(payments.iter().cloned());
+        payments
+    }
+
+    fn unrealised(&self, state: &PaperState) -> f64 {
+        state.positions.iter()
+            .map(|(symbol, (qty, entry))| (self.mark(symbol).unwrap_or(*entry) - entry) * qty)
+            .sum()
+    }
+
+    pub fn summary(&self) -> VenueSummary {
+        let state = self.state.lock().unwrap();
+        let unrealised = self.unrealised(&state);
+        VenueSummary {
+            venue: self.venue.clone(),
+            cash: state.cash,
+            equity: state.cash + unrealised,
+            realised: state.realised,
+            unrealised,
+            funding: state.funding,
+            fees: state.fees,
+        }
+    }
+}
+
+#[async_trait]
+impl ExchangeClient for PaperExchange {
+    fn venue(&self) -> &str {
+        &self.venue
+    }
+
+    async fn place_order(&self, req: &OrderRequest) -> Result<OrderState, ExchangeError> {
+        if let Some(existing) = self.state.lock().unwrap().orders.get(&req.client_order_id) {
+            return Ok(existing.state.clone());
+        }
+        let simulated = self.clock.lock().unwrap().is_some();
+        if !simulated { tokio::time::sleep(Duration::from_millis(self.config.latency_ms)).await; }
+        let book = self.book(&req.symbol)
+            .ok_or_else(|| ExchangeError::Rejected(format!("no book for {}", req.symbol)))?;
+
+        let mut state = self.state.lock().unwrap();
+        let mut order = PaperOrder {
+            state: OrderState {
+                venue: self.venue.clone(),
+                client_order_id: req.client_order_id.clone(),
+                exchange_order_id: format!("paper-{}", state.orders.len() + 1),
+                symbol: req.symbol.clone(),
+                side: req.side,
+                qty: req.qty,
+                price: req.price,
+                filled_qty: 0.0,
+                avg_fill_price: 0.0,
+                fee_paid: 0.0,
+                status: OrderStatus::New,
+                updated_at: self.now(),
+            },
+            tif: req.tif,
+            queue_ahead: 0.0,
+        };
+        self.arrive(&mut state, &mut order, &book);
+        let snapshot = order.state.clone();
+        state.orders.insert(req.client_order_id.clone(), order);
+        Ok(snapshot)
+    }
+
+    async fn cancel_order(&self, _symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let mut state = self.state.lock().unwrap();
+        let order = state.orders.get_mut(client_order_id).ok_or(ExchangeError::NotFound)?;
+        if !order.state.status.is_terminal() {
+            order.state.status = OrderStatus::Canceled;
+            order.state.updated_at = self.now();
+        }
+        Ok(order.state.clone())
+    }
+
+    async fn order_status(&self, _symbol: &str, client_order_id: &str) -> Result<OrderState, ExchangeError> {
+        let state = self.state.lock().unwrap();
+        state.orders.get(client_order_id).map(|o| o.state.clone()).ok_or(ExchangeError::NotFound)
+    }
+
+    async fn positions(&self) -> Result<Vec<VenuePosition>, ExchangeError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.positions.iter().filter(|(_, (qty, _))| *qty != 0.0).map(|(symbol, (qty, entry))| {
+            let mark = self.mark(symbol).unwrap_or(*entry);
+            VenuePosition {
+                venue: self.venue.clone(),
+                symbol: symbol.clone(),
+                qty: *qty,
+                entry_price: *entry,
+                unrealized_pnl: (mark - entry) * qty,
+                liquidation_price: None,
+            }
+        }).collect())
+    }
+
+    async fn balance(&self) -> Result<Balance, ExchangeError> {
+        let state = self.state.lock().unwrap();
+        let margin: f64 = state.positions.iter()
+            .map(|(symbol, (qty, entry))| (qty * self.mark(symbol).unwrap_or(*entry)).abs() / self.config.leverage)
+            .sum();
+        let equity = state.cash + self.unrealised(&state);
+        Ok(Balance { equity, available: equity - margin, margin_used: margin })
+    }
+
+    async fn funding_history(&self, since: DateTime<Utc>) -> Result<Vec<FundingIncome>, ExchangeError> {
+        let state = self.state.lock().unwrap();
+        Ok(state.payouts.iter().filter(|p| p.ts >= since).map(|p| FundingIncome {
+            venue: p.venue.clone(),
+            symbol: p.symbol.clone(),
+            amount: p.amount,
+            ts: p.ts,
+            rate: Some(p.rate),
+            qty: Some(p.qty),
+        }).collect())
+    }
+}
+
+#[derive(Serialize)]
+struct PaperReport {
+    ts: DateTime<Utc>,
+    venues: Vec<VenueSummary>,
+    pairs: Vec<PositionPnl>,
+}
+
+fn print_report(report: &PaperReport) {
+    println!("📊 [Paper] {}", report.ts.format("%H:%M:%S"));
+    for v in &report.venues {
+        println!("   {:<12} equity {:>10.2} | cash {:>10.2} | rPnL {:>8.2} | uPnL {:>8.2} | fund {:>7.2} | fees {:>7.2}",
+            v.venue, v.equity, v.cash, v.realised, v.unrealised, v.funding, v.fees);
+    }
+    for p in &report.pairs {
+        let (r, u) = (&p.realised, &p.unrealised);
+        println!("   {:<24} net {:>8.2} | basis {:>8.2} | slip {:>7.2} | uPnL {:>8.2} | fund {:>7.2} | fees {:>7.2}{}",
+            p.pair_id, p.net, r.basis, r.slippage, u.basis + u.slippage, r.funding, r.fees, if p.closed_at.is_some() { " (closed)" } else { "" });
+    }
+}
+
+// --- 🚀 MAIN RUNNER ---
+
+/// Runs the executor against simulated venues fed by `market:data`.
+pub async fn run(venues: &[&str], fees: FeeModel, config: PaperConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let client = redis::Client::open("redis://localhost/")?;
+    let store = Arc::new(MarketStore::new(venues, MAX_SYMBOLS));
+    let mut updates = spawn_redis_feed(Arc::clone(&store));
+
+    let exchanges = PaperExchange::for_venues(venues, &store, &fees, config);
+    let by_venue: HashMap<String, Arc<PaperExchange>> = exchanges.iter().map(|e| (e.venue.clone(), Arc::clone(e))).collect();
+    let clients: Clients = exchanges.iter()
+        .map(|e| (e.venue.clone(), Arc::clone(e) as Arc<dyn ExchangeClient>))
+        .collect();
+
+    let (fill_tx, mut fill_rx) = mpsc::unbounded_channel();
+    let mut bus = client.get_multiplexed_async_connection().await?;
+    let execution = ExecutionConfig::load();
+    let mut executor = Executor::new(clients.clone(), bus.clone())
+        .with_config(&execution)
+        .with_fees(&fees)
+        .with_books(Arc::clone(&store))
+        .with_fill_tap(fill_tx);
+    let mut ledger = Ledger::append_only(std::env::var("LEDGER_FILE").unwrap_or_else(|_| PAPER_LEDGER_FILE.to_string()));
+    executor.restore_kill_switch().await;
+    let mut recon = FundingRecon::new(execution.funding_recon);
+    let (recon_tx, mut recon_rx) = mpsc::unbounded_channel();
+    let mut recon_timer = interval(Duration::from_secs(execution.funding_recon.interval_secs.max(1)));
+    let mut monitor = AccountMonitor::new(execution.accounts);
+    let (accounts_tx, mut accounts_rx) = mpsc::unbounded_channel();
+    let mut accounts_timer = interval(Duration::from_millis(execution.accounts.poll_ms.max(1)));
+    accounts_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let mut pubsub_conn = client.get_async_pubsub().await?;
+    pubsub_conn.subscribe(&[SIGNAL_CHANNEL, CONTROL_CHANNEL]).await?;
+    let mut signals = pubsub_conn.into_on_message();
+
+    let mut poll_timer = interval(Duration::from_millis(POLL_MS));
+    poll_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    let mut report_timer = interval(Duration::from_millis(REPORT_MS));
+    report_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+    println!("📝 Paper trading on {} ({}ms latency), listening on {}", venues.join(", "), config.latency_ms, SIGNAL_CHANNEL);
+
+    loop {
+        tokio::select! {
+            msg = signals.next() => {
+                let Some(msg) = msg else { break };
+                let payload: String = msg.get_payload().unwrap_or_default();
+                if msg.get_channel_name() == CONTROL_CHANNEL {
+                    executor.handle_control_payload(&payload).await;
+                } else {
+                    executor.handle_payload(&payload).await;
+                }
+            }
+            Some((ex_id, sym_id)) = updates.recv() => {
+                exchanges[ex_id].on_book(&store.symbol_name(sym_id));
+                executor.on_book(Utc::now()).await;
+            }
+            Some(executed) = fill_rx.recv() => ledger.record_fill(&executed),
+            _ = poll_timer.tick() => executor.poll_orders(Utc::now()).await,
+            _ = recon_timer.tick(), if recon.config().enabled => {
+                let since = Utc::now() - chrono::Duration::hours(recon.config().lookback_hours);
+                let held = ledger.instruments().cloned().collect();
+                let (clients, conn, tx) = (clients.clone(), bus.clone(), recon_tx.clone());
+                tokio::spawn(async move { let _ = tx.send(funding_recon::fetch(clients, conn, since, held).await); });
+            }
+            Some(fetched) = recon_rx.recv() => {
+                let report = recon.reconcile(&ledger, &fetched, Utc::now());
+                for payment in &report.unbooked { ledger.record_funding(payment); }
+                recon.publish(&report, &mut bus).await;
+            }
+            _ = accounts_timer.tick(), if monitor.config().enabled => {
+                let (clients, tx) = (clients.clone(), accounts_tx.clone());
+                tokio::spawn(async move { let _ = tx.send(account_monitor::poll(clients).await); });
+            }
+            Some(venues) = accounts_rx.recv() => {
+                let snapshot = monitor.on_poll(venues, Utc::now(), &mut bus).await;
+                executor.block_venues(&snapshot.blocked);
+            }
+            _ = report_timer.tick() => {
+                let now = Utc::now();
+                for exchange in &exchanges {
+                    for payment in exchange.accrue_funding(now) {
+                        println!("💸 [Paper] Funding {} {} {:+.4}", payment.venue, payment.symbol, payment.amount);
+                        ledger.record_funding(&payment);
+                    }
+                }
+                let report = PaperReport {
+                    ts: now,
+                    venues: exchanges.iter().map(|e| e.summary()).collect(),
+                    pairs: ledger.positions(|venue, symbol| by_venue.get(venue).and_then(|v| v.mark(symbol))),
+                };
+                print_report(&report);
+                if let Ok(raw) = serde_json::to_string(&report) {
+                    let _: () = bus.publish(PAPER_CHANNEL, raw).await.unwrap_or(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// A paper Binance on a store holding one BTC book, on replay time so orders skip the latency.
+    fn venue(bids: &[(f64, f64)], asks: &[(f64, f64)], now: DateTime<Utc>) -> (Arc<MarketStore>, PaperExchange) {
+        let store = Arc::new(MarketStore::new(&["binance"], 8));
+        let fees = FeeSchedule { tier: "test".to_string(), maker: 0.0002, taker: 0.0005, discount: 0.0 };
+        let paper = PaperExchange::new("binance", Arc::clone(&store), fees, PaperConfig::default());
+        quote(&store, bids, asks);
+        paper.set_time(now);
+        (store, paper)
+    }
+
+    fn quote(store: &MarketStore, bids: &[(f64, f64)], asks: &[(f64, f64)]) {
+        let symbol = store.intern_symbol("BTC").unwrap();
+        store.publish(0, symbol, MarketState { bids: bids.to_vec(), asks: asks.to_vec(), funding: 0.0001 });
+    }
+
+    fn order(side: Side, qty: f64, price: Option<f64>, tif: TimeInForce) -> OrderRequest {
+        OrderRequest { client_order_id: format!("{:?}-{}-{:?}", tif, qty, price), symbol: "BTCUSDT".to_string(), side, qty, price, tif, reduce_only: false }
+    }
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-9
+    }
+
+    #[test]
+    fn fills_fold_into_entry_and_realised_pnl() {
+        let mut position = (0.0, 0.0);
+        assert_eq!(fold_fill(&mut position, 1.0, 100.0), 0.0);
+        assert_eq!(fold_fill(&mut position, 1.0, 110.0), 0.0);
+        assert_eq!(position, (2.0, 105.0));
+        // Reducing realises against the entry and leaves it alone
+        assert!(close(fold_fill(&mut position, -0.5, 120.0), 7.5));
+        assert_eq!(position, (1.5, 105.0));
+        // Flipping closes the rest and re-enters short at the fill
+        assert!(close(fold_fill(&mut position, -2.5, 90.0), -22.5));
+        assert_eq!(position, (-1.0, 90.0));
+        assert!(close(fold_fill(&mut position, 1.0, 80.0), 10.0));
+        assert_eq!(position, (0.0, 0.0));
+    }
+
+    #[tokio::test]
+    async fn marketable_orders_sweep_the_book_and_cancel_the_rest() {
+        let (_, paper) = venue(&[(99.0, 5.0)], &[(100.0, 1.0), (101.0, 1.0), (102.0, 1.0)], Utc::now());
+        let swept = paper.place_order(&order(Side::Buy, 1.5, None, TimeInForce::Ioc)).await.unwrap();
+        assert_eq!((swept.status, swept.filled_qty), (OrderStatus::Filled, 1.5));
+        assert!(close(swept.avg_fill_price, 150.5 / 1.5));
+        assert!(close(swept.fee_paid, 150.5 * 0.0005));
+
+        // Only 100 and 101 are inside the limit; the IOC remainder is cancelled, not rested
+        let (_, paper) = venue(&[(99.0, 5.0)], &[(100.0, 1.0), (101.0, 1.0), (102.0, 1.0)], Utc::now());
+        let limited = paper.place_order(&order(Side::Buy, 3.0, Some(101.0), TimeInForce::Ioc)).await.unwrap();
+        assert_eq!((limited.status, limited.filled_qty), (OrderStatus::Canceled, 2.0));
+    }
+
+    #[tokio::test]
+    async fn post_only_orders_that_would_cross_are_rejected() {
+        let (_, paper) = venue(&[(99.0, 1.0)], &[(100.0, 1.0)], Utc::now());
+        let crossing = paper.place_order(&order(Side::Buy, 1.0, Some(100.0), TimeInForce::PostOnly)).await.unwrap();
+        assert_eq!((crossing.status, crossing.filled_qty), (OrderStatus::Rejected, 0.0));
+        assert!(paper.positions().await.unwrap().is_empty());
+
+        let passive = paper.place_order(&order(Side::Buy, 1.0, Some(99.5), TimeInForce::PostOnly)).await.unwrap();
+        assert_eq!(passive.status, OrderStatus::New);
+    }
+
+    #[tokio::test]
+    async fn resting_orders_wait_for_the_queue_ahead_of_them() {
+        let (store, paper) = venue(&[(99.0, 2.0)], &[(100.0, 1.0)], Utc::now());
+        let cid = paper.place_order(&order(Side::Buy, 1.0, Some(99.0), TimeInForce::Gtc)).await.unwrap().client_order_id;
+        let status = || async { paper.order_status("BTCUSDT", &cid).await.unwrap() };
+
+        // 1 trades at our price, but 2 were displayed ahead of us
+        quote(&store, &[(99.0, 2.0)], &[(99.0, 1.0)]);
+        paper.on_book("BTC");
+        assert_eq!(status().await.filled_qty, 0.0);
+
+        // The last 1 ahead is served first, then half of what is left reaches us
+        quote(&store, &[(99.0, 1.0)], &[(99.0, 1.5)]);
+        paper.on_book("BTC");
+        let partial = status().await;
+        assert_eq!((partial.status, partial.filled_qty), (OrderStatus::PartiallyFilled, 0.5));
+        assert!(close(partial.fee_paid, 0.5 * 99.0 * 0.0002), "resting fills pay maker");
+
+        // Size strictly through our price fills us at our price
+        quote(&store, &[(98.0, 1.0)], &[(98.5, 5.0)]);
+        paper.on_book("BTC");
+        let filled = status().await;
+        assert_eq!((filled.status, filled.filled_qty, filled.avg_fill_price), (OrderStatus::Filled, 1.0, 99.0));
+    }
+
+    #[tokio::test]
+    async fn funding_is_paid_once_per_payout_crossed() {
+        let start = Utc.with_ymd_and_hms(2026, 3, 2, 7, 59, 0).unwrap();
+        let (_, paper) = venue(&[(99.0, 5.0)], &[(101.0, 5.0)], start);
+        paper.place_order(&order(Side::Buy, 2.0, None, TimeInForce::Ioc)).await.unwrap();
+
+        assert!(paper.accrue_funding(start + chrono::Duration::seconds(30)).is_empty(), "08:00 not reached yet");
+        let paid = paper.accrue_funding(start + chrono::Duration::seconds(90));
+        assert_eq!(paid.len(), 1);
+        // Long 2 at a 100 mark pays 1bp of funding
+        assert!(close(paid[0].amount, -2.0 * 100.0 * 0.0001));
+        assert!(paper.accrue_funding(start + chrono::Duration::seconds(3600)).is_empty(), "next payout is 16:00");
+
+        let history = paper.funding_history(start).await.unwrap();
+        assert_eq!((history.len(), history[0].qty, history[0].rate), (1, Some(2.0), Some(0.0001)));
+        assert!(close(paper.summary().funding, paid[0].amount));
+    }
+}
@@ -0,0 +1,382 @@
+use super::exchange::Fill;
+use super::ledger::OpenLeg;
+use crate::ingestors::signals::{coin_symbol, Side, SignalKind, SignalLeg, TradeSignal};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use uuid::Uuid;
+
+const QTY_EPS: f64 = 1e-9;
+
+/// Pre-trade limits checked before an open is sent. Notionals are gross: both legs of a pair count.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RiskLimits {
+    pub max_symbol_notional_usd: f64,
+    pub max_exchange_notional_usd: f64,
+    pub max_total_notional_usd: f64,
+    /// Realised plus marked PnL since UTC midnight, net of fees. Breaching it trips the kill switch.
+    pub max_daily_loss_usd: f64,
+    /// Pairs with any leg still open.
+    pub max_open_positions: usize,
+    /// Orders placed in any rolling minute, requotes and hedges included. Breaching it trips the kill switch.
+    pub max_orders_per_minute: usize,
+    /// Whether an automatic trip also flattens, or only halts entries.
+    pub flatten_on_breach: bool,
+}
+
+impl Default for RiskLimits {
+    fn default() -> Self {
+        Self {
+            max_symbol_notional_usd: 50_000.0,
+            max_exchange_notional_usd: 100_000.0,
+            max_total_notional_usd: 150_000.0,
+            max_daily_loss_usd: 1_000.0,
+            max_open_positions: 5,
+            max_orders_per_minute: 300,
+            flatten_on_breach: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RiskBreach { SymbolNotional, ExchangeNotional, TotalNotional, DailyLoss, OpenPositions, OrderRate, Liquidation }
+
+/// One leg of one pair as the executor has filled it.
+#[derive(Debug, Clone)]
+pub struct Exposure {
+    pub pair_id: String,
+    pub venue: String,
+    pub symbol: String,
+    /// Signed base quantity; positive is long.
+    pub qty: f64,
+    /// Last fill price, used when there is no book to mark against.
+    pub price: f64,
+    /// The signal and leg behind the latest fill, so closing orders are attributed to them.
+    pub signal_id: Uuid,
+    pub leg: usize,
+}
+
+/// What an accepted open may still add, per leg: (venue, venue symbol, qty not yet filled, price).
+#[derive(Debug, Clone)]
+struct Reservation {
+    pair_id: String,
+    legs: Vec<(String, String, f64, f64)>,
+}
+
+/// Everything the pre-trade checks need, built from the executor's own fills.
+pub struct RiskBook {
+    limits: RiskLimits,
+    exposures: HashMap<(String, String, String), Exposure>,
+    /// Accepted opens that have not fully filled or finished yet, by signal ID. They count
+    /// against the limits as if filled, so a burst of signals cannot all pass before the first fill.
+    reserved: HashMap<Uuid, Reservation>,
+    /// Quote cash from every fill since start, fees included.
+    cash: f64,
+    day: Option<NaiveDate>,
+    /// PnL at the start of `day`.
+    day_open: f64,
+    /// Day on which the loss limit last tripped, so a resume is not immediately undone.
+    loss_tripped: Option<NaiveDate>,
+    orders: VecDeque<DateTime<Utc>>,
+    /// Venues with a leg too close to liquidation, from the account monitor.
+    blocked_venues: HashSet<String>,
+}
+
+impl RiskBook {
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            exposures: HashMap::new(),
+            reserved: HashMap::new(),
+            cash: 0.0,
+            day: None,
+            day_open: 0.0,
+            loss_tripped: None,
+            orders: VecDeque::new(),
+            blocked_venues: HashSet::new(),
+        }
+    }
+
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Starts from the ledger's open legs and today's realised PnL, so a restart neither forgets
+    /// what is held nor resets the daily loss. Legs count from their entry price, so anything they
+    /// were already down before today is charged to today as well.
+    pub fn seed(&mut self, legs: &[OpenLeg], realised_today: f64, now: DateTime<Utc>) {
+        let mut signals: HashMap<&str, (Uuid, usize)> = HashMap::new();
+        for leg in legs {
+            let (signal_id, count) = signals.entry(&leg.pair_id).or_insert((Uuid::new_v4(), 0));
+            self.cash -= leg.qty * leg.entry;
+            self.exposures.insert((leg.pair_id.clone(), leg.venue.clone(), leg.symbol.clone()), Exposure {
+                pair_id: leg.pair_id.clone(),
+                venue: leg.venue.clone(),
+                symbol: leg.symbol.clone(),
+                qty: leg.qty,
+                price: leg.entry,
+                signal_id: *signal_id,
+                leg: *count,
+            });
+            *count += 1;
+        }
+        self.day = Some(now.date_naive());
+        self.day_open = -realised_today;
+    }
+
+    pub fn record_fill(&mut self, pair_id: &str, (signal_id, leg): (Uuid, usize), fill: &Fill) {
+        let signed = if fill.side == Side::Buy { fill.qty } else { -fill.qty };
+        self.cash -= signed * fill.price + fill.fee;
+        let key = (pair_id.to_string(), fill.venue.clone(), fill.symbol.clone());
+        let exposure = self.exposures.entry(key.clone()).or_insert_with(|| Exposure {
+            pair_id: pair_id.to_string(),
+            venue: fill.venue.clone(),
+            symbol: fill.symbol.clone(),
+            qty: 0.0,
+            price: fill.price,
+            signal_id,
+            leg,
+        });
+        exposure.qty += signed;
+        exposure.price = fill.price;
+        exposure.signal_id = signal_id;
+        exposure.leg = leg;
+        if exposure.qty.abs() <= QTY_EPS { self.exposures.remove(&key); }
+        // What filled is exposure now, not reservation
+        if let Some((_, _, left, _)) = self.reserved.get_mut(&signal_id).and_then(|r| r.legs.get_mut(leg)) {
+            *left = (*left - fill.qty).max(0.0);
+        }
+    }
+
+    /// Holds an accepted open's notional and pair against the limits until it fills or is released.
+    pub fn reserve(&mut self, signal: &TradeSignal, mark: &impl Fn(&str, &str) -> Option<f64>) {
+        if signal.kind != SignalKind::OpenLimit { return; }
+        let legs = signal.legs.iter()
+            .map(|leg| (leg.venue.clone(), leg.symbol.clone(), leg.size, Self::leg_price(leg, mark)))
+            .collect();
+        self.reserved.insert(signal.id, Reservation { pair_id: signal.pair_id.clone(), legs });
+    }
+
+    /// Drops whatever is left of a leg's reservation once no order is working it any more.
+    pub fn release_leg(&mut self, signal_id: Uuid, leg: usize) {
+        if let Some((_, _, left, _)) = self.reserved.get_mut(&signal_id).and_then(|r| r.legs.get_mut(leg)) { *left = 0.0; }
+    }
+
+    /// Drops a signal's reservation once all its orders are done.
+    pub fn release(&mut self, signal_id: Uuid) {
+        self.reserved.remove(&signal_id);
+    }
+
+    pub fn release_all(&mut self) {
+        self.reserved.clear();
+    }
+
+    /// Replaces the set of venues no open may touch.
+    pub fn block_venues(&mut self, venues: &[String]) {
+        self.blocked_venues = venues.iter().cloned().collect();
+    }
+
+    pub fn record_order(&mut self, now: DateTime<Utc>) {
+        self.orders.push_back(now);
+        self.prune_orders(now);
+    }
+
+    fn prune_orders(&mut self, now: DateTime<Utc>) {
+        while self.orders.front().is_some_and(|ts| now - *ts >= Duration::minutes(1)) { self.orders.pop_front(); }
+    }
+
+    pub fn exposures(&self) -> impl Iterator<Item = &Exposure> {
+        self.exposures.values()
+    }
+
+    fn notional(exposure: &Exposure, mark: &impl Fn(&str, &str) -> Option<f64>) -> f64 {
+        exposure.qty.abs() * mark(&exposure.venue, &exposure.symbol).unwrap_or(exposure.price)
+    }
+
+    fn leg_price(leg: &SignalLeg, mark: &impl Fn(&str, &str) -> Option<f64>) -> f64 {
+        leg.limit_price.or_else(|| mark(&leg.venue, &leg.symbol)).unwrap_or(0.0)
+    }
+
+    /// Gross notional held plus reserved, per (venue, venue symbol) of every open leg.
+    fn committed(&self, mark: &impl Fn(&str, &str) -> Option<f64>) -> Vec<(&str, &str, f64)> {
+        let held = self.exposures.values().map(|e| (e.venue.as_str(), e.symbol.as_str(), Self::notional(e, mark)));
+        let reserved = self.reserved.values()
+            .flat_map(|r| &r.legs)
+            .map(|(venue, symbol, left, price)| (venue.as_str(), symbol.as_str(), left * price));
+        held.chain(reserved).collect()
+    }
+
+    /// Realised plus marked PnL since start.
+    fn pnl(&self, mark: &impl Fn(&str, &str) -> Option<f64>) -> f64 {
+        self.cash + self.exposures.values().map(|e| e.qty * mark(&e.venue, &e.symbol).unwrap_or(e.price)).sum::<f64>()
+    }
+
+    /// PnL since UTC midnight, rolling the day over when `now` crosses it.
+    pub fn daily_pnl(&mut self, now: DateTime<Utc>, mark: &impl Fn(&str, &str) -> Option<f64>) -> f64 {
+        let pnl = self.pnl(mark);
+        if self.day != Some(now.date_naive()) {
+            self.day = Some(now.date_naive());
+            self.day_open = pnl;
+        }
+        pnl - self.day_open
+    }
+
+    /// Breaches that should trip the kill switch on their own, each reported once per episode.
+    pub fn check_trips(&mut self, now: DateTime<Utc>, mark: &impl Fn(&str, &str) -> Option<f64>) -> Option<(RiskBreach, String)> {
+        self.prune_orders(now);
+        if self.orders.len() > self.limits.max_orders_per_minute {
+            let detail = format!("{} orders in the last minute (max {})", self.orders.len(), self.limits.max_orders_per_minute);
+            self.orders.clear();
+            return Some((RiskBreach::OrderRate, detail));
+        }
+        let daily = self.daily_pnl(now, mark);
+        if daily <= -self.limits.max_daily_loss_usd && self.loss_tripped != self.day {
+            self.loss_tripped = self.day;
+            return Some((RiskBreach::DailyLoss, format!("daily PnL ${:.2} (max loss ${:.2})", daily, self.limits.max_daily_loss_usd)));
+        }
+        None
+    }
+
+    /// Checks an open against every limit as if it had fully filled at its limit prices.
+    pub fn check_open(&mut self, signal: &TradeSignal, now: DateTime<Utc>, mark: &impl Fn(&str, &str) -> Option<f64>) -> Result<(), RiskBreach> {
+        if signal.kind != SignalKind::OpenLimit { return Ok(()); }
+        if signal.legs.iter().any(|leg| self.blocked_venues.contains(&leg.venue)) { return Err(RiskBreach::Liquidation); }
+        self.prune_orders(now);
+        if self.orders.len() + signal.legs.len() > self.limits.max_orders_per_minute { return Err(RiskBreach::OrderRate); }
+        if self.daily_pnl(now, mark) <= -self.limits.max_daily_loss_usd { return Err(RiskBreach::DailyLoss); }
+
+        let open: HashSet<&str> = self.exposures.values().map(|e| e.pair_id.as_str())
+            .chain(self.reserved.values().map(|r| r.pair_id.as_str()))
+            .collect();
+        if !open.contains(signal.pair_id.as_str()) && open.len() >= self.limits.max_open_positions {
+            return Err(RiskBreach::OpenPositions);
+        }
+
+        let added = |leg: &SignalLeg| leg.size * Self::leg_price(leg, mark);
+        let committed = self.committed(mark);
+        let total: f64 = committed.iter().map(|(_, _, notional)| notional).sum::<f64>()
+            + signal.legs.iter().map(added).sum::<f64>();
+        if total > self.limits.max_total_notional_usd { return Err(RiskBreach::TotalNotional); }
+
+        for leg in &signal.legs {
+            let coin = coin_symbol(&leg.venue, &leg.symbol);
+            let symbol: f64 = committed.iter()
+                .filter(|(venue, symbol, _)| coin_symbol(venue, symbol) == coin)
+                .map(|(_, _, notional)| notional)
+                .sum::<f64>()
+                + signal.legs.iter().filter(|l| coin_symbol(&l.venue, &l.symbol) == coin).map(added).sum::<f64>();
+            if symbol > self.limits.max_symbol_notional_usd { return Err(RiskBreach::SymbolNotional); }
+
+            let exchange: f64 = committed.iter()
+                .filter(|(venue, _, _)| *venue == leg.venue)
+                .map(|(_, _, notional)| notional)
+                .sum::<f64>()
+                + signal.legs.iter().filter(|l| l.venue == leg.venue).map(added).sum::<f64>();
+            if exchange > self.limits.max_exchange_notional_usd { return Err(RiskBreach::ExchangeNotional); }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open(pair: &str, coin: &str, qty: f64, price: f64) -> TradeSignal {
+        let leg = |venue: &str, side| SignalLeg {
+            venue: venue.to_string(),
+            symbol: format!("{}USDT", coin),
+            side,
+            size: qty,
+            limit_price: Some(price),
+        };
+        TradeSignal::new(SignalKind::OpenLimit, pair, Utc::now(), 5_000, vec![leg("binance", Side::Buy), leg("bybit", Side::Sell)])
+    }
+
+    fn fill(venue: &str, side: Side, qty: f64, price: f64) -> Fill {
+        Fill {
+            venue: venue.to_string(),
+            client_order_id: String::new(),
+            symbol: "BTCUSDT".to_string(),
+            side,
+            qty,
+            price,
+            fee: 0.0,
+            ts: Utc::now(),
+        }
+    }
+
+    const NO_MARK: fn(&str, &str) -> Option<f64> = |_, _| None;
+
+    #[test]
+    fn restarts_resume_from_the_ledger() {
+        use super::super::executor::ExecutedFill;
+        use super::super::ledger::Ledger;
+        let now = Utc::now();
+        let mut ledger = Ledger::default();
+        let mut book_fill = |pair: &str, venue: &str, side, qty, price| ledger.record_fill(&ExecutedFill {
+            signal_id: Uuid::new_v4(),
+            pair_id: pair.to_string(),
+            fill: Fill { ts: now, ..fill(venue, side, qty, price) },
+            reference_price: Some(price),
+        });
+        // A round trip that lost 600 today, and a pair still open
+        book_fill("ETH-binance-bybit", "binance", Side::Buy, 1.0, 3_000.0);
+        book_fill("ETH-binance-bybit", "binance", Side::Sell, 1.0, 2_400.0);
+        book_fill("BTC-binance-bybit", "binance", Side::Buy, 0.1, 100_000.0);
+        book_fill("BTC-binance-bybit", "bybit", Side::Sell, 0.1, 100_000.0);
+
+        let mut book = RiskBook::new(RiskLimits { max_open_positions: 1, ..RiskLimits::default() });
+        book.seed(&ledger.open_legs(), ledger.realised_on(now.date_naive()), now);
+        assert_eq!(book.exposures().count(), 2);
+        assert!((book.daily_pnl(now, &NO_MARK) + 600.0).abs() < 1e-6);
+        assert_eq!(book.check_open(&open("SOL-binance-bybit", "SOL", 1.0, 200.0), now, &NO_MARK), Err(RiskBreach::OpenPositions));
+
+        // Binance long marked 5k down is another 500 lost: past the 1000 limit
+        let mark = |venue: &str, _: &str| Some(if venue == "binance" { 95_000.0 } else { 100_000.0 });
+        assert_eq!(book.check_trips(now, &mark).map(|(breach, _)| breach), Some(RiskBreach::DailyLoss));
+    }
+
+    #[test]
+    fn accepted_opens_count_before_they_fill() {
+        let mut book = RiskBook::new(RiskLimits { max_symbol_notional_usd: 25_000.0, ..RiskLimits::default() });
+        let first = open("BTC-binance-bybit", "BTC", 0.1, 100_000.0);
+        assert_eq!(book.check_open(&first, Utc::now(), &NO_MARK), Ok(()));
+        book.reserve(&first, &NO_MARK);
+        // 20k resting plus another 20k would breach the 25k symbol limit
+        let second = open("BTC-binance-bybit", "BTC", 0.1, 100_000.0);
+        assert_eq!(book.check_open(&second, Utc::now(), &NO_MARK), Err(RiskBreach::SymbolNotional));
+
+        book.release(first.id);
+        assert_eq!(book.check_open(&second, Utc::now(), &NO_MARK), Ok(()));
+    }
+
+    #[test]
+    fn reserved_pairs_count_as_open_positions() {
+        let mut book = RiskBook::new(RiskLimits { max_open_positions: 1, ..RiskLimits::default() });
+        let first = open("BTC-binance-bybit", "BTC", 0.01, 100_000.0);
+        book.reserve(&first, &NO_MARK);
+        let other = open("ETH-binance-bybit", "ETH", 0.1, 3_000.0);
+        assert_eq!(book.check_open(&other, Utc::now(), &NO_MARK), Err(RiskBreach::OpenPositions));
+    }
+
+    #[test]
+    fn fills_move_notional_from_reservation_to_exposure() {
+        let mut book = RiskBook::new(RiskLimits { max_total_notional_usd: 45_000.0, ..RiskLimits::default() });
+        let first = open("BTC-binance-bybit", "BTC", 0.1, 100_000.0);
+        book.reserve(&first, &NO_MARK);
+        book.record_fill(&first.pair_id, (first.id, 0), &fill("binance", Side::Buy, 0.1, 100_000.0));
+        book.record_fill(&first.pair_id, (first.id, 1), &fill("bybit", Side::Sell, 0.1, 100_000.0));
+        let committed: f64 = book.committed(&NO_MARK).iter().map(|(_, _, notional)| notional).sum();
+        assert!((committed - 20_000.0).abs() < 1e-6, "filled legs counted twice: {}", committed);
+
+        // Once a leg stops working, what it left unfilled no longer counts
+        let second = open("ETH-binance-bybit", "ETH", 5.0, 3_000.0);
+        book.reserve(&second, &NO_MARK);
+        assert_eq!(book.check_open(&open("SOL-binance-bybit", "SOL", 10.0, 200.0), Utc::now(), &NO_MARK), Err(RiskBreach::TotalNotional));
+        book.release_leg(second.id, 0);
+        book.release_leg(second.id, 1);
+        assert_eq!(book.check_open(&open("SOL-binance-bybit", "SOL", 10.0, 200.0), Utc::now(), &NO_MARK), Ok(()));
+    }
+}
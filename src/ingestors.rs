@@ -1,11 +1,54 @@
-#[path = "ingestors/binance_ingestor.rs"]
-pub mod binance;
-
-#[path = "ingestors/bybit_ingestor.rs"]
-pub mod bybit;
-
-#[path = "ingestors/hyperliquid_ingestor.rs"]
-pub mod hyperliquid;
-
-#[path = "ingestors/scanner.rs"]
-pub mod scanner;
\ No newline at end of file
+#[path = "ingestors/binance_ingestor.rs"]
+pub mod binance;
+
+#[path = "ingestors/bybit_ingestor.rs"]
+pub mod bybit;
+
+#[path = "ingestors/hyperliquid_ingestor.rs"]
+pub mod hyperliquid;
+
+#[path = "ingestors/scanner.rs"]
+pub mod scanner;
+
+#[path = "ingestors/strategy.rs"]
+pub mod strategy;
+
+#[path = "ingestors/market_store.rs"]
+pub mod market_store;
+
+#[path = "ingestors/pnl_model.rs"]
+pub mod pnl_model;
+
+#[path = "ingestors/fees.rs"]
+pub mod fees;
+
+#[path = "ingestors/sizing.rs"]
+pub mod sizing;
+
+#[path = "ingestors/basis_stats.rs"]
+pub mod basis_stats;
+
+#[path = "ingestors/portfolio.rs"]
+pub mod portfolio;
+
+#[path = "ingestors/strategy_config.rs"]
+pub mod strategy_config;
+
+#[path = "ingestors/checkpoint.rs"]
+pub mod checkpoint;
+
+#[path = "ingestors/signals.rs"]
+pub mod signals;
+
+#[path = "ingestors/control.rs"]
+pub mod control;
+
+
+#[path = "ingestors/funding_rates.rs"]
+pub mod funding_rates;
+
+#[path = "ingestors/accounts.rs"]
+pub mod accounts;
+
+#[path = "ingestors/capital.rs"]
+pub mod capital;
\ No newline at end of file
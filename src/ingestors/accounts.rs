@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+/// Account health published by the executor after every poll.
+pub const ACCOUNTS_CHANNEL: &str = "trade:accounts";
+/// The latest snapshot, so a restarted scanner does not wait a whole poll for it.
+const ACCOUNTS_KEY: &str = "trade:accounts:latest";
+/// Past this age the executor has probably stopped and the snapshot no longer blocks anything.
+const MAX_SNAPSHOT_AGE_SECS: i64 = 120;
+
+/// One leg as the venue reports it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LegHealth {
+    pub venue: String,
+    pub symbol: String,
+    pub qty: f64,
+    pub mark: f64,
+    pub liquidation_price: Option<f64>,
+    /// |mark - liquidation| as a share of the mark; `None` when the venue reports no liquidation price.
+    pub distance: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VenueAccount {
+    pub venue: String,
+    pub equity: f64,
+    pub available: f64,
+    pub margin_used: f64,
+    /// Margin used over equity.
+    pub margin_ratio: f64,
+    pub legs: Vec<LegHealth>,
+    /// Set when the venue could not be read this poll; the figures are then zero.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountSnapshot {
+    pub ts: DateTime<Utc>,
+    pub venues: Vec<VenueAccount>,
+    /// Venues holding a leg too close to liquidation; nothing new is opened there.
+    pub blocked: Vec<String>,
+}
+
+impl AccountSnapshot {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json(raw: &str) -> Option<Self> {
+        serde_json::from_str(raw).ok()
+    }
+
+    pub fn is_fresh(&self, now: DateTime<Utc>) -> bool {
+        (now - self.ts).num_seconds() <= MAX_SNAPSHOT_AGE_SECS
+    }
+
+    /// The last published snapshot, if it is still fresh.
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection, now: DateTime<Utc>) -> Option<Self> {
+        let raw: String = conn.get::<_, Option<String>>(ACCOUNTS_KEY).await.ok()??;
+        Self::from_json(&raw).filter(|snapshot| snapshot.is_fresh(now))
+    }
+
+    /// Stores the snapshot and broadcasts it.
+    pub async fn publish(&self, conn: &mut redis::aio::MultiplexedConnection) {
+        let raw = self.to_json();
+        let _: () = conn.set(ACCOUNTS_KEY, &raw).await.unwrap_or(());
+        let _: () = conn.publish(ACCOUNTS_CHANNEL, raw).await.unwrap_or(());
+    }
+}
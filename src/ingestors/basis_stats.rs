@@ -0,0 +1,177 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// Updates landing in the same bucket overwrite each other, which bounds memory on busy books.
+const BUCKET_MS: i64 = 100;
+/// Stats are computed on a regular grid so bursts of updates do not outweigh quiet periods.
+const GRID_MS: i64 = 1000;
+const MIN_GRID_POINTS: usize = 30;
+const MIN_SLOPE_SAMPLES: usize = 10;
+
+/// Timestamped basis samples for one pair over a fixed wall-clock window.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BasisHistory {
+    samples: VecDeque<(i64, f64)>, // (unix ms, basis)
+    window_ms: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BasisStats {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub z_score: f64,
+    /// Share of the window (0..1) the basis spent below its current value.
+    pub percentile: f64,
+    /// Ornstein–Uhlenbeck half-life; `None` when the window shows no mean reversion.
+    pub half_life_secs: Option<f64>,
+}
+
+/// Least-squares trend of the basis against wall-clock time.
+#[derive(Debug, Clone, Copy)]
+pub struct BasisSlope {
+    pub bps_per_sec: f64,
+    pub std_err: f64,
+}
+
+impl BasisSlope {
+    /// A perfect fit has no error, so any trend it shows is as significant as it gets.
+    pub fn t_stat(&self) -> f64 {
+        if self.std_err > 0.0 { return self.bps_per_sec / self.std_err; }
+        if self.bps_per_sec == 0.0 { 0.0 } else { f64::INFINITY.copysign(self.bps_per_sec) }
+    }
+}
+
+impl BasisHistory {
+    pub fn new(window_ms: i64) -> Self {
+        Self { samples: VecDeque::new(), window_ms }
+    }
+
+    pub fn push(&mut self, ts_ms: i64, basis: f64) {
+        match self.samples.back_mut() {
+            Some(last) if last.0 / BUCKET_MS == ts_ms / BUCKET_MS => *last = (ts_ms, basis),
+            _ => self.samples.push_back((ts_ms, basis)),
+        }
+        while self.samples.front().is_some_and(|(ts, _)| *ts < ts_ms - self.window_ms) {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Basis as it stood at each grid step from the first sample up to `now_ms`.
+    fn resample(&self, now_ms: i64) -> Vec<f64> {
+        let Some(&(first_ts, _)) = self.samples.front() else { return Vec::new() };
+        let mut grid = Vec::with_capacity(((now_ms - first_ts) / GRID_MS + 1).max(0) as usize);
+        let mut idx = 0;
+        let mut t = first_ts;
+        while t <= now_ms {
+            while idx + 1 < self.samples.len() && self.samples[idx + 1].0 <= t { idx += 1; }
+            grid.push(self.samples[idx].1);
+            t += GRID_MS;
+        }
+        grid
+    }
+
+    /// Regresses basis (bps) on sample time (seconds) over the trailing `window_ms`.
+    pub fn slope(&self, now_ms: i64, window_ms: i64) -> Option<BasisSlope> {
+        let points: Vec<(f64, f64)> = self.samples.iter()
+            .filter(|(ts, _)| *ts >= now_ms - window_ms)
+            .map(|(ts, basis)| ((ts - now_ms) as f64 / 1000.0, basis * 10_000.0))
+            .collect();
+        if points.len() < MIN_SLOPE_SAMPLES { return None; }
+
+        let n = points.len() as f64;
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let sxx: f64 = points.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+        if sxx == 0.0 { return None; }
+        let sxy: f64 = points.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+
+        let bps_per_sec = sxy / sxx;
+        let intercept = mean_y - bps_per_sec * mean_x;
+        let ssr: f64 = points.iter().map(|p| (p.1 - intercept - bps_per_sec * p.0).powi(2)).sum();
+        let std_err = (ssr / (n - 2.0) / sxx).sqrt();
+        Some(BasisSlope { bps_per_sec, std_err })
+    }
+
+    pub fn stats(&self, now_ms: i64) -> Option<BasisStats> {
+        let current = self.samples.back()?.1;
+        let grid = self.resample(now_ms);
+        if grid.len() < MIN_GRID_POINTS { return None; }
+
+        let n = grid.len() as f64;
+        let mean = grid.iter().sum::<f64>() / n;
+        let std_dev = (grid.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0)).sqrt();
+        let z_score = if std_dev > 0.0 { (current - mean) / std_dev } else { 0.0 };
+        let percentile = grid.iter().filter(|v| **v < current).count() as f64 / n;
+
+        Some(BasisStats { mean, std_dev, z_score, percentile, half_life_secs: ou_half_life(&grid) })
+    }
+}
+
+/// Fits Δb = a + β·b(t-1) on the grid; half-life = -ln 2 / ln(1 + β) grid steps.
+fn ou_half_life(grid: &[f64]) -> Option<f64> {
+    let n = (grid.len() - 1) as f64;
+    let (mut sum_x, mut sum_y, mut sum_xy, mut sum_xx) = (0.0, 0.0, 0.0, 0.0);
+    for w in grid.windows(2) {
+        let (x, y) = (w[0], w[1] - w[0]);
+        sum_x += x; sum_y += y; sum_xy += x * y; sum_xx += x * x;
+    }
+    let denominator = n * sum_xx - sum_x * sum_x;
+    if denominator == 0.0 { return None; }
+    let beta = (n * sum_xy - sum_x * sum_y) / denominator;
+    if beta >= 0.0 || beta <= -1.0 { return None; }
+    Some(-(2.0_f64.ln()) / (1.0 + beta).ln() * GRID_MS as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn perfect_trends_are_infinitely_significant() {
+        let mut history = BasisHistory::new(60_000);
+        for i in 0..20 { history.push(i * 1000, 0.0001 + i as f64 * 0.00001); }
+        let rising = history.slope(19_000, 60_000).unwrap();
+        assert!((rising.bps_per_sec - 0.1).abs() < 1e-9);
+        assert!(rising.t_stat() > 1e6);
+
+        let exact = |bps_per_sec| BasisSlope { bps_per_sec, std_err: 0.0 }.t_stat();
+        assert_eq!((exact(0.1), exact(-0.1), exact(0.0)), (f64::INFINITY, f64::NEG_INFINITY, 0.0));
+    }
+
+    #[test]
+    fn z_scores_measure_the_latest_sample_against_the_grid() {
+        let mut history = BasisHistory::new(60_000);
+        // 1bp and 3bp alternating each second, then a jump to 5bp
+        for i in 0..30 { history.push(i * 1000, if i % 2 == 0 { 0.0001 } else { 0.0003 }); }
+        history.push(30_000, 0.0005);
+        let stats = history.stats(30_000).unwrap();
+
+        let grid: Vec<f64> = (0..31).map(|i| if i == 30 { 0.0005 } else if i % 2 == 0 { 0.0001 } else { 0.0003 }).collect();
+        let mean = grid.iter().sum::<f64>() / 31.0;
+        let std_dev = (grid.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / 30.0).sqrt();
+        assert!((stats.mean - mean).abs() < 1e-15);
+        assert!((stats.z_score - (0.0005 - mean) / std_dev).abs() < 1e-9);
+        assert!((stats.percentile - 30.0 / 31.0).abs() < 1e-12);
+        // Flipping sign every step overshoots the mean; the OU fit cannot express that
+        assert!(stats.half_life_secs.is_none());
+
+        // Twenty seconds of history is too short a grid for anything
+        let mut short = BasisHistory::new(60_000);
+        for i in 0..20 { short.push(i * 1000, 0.0001); }
+        assert!(short.stats(19_000).is_none());
+    }
+
+    #[test]
+    fn geometric_decay_recovers_its_half_life() {
+        let mut history = BasisHistory::new(120_000);
+        // Closes 10% of the gap to zero every second
+        for i in 0..60 { history.push(i * 1000, 0.001 * 0.9_f64.powi(i as i32)); }
+        let stats = history.stats(59_000).unwrap();
+        let expected = -(2.0_f64.ln()) / 0.9_f64.ln();
+        assert!((stats.half_life_secs.unwrap() - expected).abs() < 1e-6);
+
+        let mut flat = BasisHistory::new(120_000);
+        for i in 0..60 { flat.push(i * 1000, 0.0002); }
+        assert_eq!(flat.stats(59_000).unwrap().half_life_secs, None);
+    }
+}
@@ -0,0 +1,270 @@
+use super::accounts::AccountSnapshot;
+use super::strategy_config::StrategyConfig;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const DEFAULT_CONFIG_PATH: &str = "config/capital.json";
+/// The latest plan, for anything that wants to act on it.
+pub const CAPITAL_CHANNEL: &str = "trade:capital";
+
+/// One way to move collateral between two venues.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferRoute {
+    pub from: String,
+    pub to: String,
+    pub network: String,
+    /// Withdrawal plus any deposit or bridge fee, in USD.
+    pub fee_usd: f64,
+    /// Withdrawal processing plus confirmations, door to door.
+    pub minutes: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapitalConfig {
+    /// Leverage each venue's positions run at; unknown venues use `default_leverage`.
+    pub leverage: HashMap<String, f64>,
+    pub default_leverage: f64,
+    /// Extra collateral kept on top of what open and planned positions need, as a share of it.
+    pub buffer: f64,
+    /// Transfers smaller than this are not worth proposing.
+    pub min_transfer_usd: f64,
+    /// What an hour in transit is worth when choosing a network; a venue near liquidation always gets the fastest.
+    pub hour_cost_usd: f64,
+    pub routes: Vec<TransferRoute>,
+}
+
+impl Default for CapitalConfig {
+    /// Indicative USDT/USDC withdrawal fees and times; check the venues' current schedules.
+    fn default() -> Self {
+        let route = |from: &str, to: &str, network: &str, fee_usd, minutes| TransferRoute {
+            from: from.to_string(), to: to.to_string(), network: network.to_string(), fee_usd, minutes,
+        };
+        Self {
+            leverage: HashMap::new(),
+            default_leverage: 5.0,
+            buffer: 0.5,
+            min_transfer_usd: 500.0,
+            hour_cost_usd: 2.0,
+            routes: vec![
+                route("binance", "bybit", "TRC20", 1.0, 5),
+                route("binance", "bybit", "ARBITRUM", 0.1, 10),
+                route("bybit", "binance", "TRC20", 1.0, 5),
+                route("bybit", "binance", "ARBITRUM", 0.1, 10),
+                // Hyperliquid only takes USDC over its Arbitrum bridge and charges $1 to withdraw
+                route("binance", "hyperliquid", "ARBITRUM", 0.1, 10),
+                route("bybit", "hyperliquid", "ARBITRUM", 0.1, 10),
+                route("hyperliquid", "binance", "ARBITRUM", 1.0, 10),
+                route("hyperliquid", "bybit", "ARBITRUM", 1.0, 10),
+            ],
+        }
+    }
+}
+
+impl CapitalConfig {
+    /// Reads `CAPITAL_CONFIG` (or `config/capital.json`); missing fields keep their defaults.
+    pub fn load() -> Self {
+        let path = std::env::var("CAPITAL_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        let Ok(raw) = std::fs::read_to_string(&path) else { return Self::default() };
+        serde_json::from_str(&raw).unwrap_or_else(|e| {
+            println!("⚠️ [Capital] Ignoring {}: {}", path, e);
+            Self::default()
+        })
+    }
+
+    pub fn leverage(&self, venue: &str) -> f64 {
+        self.leverage.get(venue).copied().unwrap_or(self.default_leverage).max(1.0)
+    }
+
+    /// Cheapest route once time in transit is priced in, or the fastest when `urgent`.
+    fn route(&self, from: &str, to: &str, urgent: bool) -> Option<&TransferRoute> {
+        let cost = |r: &TransferRoute| if urgent { r.minutes as f64 } else { r.fee_usd + self.hour_cost_usd * r.minutes as f64 / 60.0 };
+        self.routes.iter()
+            .filter(|r| r.from == from && r.to == to)
+            .min_by(|a, b| cost(a).total_cmp(&cost(b)))
+    }
+}
+
+/// Collateral on one venue against what it needs.
+#[derive(Debug, Clone, Serialize)]
+pub struct VenueCapital {
+    pub venue: String,
+    pub equity: f64,
+    pub available: f64,
+    /// Margin held by open positions.
+    pub open_margin: f64,
+    /// Margin for the entries the position limits still allow here, at full clip size.
+    pub planned_margin: f64,
+    /// Open plus planned margin, with the buffer on top.
+    pub target: f64,
+    /// Equity above target that can be withdrawn now (negative: short of target).
+    pub surplus: f64,
+    /// Notional new entries may still add here without eating into the buffer.
+    pub capacity_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferProposal {
+    pub from: String,
+    pub to: String,
+    pub amount: f64,
+    pub network: String,
+    pub fee_usd: f64,
+    pub minutes: u32,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapitalPlan {
+    pub ts: DateTime<Utc>,
+    pub venues: Vec<VenueCapital>,
+    pub transfers: Vec<TransferProposal>,
+    /// Shortfalls no route or surplus could cover.
+    pub unmet: Vec<(String, f64)>,
+}
+
+impl CapitalPlan {
+    /// Notional each venue can still take, for the scanner's entry check.
+    pub fn capacity(&self) -> HashMap<String, f64> {
+        self.venues.iter().map(|v| (v.venue.clone(), v.capacity_usd)).collect()
+    }
+}
+
+/// Projects each venue's margin needs from its open legs plus the entries the strategy's limits
+/// still allow at its clip, and proposes transfers from venues above target to venues below it.
+pub fn plan(accounts: &AccountSnapshot, strategy: &StrategyConfig, config: &CapitalConfig) -> CapitalPlan {
+    let limits = &strategy.limits;
+    let readable: Vec<_> = accounts.venues.iter().filter(|v| v.error.is_none()).collect();
+    // Every pair has a leg on two venues
+    let open_pairs = readable.iter().map(|v| v.legs.len()).sum::<usize>() / 2;
+    let pairs_left = limits.max_total.saturating_sub(open_pairs);
+
+    let venues: Vec<VenueCapital> = readable.iter().map(|account| {
+        let leverage = config.leverage(&account.venue);
+        let planned = limits.max_per_exchange.saturating_sub(account.legs.len()).min(pairs_left);
+        let planned_margin = planned as f64 * strategy.entry.clip_usd / leverage;
+        let target = (account.margin_used + planned_margin) * (1.0 + config.buffer);
+        VenueCapital {
+            venue: account.venue.clone(),
+            equity: account.equity,
+            available: account.available,
+            open_margin: account.margin_used,
+            planned_margin,
+            target,
+            surplus: (account.equity - target).min(account.available),
+            capacity_usd: (account.available / (1.0 + config.buffer)).max(0.0) * leverage,
+        }
+    }).collect();
+
+    let mut transfers = Vec::new();
+    let mut unmet = Vec::new();
+    // What each donor can still give once earlier proposals are counted
+    let mut spare: Vec<f64> = venues.iter().map(|v| v.surplus).collect();
+    let mut short: Vec<usize> = (0..venues.len()).filter(|&i| venues[i].surplus < 0.0).collect();
+    short.sort_by(|&a, &b| venues[a].surplus.total_cmp(&venues[b].surplus));
+    for to in short {
+        let urgent = accounts.blocked.contains(&venues[to].venue);
+        let mut missing = -venues[to].surplus;
+        // Largest surplus first keeps the number of transfers down
+        let mut donors: Vec<usize> = (0..venues.len()).filter(|&i| spare[i] >= config.min_transfer_usd).collect();
+        donors.sort_by(|&a, &b| spare[b].total_cmp(&spare[a]));
+        for from in donors {
+            if missing < config.min_transfer_usd { break; }
+            let Some(route) = config.route(&venues[from].venue, &venues[to].venue, urgent) else { continue };
+            // Gross up so what lands covers the shortfall after fees
+            let amount = (missing + route.fee_usd).min(spare[from]);
+            if amount < config.min_transfer_usd { continue; }
+            transfers.push(TransferProposal {
+                from: venues[from].venue.clone(),
+                to: venues[to].venue.clone(),
+                amount,
+                network: route.network.clone(),
+                fee_usd: route.fee_usd,
+                minutes: route.minutes,
+                reason: if urgent { "leg near liquidation".to_string() } else { format!("{} below margin target", venues[to].venue) },
+            });
+            spare[from] -= amount;
+            missing -= amount - route.fee_usd;
+        }
+        if missing >= config.min_transfer_usd { unmet.push((venues[to].venue.clone(), missing)); }
+    }
+    CapitalPlan { ts: accounts.ts, venues, transfers, unmet }
+}
+
+// --- 🧾 REPORT ---
+
+/// `scanner-rust rebalance`: plans against the executor's latest account snapshot.
+pub async fn run_report() -> Result<(), Box<dyn std::error::Error>> {
+    let client = redis::Client::open("redis://localhost/")?;
+    let mut conn = client.get_multiplexed_async_connection().await?;
+    let accounts = AccountSnapshot::load(&mut conn, Utc::now()).await
+        .ok_or("no recent account snapshot; is the executor running?")?;
+    let plan = plan(&accounts, &StrategyConfig::load(), &CapitalConfig::load());
+
+    println!("🏦 Capital at {}", plan.ts.format("%Y-%m-%d %H:%M:%S"));
+    for v in &plan.venues {
+        println!("   {:<12} equity {:>10.2} | avail {:>10.2} | open {:>9.2} | planned {:>9.2} | target {:>10.2} | surplus {:>+10.2} | capacity {:>10.0}$",
+            v.venue, v.equity, v.available, v.open_margin, v.planned_margin, v.target, v.surplus, v.capacity_usd);
+    }
+    if plan.transfers.is_empty() { println!("✅ No transfers needed"); }
+    for t in &plan.transfers {
+        println!("💱 Move {:.2} USD {} → {} via {} (fee {:.2}, ~{}m): {}", t.amount, t.from, t.to, t.network, t.fee_usd, t.minutes, t.reason);
+    }
+    for (venue, missing) in &plan.unmet {
+        println!("⚠️ {} is still {:.2} USD short; no venue has the surplus (or no route is configured)", venue, missing);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ingestors::accounts::VenueAccount;
+
+    fn venue(venue: &str, equity: f64, margin_used: f64) -> VenueAccount {
+        VenueAccount { venue: venue.to_string(), equity, available: equity - margin_used, margin_used, ..VenueAccount::default() }
+    }
+
+    /// Bybit is 2,150 short of its target; binance has 1,600 to spare and hyperliquid `hyperliquid_equity - 900`.
+    fn accounts(hyperliquid_equity: f64, blocked: &[&str]) -> AccountSnapshot {
+        AccountSnapshot {
+            ts: Utc::now(),
+            venues: vec![venue("binance", 2_500.0, 0.0), venue("bybit", 2_500.0, 2_500.0), venue("hyperliquid", hyperliquid_equity, 0.0)],
+            blocked: blocked.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn shortfalls_draw_on_the_largest_surplus_first_and_gross_up_for_fees() {
+        let plan = plan(&accounts(2_000.0, &[]), &StrategyConfig::default(), &CapitalConfig::default());
+        // Three clips of 1,000 at 5x with the 50% buffer
+        assert!((plan.venues[0].target - 900.0).abs() < 1e-9);
+        assert!((plan.venues[1].surplus + 2_150.0).abs() < 1e-9);
+
+        let moves: Vec<(&str, &str, &str)> = plan.transfers.iter().map(|t| (t.from.as_str(), t.to.as_str(), t.network.as_str())).collect();
+        assert_eq!(moves, [("binance", "bybit", "ARBITRUM"), ("hyperliquid", "bybit", "ARBITRUM")]);
+        assert!((plan.transfers[0].amount - 1_600.0).abs() < 1e-9);
+        // What lands after both fees covers the 2,150 exactly
+        let landed: f64 = plan.transfers.iter().map(|t| t.amount - t.fee_usd).sum();
+        assert!((landed - 2_150.0).abs() < 1e-9);
+        assert!(plan.unmet.is_empty());
+    }
+
+    #[test]
+    fn uncovered_shortfalls_are_reported_as_unmet() {
+        // Hyperliquid's 100 spare is below the minimum transfer
+        let plan = plan(&accounts(1_000.0, &[]), &StrategyConfig::default(), &CapitalConfig::default());
+        assert_eq!(plan.transfers.len(), 1);
+        assert_eq!(plan.unmet.len(), 1);
+        assert_eq!(plan.unmet[0].0, "bybit");
+        assert!((plan.unmet[0].1 - 550.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn blocked_venues_get_the_fastest_route() {
+        let plan = plan(&accounts(2_000.0, &["bybit"]), &StrategyConfig::default(), &CapitalConfig::default());
+        assert_eq!(plan.transfers[0].network, "TRC20");
+        assert_eq!(plan.transfers[0].reason, "leg near liquidation");
+    }
+}
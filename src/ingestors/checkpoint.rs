@@ -0,0 +1,162 @@
+use super::basis_stats::BasisHistory;
+use super::portfolio::PairPosition;
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const REDIS_KEY: &str = "scanner:checkpoint";
+const WINDOWS_REDIS_KEY: &str = "scanner:checkpoint:windows";
+
+/// Everything the strategy needs to pick up where it left off.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub saved_at: DateTime<Utc>,
+    pub positions: Vec<PairPosition>,
+    /// Stored on its own by `save_windows`, so the save in front of every signal stays small.
+    /// Still read from checkpoints written before the split.
+    #[serde(default, skip_serializing)]
+    pub basis_windows: HashMap<String, BasisHistory>,
+    pub signal_streak: HashMap<String, u32>,
+    /// Pair ID -> unix ms at which its OBI first turned favourable.
+    pub sentinels: HashMap<String, i64>,
+}
+
+/// Where checkpoints live: `CHECKPOINT_FILE` if set, Redis otherwise.
+pub enum CheckpointStore {
+    Redis(redis::aio::MultiplexedConnection),
+    File(PathBuf),
+}
+
+impl CheckpointStore {
+    pub async fn from_env(client: &redis::Client) -> redis::RedisResult<Self> {
+        match std::env::var("CHECKPOINT_FILE") {
+            Ok(path) => Ok(Self::File(PathBuf::from(path))),
+            Err(_) => Ok(Self::Redis(client.get_multiplexed_async_connection().await?)),
+        }
+    }
+
+    /// `Ok(None)` when nothing was ever saved. A checkpoint that cannot be read or parsed is an
+    /// error: starting without it would forget every open position. Basis windows only warm up
+    /// the stats, so missing or unreadable ones just start cold.
+    pub async fn load(&mut self) -> Result<Option<Checkpoint>, Box<dyn std::error::Error>> {
+        let Some(raw) = self.read(false).await? else { return Ok(None) };
+        let mut checkpoint: Checkpoint = serde_json::from_str(&raw)?;
+        if let Some(windows) = self.read(true).await.ok().flatten().and_then(|raw| serde_json::from_str(&raw).ok()) {
+            checkpoint.basis_windows = windows;
+        }
+        Ok(Some(checkpoint))
+    }
+
+    /// Saves everything but the basis windows.
+    pub async fn save(&mut self, checkpoint: &Checkpoint) -> Result<(), Box<dyn std::error::Error>> {
+        let raw = serde_json::to_string(checkpoint)?;
+        self.write(false, raw).await
+    }
+
+    pub async fn save_windows(&mut self, windows: &HashMap<String, BasisHistory>) -> Result<(), Box<dyn std::error::Error>> {
+        let raw = serde_json::to_string(windows)?;
+        self.write(true, raw).await
+    }
+
+    fn windows_path(path: &Path) -> PathBuf {
+        path.with_extension("windows.json")
+    }
+
+    async fn read(&mut self, windows: bool) -> Result<Option<String>, Box<dyn std::error::Error>> {
+        let key = if windows { WINDOWS_REDIS_KEY } else { REDIS_KEY };
+        Ok(match self {
+            Self::Redis(conn) => conn.get(key).await?,
+            Self::File(path) => {
+                let path = if windows { Self::windows_path(path) } else { path.clone() };
+                match tokio::fs::read_to_string(path).await {
+                    Ok(raw) => Some(raw),
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                    Err(e) => return Err(e.into()),
+                }
+            }
+        })
+    }
+
+    async fn write(&mut self, windows: bool, raw: String) -> Result<(), Box<dyn std::error::Error>> {
+        let key = if windows { WINDOWS_REDIS_KEY } else { REDIS_KEY };
+        match self {
+            Self::Redis(conn) => conn.set::<_, _, ()>(key, raw).await?,
+            Self::File(path) => {
+                let path = if windows { Self::windows_path(path) } else { path.clone() };
+                // Write-then-rename so a crash mid-write never leaves a torn checkpoint
+                let tmp = path.with_extension("tmp");
+                tokio::fs::write(&tmp, raw).await?;
+                tokio::fs::rename(&tmp, path).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("checkpoint-{}-{}.json", name, uuid::Uuid::new_v4().simple()))
+    }
+
+    #[tokio::test]
+    async fn missing_checkpoint_is_a_fresh_start() {
+        let mut store = CheckpointStore::File(scratch("missing"));
+        assert!(store.load().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn unreadable_checkpoint_is_an_error() {
+        let path = scratch("torn");
+        std::fs::write(&path, r#"{"saved_at":"2026-01-01T00:00:00Z","positions":[{"pair_id":"#).unwrap();
+        let mut store = CheckpointStore::File(path.clone());
+        assert!(store.load().await.is_err());
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn saved_checkpoints_load_back() {
+        let path = scratch("roundtrip");
+        let mut store = CheckpointStore::File(path.clone());
+        let checkpoint = Checkpoint {
+            saved_at: Utc::now(),
+            positions: Vec::new(),
+            basis_windows: HashMap::new(),
+            signal_streak: HashMap::from([("BINANCE-BYBIT-BTC".to_string(), 3)]),
+            sentinels: HashMap::new(),
+        };
+        store.save(&checkpoint).await.unwrap();
+        let loaded = store.load().await.unwrap().expect("just saved");
+        assert_eq!(loaded.signal_streak.get("BINANCE-BYBIT-BTC"), Some(&3));
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[tokio::test]
+    async fn basis_windows_are_saved_apart_from_positions() {
+        let path = scratch("windows");
+        let mut store = CheckpointStore::File(path.clone());
+        let mut window = BasisHistory::new(60_000);
+        window.push(1_000, 0.001);
+        let windows = HashMap::from([("BINANCE-BYBIT-BTC".to_string(), window)]);
+        let checkpoint = Checkpoint {
+            saved_at: Utc::now(),
+            positions: Vec::new(),
+            basis_windows: windows.clone(),
+            signal_streak: HashMap::new(),
+            sentinels: HashMap::new(),
+        };
+        store.save(&checkpoint).await.unwrap();
+        assert!(!std::fs::read_to_string(&path).unwrap().contains("basis_windows"));
+        assert!(store.load().await.unwrap().unwrap().basis_windows.is_empty(), "windows have not been flushed yet");
+
+        store.save_windows(&windows).await.unwrap();
+        let loaded = store.load().await.unwrap().unwrap();
+        assert!(loaded.basis_windows.contains_key("BINANCE-BYBIT-BTC"));
+        let _ = std::fs::remove_file(CheckpointStore::windows_path(&path));
+        let _ = std::fs::remove_file(path);
+    }
+}
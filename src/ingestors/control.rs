@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+/// Operator and risk commands shared by the scanner and every executor.
+pub const CONTROL_CHANNEL: &str = "trade:control";
+/// The engaged kill switch, so a restarted process comes back halted.
+const KILL_SWITCH_KEY: &str = "trade:kill_switch";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum ControlCommand {
+    /// Stop opening anything new; `flatten` also closes whatever is open.
+    Kill { flatten: bool, reason: String, source: String },
+    Resume { source: String },
+}
+
+impl ControlCommand {
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    pub fn from_json(raw: &str) -> Option<Self> {
+        serde_json::from_str(raw).ok()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KillSwitch {
+    pub flatten: bool,
+    pub reason: String,
+    pub source: String,
+    pub since: DateTime<Utc>,
+}
+
+impl KillSwitch {
+    /// Folds a command into the current state; `None` means trading is allowed.
+    pub fn apply(current: Option<Self>, command: &ControlCommand, now: DateTime<Utc>) -> Option<Self> {
+        match command {
+            ControlCommand::Resume { .. } => None,
+            ControlCommand::Kill { flatten, reason, source } => Some(match current {
+                // A second kill can escalate to a flatten but never soften one
+                Some(engaged) => Self { flatten: engaged.flatten || *flatten, ..engaged },
+                None => Self { flatten: *flatten, reason: reason.clone(), source: source.clone(), since: now },
+            }),
+        }
+    }
+
+    pub async fn load(conn: &mut redis::aio::MultiplexedConnection) -> Option<Self> {
+        let raw: String = conn.get::<_, Option<String>>(KILL_SWITCH_KEY).await.ok()??;
+        serde_json::from_str(&raw).ok()
+    }
+
+    pub async fn save(state: Option<&Self>, conn: &mut redis::aio::MultiplexedConnection) {
+        let _: () = match state.and_then(|s| serde_json::to_string(s).ok()) {
+            Some(raw) => conn.set(KILL_SWITCH_KEY, raw).await.unwrap_or(()),
+            None => conn.del(KILL_SWITCH_KEY).await.unwrap_or(()),
+        };
+    }
+}
+
+/// Persists the resulting state and broadcasts `command` to everyone listening.
+pub async fn send(conn: &mut redis::aio::MultiplexedConnection, command: &ControlCommand, now: DateTime<Utc>) {
+    let current = KillSwitch::load(conn).await;
+    KillSwitch::save(KillSwitch::apply(current, command, now).as_ref(), conn).await;
+    let _: () = conn.publish(CONTROL_CHANNEL, command.to_json()).await.unwrap_or(());
+}
@@ -0,0 +1,139 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::time::{timeout, Duration};
+
+const DEFAULT_CONFIG_PATH: &str = "config/fees.json";
+/// Startup waits this long for live rates before going with the configured schedule.
+const FETCH_TIMEOUT_SECS: u64 = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OrderType { Maker, Taker }
+
+/// Order types used to get in and out of a pair; applied to both legs.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ExecutionPlan {
+    pub open: OrderType,
+    pub close: OrderType,
+}
+
+impl Default for ExecutionPlan {
+    // OPEN_LIMIT / CLOSE_LIMIT both rest on the book
+    fn default() -> Self {
+        Self { open: OrderType::Maker, close: OrderType::Maker }
+    }
+}
+
+/// Fee rates for one account on one venue, as fractions of notional. Negative = rebate.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeSchedule {
+    pub tier: String,
+    pub maker: f64,
+    pub taker: f64,
+    /// Share knocked off positive fees (BNB payment, HYPE staking, ...). Rebates are left alone.
+    #[serde(default)]
+    pub discount: f64,
+}
+
+impl FeeSchedule {
+    pub fn rate(&self, order: OrderType) -> f64 {
+        let raw = match order { OrderType::Maker => self.maker, OrderType::Taker => self.taker };
+        if raw > 0.0 { raw * (1.0 - self.discount) } else { raw }
+    }
+
+    /// Open + close cost for one leg.
+    pub fn round_trip(&self, plan: ExecutionPlan) -> f64 {
+        self.rate(plan.open) + self.rate(plan.close)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeeModel {
+    pub plan: ExecutionPlan,
+    pub schedules: HashMap<String, FeeSchedule>,
+}
+
+impl Default for FeeModel {
+    /// Public base-tier USDT perp rates.
+    fn default() -> Self {
+        let schedule = |maker, taker| FeeSchedule { tier: "VIP0".to_string(), maker, taker, discount: 0.0 };
+        let schedules = HashMap::from([
+            ("binance".to_string(), schedule(0.0002, 0.0005)),
+            ("bybit".to_string(), schedule(0.0002, 0.00055)),
+            ("hyperliquid".to_string(), schedule(0.00015, 0.00045)),
+        ]);
+        Self { plan: ExecutionPlan::default(), schedules }
+    }
+}
+
+impl FeeModel {
+    /// Reads `FEES_CONFIG` (or `config/fees.json`); venues missing from the file keep their defaults.
+    pub fn load() -> Self {
+        let mut model = Self::default();
+        let path = std::env::var("FEES_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        if let Ok(raw) = std::fs::read_to_string(&path) {
+            match serde_json::from_str::<FeeModel>(&raw) {
+                Ok(file) => {
+                    model.plan = file.plan;
+                    model.schedules.extend(file.schedules);
+                }
+                Err(e) => println!("⚠️ [Fees] Ignoring {}: {}", path, e),
+            }
+        }
+        model
+    }
+
+    pub fn schedule(&self, exchange: &str) -> Option<&FeeSchedule> {
+        self.schedules.get(exchange)
+    }
+
+    /// One order on `exchange`. Unknown venues are priced at 5bps whatever the order type.
+    pub fn rate(&self, exchange: &str, order: OrderType) -> f64 {
+        self.schedule(exchange).map(|s| s.rate(order)).unwrap_or(0.0005)
+    }
+
+    /// Round-trip cost for one leg on `exchange`. Unknown venues are priced as taker-only at 5bps.
+    pub fn leg_round_trip(&self, exchange: &str) -> f64 {
+        self.schedule(exchange).map(|s| s.round_trip(self.plan)).unwrap_or(0.0010)
+    }
+
+    /// Both legs in and out, as a fraction of one leg's notional.
+    pub fn pair_round_trip(&self, short_exchange: &str, long_exchange: &str) -> f64 {
+        self.leg_round_trip(short_exchange) + self.leg_round_trip(long_exchange)
+    }
+
+    /// Pulls the account's live rates where the venue exposes them without a signed request.
+    ///
+    /// Hyperliquid `userFees` already folds in volume tier, staking and referral discounts.
+    /// Binance and Bybit only serve account rates to signed requests, which the scanner never
+    /// makes, so their tiers come from the config file and must be kept current by hand.
+    pub async fn fetch_account_rates(&mut self, client: &reqwest::Client) {
+        for venue in ["binance", "bybit"] {
+            if let Some(schedule) = self.schedule(venue) {
+                println!("ℹ️ [Fees] {} uses the configured {} schedule ({:.2}/{:.2}bp)", venue, schedule.tier, schedule.maker * 1e4, schedule.taker * 1e4);
+            }
+        }
+        let Ok(user) = std::env::var("HL_ACCOUNT_ADDRESS") else { return };
+        let body = json!({"type": "userFees", "user": user});
+        let request = async {
+            let resp = client.post("https://api.hyperliquid.xyz/info").json(&body).send().await.ok()?;
+            resp.json::<Value>().await.ok()
+        };
+        let v = match timeout(Duration::from_secs(FETCH_TIMEOUT_SECS), request).await {
+            Ok(Some(v)) => v,
+            Ok(None) => return,
+            Err(_) => {
+                println!("⚠️ [Fees] Hyperliquid userFees timed out after {}s, keeping the configured schedule", FETCH_TIMEOUT_SECS);
+                return;
+            }
+        };
+
+        let parse = |key: &str| v[key].as_str().and_then(|s| s.parse::<f64>().ok());
+        if let (Some(maker), Some(taker)) = (parse("userAddRate"), parse("userCrossRate")) {
+            self.schedules.insert(
+                "hyperliquid".to_string(),
+                FeeSchedule { tier: "account".to_string(), maker, taker, discount: 0.0 },
+            );
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use super::market_store::MarketStore;
+use chrono::{DateTime, Duration, Utc};
+use redis::AsyncCommands;
+
+/// One sorted set per venue and coin: score is unix ms, member is `ms:rate`.
+const KEY_PREFIX: &str = "funding:rates";
+const RETENTION_DAYS: i64 = 7;
+/// A sample older than this says nothing about the rate at payout; the scanner was probably down.
+const MAX_SAMPLE_AGE_MINS: i64 = 15;
+
+fn key(exchange: &str, coin: &str) -> String {
+    format!("{}:{}:{}", KEY_PREFIX, exchange, coin)
+}
+
+/// Stores the funding rate currently quoted for every book in `store`, so payouts can later be
+/// checked against what the scanner was pricing at the time. Every sample and trim goes out in
+/// one pipelined round trip.
+pub async fn record(conn: &mut redis::aio::MultiplexedConnection, store: &MarketStore, now: DateTime<Utc>) {
+    let ms = now.timestamp_millis();
+    let cutoff = (now - Duration::days(RETENTION_DAYS)).timestamp_millis();
+    let mut pipe = redis::pipe();
+    for exchange in 0..store.exchange_count() {
+        for symbol in 0..store.symbol_count() {
+            let Some(state) = store.snapshot(exchange, symbol) else { continue };
+            let key = key(store.exchange_name(exchange), &store.symbol_name(symbol));
+            pipe.zadd(&key, format!("{}:{}", ms, state.funding), ms).ignore();
+            pipe.zrembyscore(&key, "-inf", cutoff).ignore();
+        }
+    }
+    let _: () = pipe.query_async(conn).await.unwrap_or(());
+}
+
+/// The last rate recorded for `coin` on `exchange` at or before `ts`.
+pub async fn rate_at(conn: &mut redis::aio::MultiplexedConnection, exchange: &str, coin: &str, ts: DateTime<Utc>) -> Option<f64> {
+    let ms = ts.timestamp_millis();
+    let min = (ts - Duration::minutes(MAX_SAMPLE_AGE_MINS)).timestamp_millis();
+    let found: Vec<String> = conn.zrevrangebyscore_limit(key(exchange, coin), ms, min, 0, 1).await.ok()?;
+    found.first()?.split_once(':')?.1.parse().ok()
+}
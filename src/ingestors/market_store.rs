@@ -0,0 +1,163 @@
+use arc_swap::ArcSwapOption;
+use futures::StreamExt;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::mpsc;
+
+pub type ExchangeId = usize;
+pub type SymbolId = usize;
+
+#[derive(Clone, Debug, Default)]
+pub struct MarketState {
+    pub bids: Vec<(f64, f64)>, // (price, quantity)
+    pub asks: Vec<(f64, f64)>,
+    pub funding: f64,
+}
+
+/// Symbol names <-> dense IDs. Only written the first time a symbol is seen.
+#[derive(Default)]
+struct SymbolInterner {
+    ids: HashMap<String, SymbolId>,
+    names: Vec<Arc<str>>,
+}
+
+/// Latest book per (exchange, symbol), addressed by interned IDs.
+///
+/// Every slot is its own atomic pointer: a writer swaps in a fresh `Arc<MarketState>`
+/// and readers load whatever snapshot is current, so neither side ever waits on the other
+/// and a reader only pays for the entries it actually touches.
+pub struct MarketStore {
+    exchanges: Vec<String>,
+    symbols: RwLock<SymbolInterner>,
+    max_symbols: usize,
+    slots: Box<[ArcSwapOption<MarketState>]>,
+}
+
+impl MarketStore {
+    pub fn new(exchanges: &[&str], max_symbols: usize) -> Self {
+        let slots = (0..exchanges.len() * max_symbols).map(|_| ArcSwapOption::empty()).collect();
+        Self {
+            exchanges: exchanges.iter().map(|e| e.to_string()).collect(),
+            symbols: RwLock::new(SymbolInterner::default()),
+            max_symbols,
+            slots,
+        }
+    }
+
+    pub fn exchange_count(&self) -> usize {
+        self.exchanges.len()
+    }
+
+    pub fn exchange_id(&self, name: &str) -> Option<ExchangeId> {
+        self.exchanges.iter().position(|e| e == name)
+    }
+
+    pub fn exchange_name(&self, id: ExchangeId) -> &str {
+        &self.exchanges[id]
+    }
+
+    pub fn symbol_id(&self, name: &str) -> Option<SymbolId> {
+        self.symbols.read().unwrap().ids.get(name).copied()
+    }
+
+    /// Returns the ID for `name`, registering it on first sight. `None` once the store is full.
+    pub fn intern_symbol(&self, name: &str) -> Option<SymbolId> {
+        if let Some(id) = self.symbol_id(name) { return Some(id); }
+        let mut lock = self.symbols.write().unwrap();
+        if let Some(id) = lock.ids.get(name) { return Some(*id); }
+        if lock.names.len() >= self.max_symbols { return None; }
+        let id = lock.names.len();
+        lock.names.push(Arc::from(name));
+        lock.ids.insert(name.to_string(), id);
+        Some(id)
+    }
+
+    /// Symbols registered so far; every ID below this is valid.
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.read().unwrap().names.len()
+    }
+
+    pub fn symbol_name(&self, id: SymbolId) -> Arc<str> {
+        Arc::clone(&self.symbols.read().unwrap().names[id])
+    }
+
+    /// Atomically replaces the book for one venue/symbol.
+    pub fn publish(&self, exchange: ExchangeId, symbol: SymbolId, state: MarketState) {
+        self.slots[self.slot(exchange, symbol)].store(Some(Arc::new(state)));
+    }
+
+    /// Current snapshot for one venue/symbol; stays valid even if a newer one is published.
+    pub fn snapshot(&self, exchange: ExchangeId, symbol: SymbolId) -> Option<Arc<MarketState>> {
+        self.slots[self.slot(exchange, symbol)].load_full()
+    }
+
+    fn slot(&self, exchange: ExchangeId, symbol: SymbolId) -> usize {
+        exchange * self.max_symbols + symbol
+    }
+}
+
+// --- 📡 REDIS FEED ---
+
+/// Parses one `market:data` message (`exchange:symbol:bids|asks:funding`).
+pub fn parse_market_payload(payload: &str) -> Option<(&str, &str, MarketState)> {
+    let parts: Vec<&str> = payload.split(':').collect();
+    if parts.len() < 4 { return None; }
+    let depth_parts: Vec<&str> = parts[2].split('|').collect();
+    if depth_parts.len() != 2 { return None; }
+    let parse_depth = |s: &str| -> Vec<(f64, f64)> {
+        s.split(',').map(|v| v.trim().replace('\"', ""))
+            .collect::<Vec<String>>().chunks(2).filter(|c| c.len() == 2)
+            .map(|c| (c[0].parse().unwrap_or(0.0), c[1].parse().unwrap_or(0.0)))
+            .collect()
+    };
+    let state = MarketState {
+        bids: parse_depth(depth_parts[0]),
+        asks: parse_depth(depth_parts[1]),
+        funding: parts[3].parse().unwrap_or(0.0),
+    };
+    Some((parts[0], parts[1], state))
+}
+
+/// Subscribes to `market:data` and keeps `store` current. Every stored update is announced
+/// on the returned channel so consumers only re-evaluate what actually changed.
+pub fn spawn_redis_feed(store: Arc<MarketStore>) -> mpsc::UnboundedReceiver<(ExchangeId, SymbolId)> {
+    let (update_tx, update_rx) = mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        let mut pubsub_conn = redis::Client::open("redis://localhost/").unwrap()
+            .get_async_pubsub().await.unwrap();
+        pubsub_conn.subscribe("market:data").await.unwrap();
+        let mut stream = pubsub_conn.into_on_message();
+
+        while let Some(msg) = stream.next().await {
+            let payload: String = msg.get_payload().unwrap();
+            let Some((exchange, symbol, state)) = parse_market_payload(&payload) else { continue };
+            let (Some(ex_id), Some(sym_id)) = (store.exchange_id(exchange), store.intern_symbol(symbol)) else { continue };
+            store.publish(ex_id, sym_id, state);
+            if update_tx.send((ex_id, sym_id)).is_err() { break; }
+        }
+    });
+    update_rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning_stops_at_the_symbol_cap() {
+        let store = MarketStore::new(&["binance", "bybit"], 2);
+        assert_eq!(store.intern_symbol("BTCUSDT"), Some(0));
+        assert_eq!(store.intern_symbol("ETHUSDT"), Some(1));
+        assert_eq!(store.intern_symbol("SOLUSDT"), None);
+        // Known symbols still resolve once the store is full
+        assert_eq!(store.intern_symbol("BTCUSDT"), Some(0));
+        assert_eq!((store.symbol_count(), store.symbol_id("SOLUSDT")), (2, None));
+
+        // The last symbol of one venue does not share a slot with the next venue's first
+        store.publish(0, 1, MarketState { funding: 0.0001, ..MarketState::default() });
+        store.publish(1, 0, MarketState { funding: 0.0002, ..MarketState::default() });
+        assert_eq!(store.snapshot(0, 1).unwrap().funding, 0.0001);
+        assert_eq!(store.snapshot(1, 0).unwrap().funding, 0.0002);
+        assert!(store.snapshot(1, 1).is_none());
+    }
+}
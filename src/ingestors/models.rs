@@ -1,10 +1,10 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MarketState {
-    pub exchange: String,
-    pub symbol: String,
-    pub bids: Vec<(f64, f64)>, // (price, quantity)
-    pub asks: Vec<(f64, f64)>,
-    pub funding: f64,
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketState {
+    pub exchange: String,
+    pub symbol: String,
+    pub bids: Vec<(f64, f64)>, // (price, quantity)
+    pub asks: Vec<(f64, f64)>,
+    pub funding: f64,
 }
\ No newline at end of file
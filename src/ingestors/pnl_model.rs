@@ -0,0 +1,111 @@
+use chrono::{DateTime, Timelike, Utc};
+
+// --- ⏰ FUNDING SCHEDULES ---
+
+/// Hours between funding payouts on a venue.
+pub fn funding_interval_hours(exchange: &str, funding_rate: f64) -> u32 {
+    if exchange == "hyperliquid" || (exchange == "binance" && funding_rate.abs() >= 0.03) { 1 } else { 8 }
+}
+
+pub fn seconds_to_payout(exchange: &str, funding_rate: f64, now: DateTime<Utc>) -> i64 {
+    let window_size = funding_interval_hours(exchange, funding_rate);
+    let hour = now.hour();
+    let next_payout_hour = ((hour / window_size) + 1) * window_size;
+    let mut next_window = now.with_hour(next_payout_hour % 24).unwrap()
+        .with_minute(0).unwrap().with_second(0).unwrap().with_nanosecond(0).unwrap();
+    if next_payout_hour >= 24 { next_window += chrono::Duration::days(1); }
+    (next_window - now).num_seconds()
+}
+
+/// Number of payouts a position opened at `now` collects before `now + horizon_secs`.
+pub fn payouts_within(exchange: &str, funding_rate: f64, now: DateTime<Utc>, horizon_secs: i64) -> i64 {
+    let first = seconds_to_payout(exchange, funding_rate, now);
+    if first > horizon_secs { return 0; }
+    let interval = funding_interval_hours(exchange, funding_rate) as i64 * 3600;
+    1 + (horizon_secs - first) / interval
+}
+
+// --- 💰 EXPECTED RETURN ---
+
+/// One side of a pair trade, priced at the VWAP we expect to trade at.
+#[derive(Debug, Clone, Copy)]
+pub struct Leg<'a> {
+    pub exchange: &'a str,
+    pub price: f64,
+    pub funding: f64,
+}
+
+/// Expected return of a pair position, as a fraction of notional, split by source.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExpectedPnl {
+    pub entry_basis: f64,
+    pub convergence: f64,
+    pub funding: f64,
+    pub fees: f64,
+    pub total: f64,
+}
+
+pub struct PnlModel {
+    /// Share of the entry basis we expect to capture before exiting.
+    pub convergence_ratio: f64,
+    /// Planned holding time; funding is accrued for every payout inside it.
+    pub hold_horizon_secs: i64,
+    /// Open + close on both legs.
+    pub round_trip_fees: f64,
+}
+
+impl PnlModel {
+    /// Short `short` at its bid and long `long` at its ask, hold for the horizon, then unwind.
+    pub fn expected(&self, short: Leg, long: Leg, now: DateTime<Utc>) -> ExpectedPnl {
+        if long.price <= 0.0 || short.price <= 0.0 { return ExpectedPnl::default(); }
+        let entry_basis = (short.price - long.price) / long.price;
+        let convergence = entry_basis * self.convergence_ratio;
+
+        // Shorts receive positive funding, longs pay it; each venue on its own clock
+        let short_payouts = payouts_within(short.exchange, short.funding, now, self.hold_horizon_secs) as f64;
+        let long_payouts = payouts_within(long.exchange, long.funding, now, self.hold_horizon_secs) as f64;
+        let funding = short.funding * short_payouts - long.funding * long_payouts;
+
+        let fees = self.round_trip_fees;
+        ExpectedPnl { entry_basis, convergence, funding, fees, total: convergence + funding - fees }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn half_past_seven() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 2, 7, 30, 0).unwrap()
+    }
+
+    #[test]
+    fn payouts_are_counted_on_each_venue_clock() {
+        let now = half_past_seven();
+        // 08:00 is the first payout on an 8h venue, 16:00 the next
+        assert_eq!(payouts_within("bybit", 0.0001, now, 8 * 3600), 1);
+        assert_eq!(payouts_within("bybit", 0.0001, now, 9 * 3600), 2);
+        assert_eq!(payouts_within("bybit", 0.0001, now, 1_000), 0);
+        // Hyperliquid pays hourly, and so does binance once the rate reaches 0.03
+        assert_eq!(payouts_within("hyperliquid", 0.0001, now, 8 * 3600), 8);
+        assert_eq!(payouts_within("binance", 0.05, now, 8 * 3600), 8);
+    }
+
+    #[test]
+    fn expected_pnl_nets_funding_on_both_legs_against_fees() {
+        let model = PnlModel { convergence_ratio: 0.8, hold_horizon_secs: 8 * 3600, round_trip_fees: 0.001 };
+        let short = Leg { exchange: "bybit", price: 101.0, funding: 0.0001 };
+        let long = Leg { exchange: "hyperliquid", price: 100.0, funding: 0.00002 };
+        let pnl = model.expected(short, long, half_past_seven());
+
+        assert!((pnl.entry_basis - 0.01).abs() < 1e-12);
+        assert!((pnl.convergence - 0.008).abs() < 1e-12);
+        // One 8h payout received on the short, eight hourly ones paid on the long
+        assert!((pnl.funding - (0.0001 - 8.0 * 0.00002)).abs() < 1e-12);
+        assert!((pnl.total - (0.008 - 0.00006 - 0.001)).abs() < 1e-12);
+
+        let unpriced = model.expected(Leg { price: 0.0, ..short }, long, half_past_seven());
+        assert_eq!(unpriced.total, 0.0);
+    }
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use uuid::Uuid;
+
+/// When an open pair gets closed. Basis figures are fractions, like the scanner's basis.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExitRules {
+    /// Close once this share of the entry basis has converged.
+    pub take_profit_ratio: f64,
+    /// Close if the basis widens this far past entry.
+    pub stop_loss: f64,
+    /// Bank any gain when a payout is closer than this.
+    pub pre_payout_secs: i64,
+    /// Bank any gain once the basis z-score has reverted to this level. Off when unset.
+    pub exit_zscore: Option<f64>,
+}
+
+impl Default for ExitRules {
+    fn default() -> Self {
+        Self { take_profit_ratio: 0.80, stop_loss: 0.0010, pre_payout_secs: 30, exit_zscore: None }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExitReason { Normalized, StopLoss, Reverted, PrePayout }
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PositionLimits {
+    pub max_total: usize,
+    pub max_per_symbol: usize,
+    /// Counts every position with a leg on the venue.
+    pub max_per_exchange: usize,
+}
+
+impl Default for PositionLimits {
+    fn default() -> Self {
+        Self { max_total: 5, max_per_symbol: 1, max_per_exchange: 3 }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LimitBreach { Total, Symbol, Exchange, AlreadyOpen, Liquidation, Capital }
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairPosition {
+    pub pair_id: String,
+    pub symbol: String,
+    pub short_exchange: String,
+    pub long_exchange: String,
+    pub entry_basis: f64,
+    pub size_usd: f64,
+    /// Base quantity held on each leg.
+    #[serde(default)]
+    pub qty: f64,
+    /// The `OPEN_LIMIT` signal that created this position; closes reference it.
+    #[serde(default)]
+    pub open_signal_id: Uuid,
+    pub opened_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub exit: ExitRules,
+}
+
+impl PairPosition {
+    pub fn has_leg_on(&self, exchange: &str) -> bool {
+        self.short_exchange == exchange || self.long_exchange == exchange
+    }
+
+    pub fn exit_reason(&self, current_basis: f64, z_score: Option<f64>, ttl: i64) -> Option<ExitReason> {
+        let unrealized_gain = self.entry_basis - current_basis;
+        if self.entry_basis != 0.0 && unrealized_gain / self.entry_basis > self.exit.take_profit_ratio {
+            Some(ExitReason::Normalized)
+        } else if current_basis > self.entry_basis + self.exit.stop_loss {
+            Some(ExitReason::StopLoss)
+        } else if unrealized_gain > 0.0 && z_score.zip(self.exit.exit_zscore).is_some_and(|(z, exit)| z <= exit) {
+            Some(ExitReason::Reverted)
+        } else if unrealized_gain > 0.0 && ttl < self.exit.pre_payout_secs {
+            Some(ExitReason::PrePayout)
+        } else {
+            None
+        }
+    }
+}
+
+/// Every open pair position, keyed by pair ID (`SHORT-LONG-SYMBOL`).
+pub struct PositionBook {
+    positions: HashMap<String, PairPosition>,
+    limits: PositionLimits,
+    /// Venues holding a leg too close to liquidation, as last reported by the executor.
+    blocked_venues: HashSet<String>,
+    /// Notional each venue can still take, from the capital plan. Venues missing here are unconstrained.
+    capacity: HashMap<String, f64>,
+}
+
+impl PositionBook {
+    pub fn new(limits: PositionLimits) -> Self {
+        Self { positions: HashMap::new(), limits, blocked_venues: HashSet::new(), capacity: HashMap::new() }
+    }
+
+    /// Replaces the per-venue notional new entries may use.
+    pub fn set_capacity(&mut self, capacity: HashMap<String, f64>) {
+        self.capacity = capacity;
+    }
+
+    /// Replaces the set of venues nothing new may be opened on.
+    pub fn block_venues(&mut self, venues: &[String]) {
+        self.blocked_venues = venues.iter().cloned().collect();
+    }
+
+    pub fn get(&self, pair_id: &str) -> Option<&PairPosition> {
+        self.positions.get(pair_id)
+    }
+
+    pub fn get_mut(&mut self, pair_id: &str) -> Option<&mut PairPosition> {
+        self.positions.get_mut(pair_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &PairPosition> {
+        self.positions.values()
+    }
+
+    /// Checks the configured limits for a new `symbol` position of `size_usd` per leg shorting `short_ex` and longing `long_ex`.
+    pub fn can_open(&self, symbol: &str, short_ex: &str, long_ex: &str, size_usd: f64) -> Result<(), LimitBreach> {
+        if self.positions.values().any(|p| p.symbol == symbol && p.has_leg_on(short_ex) && p.has_leg_on(long_ex)) {
+            return Err(LimitBreach::AlreadyOpen);
+        }
+        if self.blocked_venues.contains(short_ex) || self.blocked_venues.contains(long_ex) { return Err(LimitBreach::Liquidation); }
+        if [short_ex, long_ex].iter().any(|ex| self.capacity.get(*ex).is_some_and(|cap| *cap < size_usd)) {
+            return Err(LimitBreach::Capital);
+        }
+        if self.positions.len() >= self.limits.max_total { return Err(LimitBreach::Total); }
+        if self.positions.values().filter(|p| p.symbol == symbol).count() >= self.limits.max_per_symbol {
+            return Err(LimitBreach::Symbol);
+        }
+        for ex in [short_ex, long_ex] {
+            if self.positions.values().filter(|p| p.has_leg_on(ex)).count() >= self.limits.max_per_exchange {
+                return Err(LimitBreach::Exchange);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn open(&mut self, position: PairPosition) {
+        // Spend the capacity now rather than waiting for the next balance poll to show it
+        for ex in [&position.short_exchange, &position.long_exchange] {
+            if let Some(cap) = self.capacity.get_mut(ex) { *cap = (*cap - position.size_usd).max(0.0); }
+        }
+        self.positions.insert(position.pair_id.clone(), position);
+    }
+
+    pub fn close(&mut self, pair_id: &str) -> Option<PairPosition> {
+        self.positions.remove(pair_id)
+    }
+}
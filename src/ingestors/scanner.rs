@@ -1,289 +1,423 @@
-use futures::StreamExt;
-use std::collections::{HashMap, VecDeque};
-use std::io::{stdout, Write};
-use std::sync::Arc;
-use tokio::sync::RwLock;
-use tokio::time::{interval, Duration, Instant};
-use chrono::{Utc, Timelike};
-use redis::AsyncCommands;
-use crossterm::{
-    cursor,
-    event::{self, Event, KeyCode, KeyModifiers},
-    execute,
-    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
-    style::{Color, Print, ResetColor, SetForegroundColor},
-    QueueableCommand,
-};
-
-// --- 📊 TYPES & ENUMS ---
-
-#[derive(Debug, PartialEq, Clone, Copy)]
-enum TradeTier { Noise, Acceptable, GreatEntry, Sniper }
-
-fn get_trade_tier(net_profit: f64) -> TradeTier {
-    if net_profit < 0.0002 { TradeTier::Noise }
-    else if net_profit < 0.0005 { TradeTier::Acceptable }
-    else if net_profit < 0.0015 { TradeTier::GreatEntry }
-    else { TradeTier::Sniper }
-}
-
-#[derive(Clone)]
-struct MarketState {
-    bids: Vec<(f64, f64)>,
-    asks: Vec<(f64, f64)>,
-    funding: f64,
-}
-
-/// NEW: The Sentinel ensures the OBI "Settles" before we trade.
-struct TradeSentinel {
-    first_favorable_obi: Option<Instant>,
-    cooldown: Duration,
-}
-
-impl TradeSentinel {
-    fn new() -> Self {
-        Self {
-            first_favorable_obi: None,
-            cooldown: Duration::from_millis(500),
-        }
-    }
-
-    fn check_obi_stability(&mut self, is_favorable: bool) -> bool {
-        if !is_favorable {
-            self.first_favorable_obi = None; // Reset timer if OBI dips
-            return false;
-        }
-        match self.first_favorable_obi {
-            Some(start) => start.elapsed() >= self.cooldown,
-            None => {
-                self.first_favorable_obi = Some(Instant::now());
-                false
-            }
-        }
-    }
-}
-
-// --- 🧠 MATH & LIQUIDITY ENGINE ---
-
-fn calculate_weighted_obi(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> f64 {
-    let mut bid_vol = 0.0;
-    let mut ask_vol = 0.0;
-    for i in 0..5 {
-        let weight = (5 - i) as f64;
-        if let Some(level) = bids.get(i) { bid_vol += level.1 * weight; }
-        if let Some(level) = asks.get(i) { ask_vol += level.1 * weight; }
-    }
-    if bid_vol + ask_vol == 0.0 { return 0.0; }
-    (bid_vol - ask_vol) / (bid_vol + ask_vol)
-}
-
-fn calculate_vwap(levels: &[(f64, f64)], target_usd: f64) -> f64 {
-    let mut current_usd = 0.0;
-    let mut current_qty = 0.0;
-    if levels.is_empty() { return 0.0; }
-    for (price, qty) in levels {
-        let level_value = price * qty;
-        if current_usd + level_value >= target_usd {
-            let needed_usd = target_usd - current_usd;
-            current_qty += needed_usd / price;
-            return target_usd / current_qty;
-        }
-        current_usd += level_value;
-        current_qty += qty;
-    }
-    levels.last().map(|l| l.0).unwrap_or(0.0)
-}
-
-fn calculate_regression_slope(data: &VecDeque<f64>) -> f64 {
-    let n = data.len() as f64;
-    if n < 10.0 { return 0.0; } 
-    let (mut sum_x, mut sum_y, mut sum_xy, mut sum_xx) = (0.0, 0.0, 0.0, 0.0);
-    for (i, &y) in data.iter().enumerate() {
-        let x = i as f64;
-        sum_x += x; sum_y += y; sum_xy += x * y; sum_xx += x * x;
-    }
-    let denominator = n * sum_xx - sum_x * sum_x;
-    if denominator == 0.0 { return 0.0; }
-    (n * sum_xy - sum_x * sum_y) / denominator
-}
-
-fn get_seconds_to_payout(exchange: &str, funding_rate: f64) -> i64 {
-    let now = Utc::now();
-    let window_size = if exchange == "hyperliquid" { 1 } 
-                      else if exchange == "binance" && funding_rate.abs() >= 0.03 { 1 } 
-                      else { 8 };
-    let hour = now.hour();
-    let next_payout_hour = ((hour / window_size) + 1) * window_size;
-    let mut next_window = now.with_hour(next_payout_hour % 24).unwrap()
-        .with_minute(0).unwrap().with_second(0).unwrap();
-    if next_payout_hour >= 24 { next_window = next_window + chrono::Duration::days(1); }
-    (next_window - now).num_seconds()
-}
-
-// --- 🚀 MAIN RUNNER ---
-
-pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
-    let base_fees = 0.00105; 
-    let trade_size_usd = 1000.0;
-    let client = redis::Client::open("redis://localhost/")?;
-    let market_data = Arc::new(RwLock::new(HashMap::<String, MarketState>::new()));
-    let m_clone = Arc::clone(&market_data);
-
-    let mut out = stdout();
-    terminal::enable_raw_mode()?; 
-    execute!(out, EnterAlternateScreen, cursor::Hide, Clear(ClearType::All))?;
-
-    tokio::spawn(async move {
-        let mut pubsub_conn = redis::Client::open("redis://localhost/").unwrap()
-            .get_async_pubsub().await.unwrap();
-        pubsub_conn.subscribe("market:data").await.unwrap();
-        let mut stream = pubsub_conn.into_on_message();
-
-        while let Some(msg) = stream.next().await {
-            let payload: String = msg.get_payload().unwrap();
-            let parts: Vec<&str> = payload.split(':').collect();
-            if parts.len() >= 4 {
-                let (exchange, symbol) = (parts[0], parts[1]);
-                let depth_parts: Vec<&str> = parts[2].split('|').collect();
-                let parse_depth = |s: &str| -> Vec<(f64, f64)> {
-                    s.split(',').map(|v| v.trim().replace('\"', ""))
-                        .collect::<Vec<String>>().chunks(2).filter(|c| c.len() == 2)
-                        .map(|c| (c[0].parse().unwrap_or(0.0), c[1].parse().unwrap_or(0.0)))
-                        .collect()
-                };
-                if depth_parts.len() == 2 {
-                    let state = MarketState {
-                        bids: parse_depth(depth_parts[0]),
-                        asks: parse_depth(depth_parts[1]),
-                        funding: parts[3].parse().unwrap_or(0.0),
-                    };
-                    m_clone.write().await.insert(format!("{}_{}", exchange, symbol), state);
-                }
-            }
-        }
-    });
-
-    let mut signal_conn = client.get_multiplexed_async_connection().await?;
-    let mut is_in_position = false;
-    let mut active_pair_id = String::new();
-    let mut entry_basis = 0.0;
-    let mut basis_histories: HashMap<String, VecDeque<f64>> = HashMap::new();
-    let mut signal_streak: HashMap<String, u32> = HashMap::new();
-    let mut sentinels: HashMap<String, TradeSentinel> = HashMap::new();
-
-    let mut render_timer = interval(Duration::from_millis(200));
-
-    loop {
-        if event::poll(Duration::from_millis(0))? {
-            if let Event::Key(key) = event::read()? {
-                if key.code == KeyCode::Char('q') || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)) {
-                    break; 
-                }
-            }
-        }
-
-        render_timer.tick().await;
-        let now = Utc::now();
-        let m_snap = market_data.read().await.clone();
-        out.queue(cursor::MoveTo(0, 0))?;
-
-        out.queue(SetForegroundColor(Color::Cyan))?;
-        out.queue(Print(format!("═══ 🕒 {:02}:{:02}:{:02} | TIERED NORMALIZATION | OBI FILTERED ═══\r\n", 
-            now.hour(), now.minute(), now.second())))?;
-        out.queue(ResetColor)?;
-        out.queue(Print(format!("{:<20} | {:>7} | {:>7} | {:>5} | {:>5} | OBI\r\n", "PAIR", "BASIS", "NET", "FUND", "NEXT")))?;
-        out.queue(Print(format!("{}\r\n", "─".repeat(75))))?;
-
-        let symbols = vec!["BTC", "ETH", "SOL", "TIA", "ARB"]; 
-        let exchanges = vec!["binance", "hyperliquid", "bybit"];
-
-        for symbol in &symbols {
-            for i in 0..exchanges.len() {
-                for j in (i + 1)..exchanges.len() {
-                    let (ex_a, ex_b) = (exchanges[i], exchanges[j]);
-                    let (k_a, k_b) = (format!("{}_{}", ex_a, symbol), format!("{}_{}", ex_b, symbol));
-
-                    if let (Some(state_a), Some(state_b)) = (m_snap.get(&k_a), m_snap.get(&k_b)) {
-                        let a_bid = calculate_vwap(&state_a.bids, trade_size_usd);
-                        let a_ask = calculate_vwap(&state_a.asks, trade_size_usd);
-                        let b_bid = calculate_vwap(&state_b.bids, trade_size_usd);
-                        let b_ask = calculate_vwap(&state_b.asks, trade_size_usd);
-
-                        let (v_short, v_long, current_basis, fund_diff) = if a_bid > b_ask {
-                            (ex_a, ex_b, (a_bid - b_ask) / b_ask, state_a.funding - state_b.funding)
-                        } else {
-                            (ex_b, ex_a, (b_bid - a_ask) / a_ask, state_b.funding - state_a.funding)
-                        };
-
-                        let net_profit = current_basis - base_fees;
-                        let tier = get_trade_tier(net_profit);
-                        let pair_id = format!("{}-{}-{}", v_short, v_long, symbol).to_uppercase();
-                        
-                        let short_obi = calculate_weighted_obi(if v_short == ex_a { &state_a.bids } else { &state_b.bids }, if v_short == ex_a { &state_a.asks } else { &state_b.asks });
-                        let long_obi = calculate_weighted_obi(if v_long == ex_a { &state_a.bids } else { &state_b.bids }, if v_long == ex_a { &state_a.asks } else { &state_b.asks });
-
-                        let ttl = get_seconds_to_payout(ex_a, state_a.funding).min(get_seconds_to_payout(ex_b, state_b.funding));
-
-                        // Track History & Slope
-                        let history = basis_histories.entry(pair_id.clone()).or_insert_with(|| VecDeque::with_capacity(120));
-                        history.push_back(current_basis);
-                        if history.len() > 120 { history.pop_front(); }
-                        let slope = calculate_regression_slope(history);
-                        let streak = signal_streak.entry(pair_id.clone()).or_insert(0);
-                        if tier != TradeTier::Noise && slope > 0.0 { *streak += 1; } else { *streak = 0; }
-
-                        // Sentinel Check (Stability)
-                        let sentinel = sentinels.entry(pair_id.clone()).or_insert_with(TradeSentinel::new);
-                        let is_favorable = short_obi < 0.6 && long_obi > -0.6;
-                        let obi_stable = sentinel.check_obi_stability(is_favorable);
-
-                        // Rendering Logic (Same as yours)
-                        let tier_color = match tier {
-                            TradeTier::Sniper => Color::Magenta,
-                            TradeTier::GreatEntry => Color::Green,
-                            TradeTier::Acceptable => Color::Yellow,
-                            _ => Color::DarkGrey,
-                        };
-
-                        out.queue(SetForegroundColor(tier_color))?;
-                        out.queue(Print(format!("{:<20}", pair_id)))?;
-                        out.queue(ResetColor)?;
-                        out.queue(Print(format!(" | {:>6.3}% | {:>6.3}% | {:>4.2}% | {:>4}m | {:+.2} {}\r\n", 
-                            current_basis * 100.0, net_profit * 100.0, fund_diff * 100.0, ttl / 60, short_obi, if obi_stable {"STABLE"} else {"..."})))?;
-
-                        // --- 🧠 STRATEGY ENGINE ---
-                        if !is_in_position && tier != TradeTier::Noise {
-                            let is_sniper = tier == TradeTier::Sniper || (ttl < 600 && net_profit > 0.0003);
-
-                            if (tier == TradeTier::GreatEntry || is_sniper) && *streak >= 5 && obi_stable {
-                                let cmd = format!("CMD:OPEN_LIMIT|SYM:{}|S:{}|L:{}|B:{}|T:{:?}", symbol, v_short, v_long, current_basis, tier);
-                                let _: () = signal_conn.publish("trade:signals", cmd).await.unwrap_or(());
-                                is_in_position = true;
-                                active_pair_id = pair_id.clone();
-                                entry_basis = current_basis;
-                            }
-                        } else if active_pair_id == pair_id {
-                            let unrealized_gain = entry_basis - current_basis;
-                            let normalized = if entry_basis != 0.0 { unrealized_gain / entry_basis > 0.80 } else { false };
-                            let stop_loss = current_basis > (entry_basis + 0.0010);
-
-                            if normalized || stop_loss || (ttl < 30 && unrealized_gain > 0.0) {
-                                let cmd = format!("CMD:CLOSE_LIMIT|SYM:{}|S:{}|L:{}", symbol, v_short, v_long);
-                                let _: () = signal_conn.publish("trade:signals", cmd).await.unwrap_or(());
-                                is_in_position = false;
-                                active_pair_id = String::new();
-                            }
-                        }
-                    }
-                }
-            }
-        }
-        out.queue(Print(format!("\r\n═══ 📜 MONITOR: {} ═══\r\n", if active_pair_id.is_empty() { "SCANNING..." } else { &active_pair_id })))?;
-        out.queue(Clear(ClearType::FromCursorDown))?;
-        out.flush()?;
-    }
-
-    terminal::disable_raw_mode()?;
-    execute!(out, cursor::Show, LeaveAlternateScreen)?;
-    Ok(())
-}
\ No newline at end of file
+use std::collections::{HashMap, HashSet};
+use std::io::{stdout, Stdout, Write};
+use std::sync::Arc;
+use tokio::time::{interval, Duration, MissedTickBehavior};
+use chrono::{DateTime, Utc, Timelike};
+use futures::StreamExt;
+use redis::AsyncCommands;
+use super::market_store::{spawn_redis_feed, MarketStore};
+use super::accounts::{AccountSnapshot, ACCOUNTS_CHANNEL};
+use super::checkpoint::CheckpointStore;
+use super::control::{self, ControlCommand, KillSwitch, CONTROL_CHANNEL};
+use super::funding_rates;
+use super::basis_stats::BasisSlope;
+use super::capital::{self, CapitalConfig, CapitalPlan, CAPITAL_CHANNEL};
+use super::fees::FeeModel;
+use super::portfolio::{PairPosition, PositionBook};
+use super::signals::{SignalKind, TradeSignal, SIGNAL_CHANNEL};
+use super::sizing::calculate_vwap;
+use super::strategy::{close_signal, evaluate_update, PairView, RowKey, StrategyState, TradeTier};
+use super::strategy_config::StrategyConfig;
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyModifiers},
+    execute,
+    terminal::{self, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen},
+    style::{Color, Print, ResetColor, SetForegroundColor},
+    QueueableCommand,
+};
+
+// --- ⚙️ CONFIG ---
+
+const SYMBOLS: [&str; 5] = ["BTC", "ETH", "SOL", "TIA", "ARB"];
+const EXCHANGES: [&str; 3] = ["binance", "hyperliquid", "bybit"];
+const MAX_SYMBOLS: usize = 512;
+const CHECKPOINT_INTERVAL_MS: u64 = 5000;
+/// A restored position is questioned if the checkpoint is older than this.
+const MAX_CHECKPOINT_AGE_SECS: i64 = 15 * 60;
+const RECONCILE_WAIT_MS: u64 = 5000;
+/// How often the quoted funding rates are stored for payout reconciliation.
+const FUNDING_SAMPLE_MS: u64 = 60_000;
+
+/// Checks restored positions against live books and asks the operator about any that look off.
+async fn reconcile(
+    strat: &mut StrategyState,
+    saved_at: DateTime<Utc>,
+    store: &MarketStore,
+    signal_conn: &mut redis::aio::MultiplexedConnection,
+) {
+    if strat.positions.is_empty() { return; }
+    println!("♻️  Restored {} open position(s) from checkpoint saved at {}", strat.positions.len(), saved_at);
+
+    let snapshot = |exchange: &str, symbol: &str| {
+        store.exchange_id(exchange).zip(store.symbol_id(symbol)).and_then(|(ex, sym)| store.snapshot(ex, sym))
+    };
+    let has_books = |p: &PairPosition| snapshot(&p.short_exchange, &p.symbol).is_some() && snapshot(&p.long_exchange, &p.symbol).is_some();
+
+    // Give the ingestors a moment to deliver books for every restored pair
+    let deadline = tokio::time::Instant::now() + Duration::from_millis(RECONCILE_WAIT_MS);
+    while tokio::time::Instant::now() < deadline && !strat.positions.iter().all(has_books) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let now = Utc::now();
+    let positions: Vec<PairPosition> = strat.positions.iter().cloned().collect();
+    for pos in positions {
+        let mut issues = Vec::new();
+        if (now - saved_at).num_seconds() > MAX_CHECKPOINT_AGE_SECS {
+            issues.push(format!("checkpoint is {}m old", (now - saved_at).num_minutes()));
+        }
+        let (short_book, long_book) = (snapshot(&pos.short_exchange, &pos.symbol), snapshot(&pos.long_exchange, &pos.symbol));
+        let current_basis = short_book.clone().zip(long_book.clone())
+            .and_then(|(short, long)| Some((calculate_vwap(&short.bids, pos.size_usd)?, calculate_vwap(&long.asks, pos.size_usd)?)))
+            .map(|(short_px, long_px)| (short_px - long_px) / long_px);
+        match current_basis {
+            None => issues.push("no live book for one of the legs".to_string()),
+            Some(basis) => {
+                if let Some(reason) = pos.exit_reason(basis, None, i64::MAX) {
+                    issues.push(format!("basis now {:.3}% vs entry {:.3}%, {:?} already triggered", basis * 100.0, pos.entry_basis * 100.0, reason));
+                }
+            }
+        }
+        if issues.is_empty() { continue; }
+
+        println!("⚠️  {} looks inconsistent: {}", pos.pair_id, issues.join("; "));
+        println!("    [k]eep / [c]lose (emit CLOSE_LIMIT) / [d]rop (forget) ? ");
+        let answer = tokio::task::spawn_blocking(|| {
+            let mut line = String::new();
+            std::io::stdin().read_line(&mut line).ok();
+            line.trim().to_lowercase()
+        }).await.unwrap_or_default();
+
+        match answer.as_str() {
+            "c" => {
+                let signal = close_signal(&pos, short_book.as_deref(), long_book.as_deref(), "Reconciled", now);
+                let _: () = signal_conn.publish(SIGNAL_CHANNEL, signal.to_json()).await.unwrap_or(());
+                strat.positions.close(&pos.pair_id);
+            }
+            "d" => { strat.positions.close(&pos.pair_id); }
+            _ => {}
+        }
+    }
+}
+
+/// Folds a control command into the scanner. A flatten is carried out by the executors, so the
+/// scanner only forgets its positions.
+fn apply_control(strat: &mut StrategyState, kill: Option<KillSwitch>, command: &ControlCommand, now: DateTime<Utc>) -> Option<KillSwitch> {
+    let was_flat = kill.as_ref().is_some_and(|k| k.flatten);
+    let next = KillSwitch::apply(kill, command, now);
+    if next.as_ref().is_some_and(|k| k.flatten) && !was_flat {
+        let open: Vec<String> = strat.positions.iter().map(|p| p.pair_id.clone()).collect();
+        for pair_id in open { strat.positions.close(&pair_id); }
+    }
+    next
+}
+
+/// Saves the checkpoint, then publishes `signals` plus any closes held back earlier. If the save
+/// fails nothing goes out: entries are forgotten and closes wait in `held` for a save that works.
+async fn persist_and_publish(
+    strat: &mut StrategyState,
+    checkpoints: &mut CheckpointStore,
+    signals: Vec<TradeSignal>,
+    held: &mut Vec<TradeSignal>,
+    conn: &mut redis::aio::MultiplexedConnection,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    if let Err(e) = checkpoints.save(&strat.checkpoint(now)).await {
+        for signal in signals {
+            if signal.kind == SignalKind::CloseLimit { held.push(signal); } else { strat.positions.close(&signal.pair_id); }
+        }
+        return Err(e.to_string());
+    }
+    // Held closes are re-stamped so the executor does not drop them as stale
+    let retried = held.drain(..).map(|signal| TradeSignal { created_at: now, ..signal });
+    for signal in retried.chain(signals) {
+        let _: () = conn.publish(SIGNAL_CHANNEL, signal.to_json()).await.unwrap_or(());
+    }
+    Ok(())
+}
+
+/// Takes in a fresh account snapshot: blocks venues near liquidation and re-plans capital.
+async fn apply_accounts(strat: &mut StrategyState, snapshot: &AccountSnapshot, (config, capital_config): (&StrategyConfig, &CapitalConfig), conn: &mut redis::aio::MultiplexedConnection) -> CapitalPlan {
+    strat.positions.block_venues(&snapshot.blocked);
+    let plan = capital::plan(snapshot, config, capital_config);
+    strat.positions.set_capacity(plan.capacity());
+    if let Ok(raw) = serde_json::to_string(&plan) {
+        let _: () = conn.publish(CAPITAL_CHANNEL, raw).await.unwrap_or(());
+    }
+    plan
+}
+
+/// Recommended transfers and what each venue can still take.
+fn render_capital(out: &mut Stdout, plan: &CapitalPlan) -> std::io::Result<()> {
+    out.queue(Print("\r\n═══ 💱 CAPITAL ═══\r\n"))?;
+    for v in &plan.venues {
+        out.queue(Print(format!("{:<12} | target {:>10.2}$ | surplus {:>+10.2}$ | capacity {:>10.0}$\r\n", v.venue, v.target, v.surplus, v.capacity_usd)))?;
+    }
+    if plan.transfers.is_empty() && plan.unmet.is_empty() { out.queue(Print("balanced, no transfers needed\r\n"))?; }
+    out.queue(SetForegroundColor(Color::Yellow))?;
+    for t in &plan.transfers {
+        out.queue(Print(format!("→ move {:.2}$ {} → {} via {} (fee {:.2}$, ~{}m): {}\r\n", t.amount, t.from, t.to, t.network, t.fee_usd, t.minutes, t.reason)))?;
+    }
+    out.queue(SetForegroundColor(Color::Red))?;
+    for (venue, missing) in &plan.unmet {
+        out.queue(Print(format!("⚠️ {} still {:.2}$ short of target\r\n", venue, missing)))?;
+    }
+    out.queue(ResetColor)?;
+    Ok(())
+}
+
+/// Balances, margin and liquidation distance per venue, as the executor last reported them.
+fn render_accounts(out: &mut Stdout, accounts: &AccountSnapshot, now: DateTime<Utc>) -> std::io::Result<()> {
+    out.queue(Print(format!("\r\n═══ 🏦 ACCOUNTS ({}s ago) ═══\r\n", (now - accounts.ts).num_seconds())))?;
+    for account in &accounts.venues {
+        if let Some(error) = &account.error {
+            out.queue(SetForegroundColor(Color::DarkGrey))?;
+            out.queue(Print(format!("{:<12} | unavailable: {}\r\n", account.venue, error)))?;
+            out.queue(ResetColor)?;
+            continue;
+        }
+        let blocked = accounts.blocked.contains(&account.venue);
+        if blocked { out.queue(SetForegroundColor(Color::Red))?; }
+        out.queue(Print(format!("{:<12} | equity {:>10.2}$ | avail {:>10.2}$ | margin {:>5.1}%{}\r\n",
+            account.venue, account.equity, account.available, account.margin_ratio * 100.0, if blocked { " | ⛔ entries blocked" } else { "" })))?;
+        out.queue(ResetColor)?;
+        for leg in &account.legs {
+            let liquidation = match (leg.liquidation_price, leg.distance) {
+                (Some(price), Some(distance)) => format!("liq {:>10.4} ({:>5.1}% away)", price, distance * 100.0),
+                _ => "liq -".to_string(),
+            };
+            out.queue(Print(format!("   {:<12} {:>+12.4} @ {:>10.4} | {}\r\n", leg.symbol, leg.qty, leg.mark, liquidation)))?;
+        }
+    }
+    Ok(())
+}
+
+/// Status shown above the pair table: kill switch, accounts, capital plan and a failing checkpoint
+/// (error, closes held back).
+type RenderStatus<'a> = (Option<&'a KillSwitch>, Option<&'a AccountSnapshot>, Option<&'a CapitalPlan>, Option<(&'a str, usize)>);
+
+fn render(out: &mut Stdout, store: &MarketStore, views: &HashMap<RowKey, PairView>, positions: &PositionBook, min_slope_t_stat: f64, (kill, accounts, plan, checkpoint): RenderStatus) -> std::io::Result<()> {
+    let now = Utc::now();
+    out.queue(cursor::MoveTo(0, 0))?;
+
+    out.queue(SetForegroundColor(Color::Cyan))?;
+    out.queue(Print(format!("═══ 🕒 {:02}:{:02}:{:02} | TIERED NORMALIZATION | OBI FILTERED ═══\r\n", 
+        now.hour(), now.minute(), now.second())))?;
+    out.queue(ResetColor)?;
+    match kill {
+        Some(kill) => {
+            out.queue(SetForegroundColor(Color::Red))?;
+            out.queue(Print(format!("🛑 KILL SWITCH by {} since {}: {}{} — [r] resume\r\n",
+                kill.source, kill.since.format("%H:%M:%S"), kill.reason, if kill.flatten { " (flattened)" } else { "" })))?;
+            out.queue(ResetColor)?;
+        }
+        None => { out.queue(Print("[k] kill  [K] kill + flatten  [q] quit\r\n"))?; }
+    }
+    if let Some((error, held)) = checkpoint {
+        out.queue(SetForegroundColor(Color::Red))?;
+        out.queue(Print(format!("💾 CHECKPOINT SAVE FAILING: {} — nothing is published; entries dropped, {} close(s) held back\r\n", error, held)))?;
+        out.queue(ResetColor)?;
+    }
+    out.queue(Print(format!("{:<20} | {:>7} | {:>7} | {:>7} | {:>7} | {:>7} | {:>9} | {:>7} | {:>6} | {:>5} | {:>4} | {:>6} | {:>8} | {:>8} | {:>5} | OBI\r\n", "PAIR", "BASIS", "CONV", "FUND", "FEES", "EXP", "MAX@EDGE", "MEAN", "VOL", "Z", "PCT", "HL", "SLP30S", "SLP5M", "NEXT")))?;
+    out.queue(Print(format!("{}\r\n", "─".repeat(187))))?;
+
+    for symbol in &SYMBOLS {
+        let Some(sym_id) = store.symbol_id(symbol) else { continue };
+        for ex_a in 0..store.exchange_count() {
+            for ex_b in (ex_a + 1)..store.exchange_count() {
+                let Some(view) = views.get(&(ex_a, ex_b, sym_id)) else { continue };
+
+                let tier_color = match view.tier {
+                    TradeTier::Sniper => Color::Magenta,
+                    TradeTier::GreatEntry => Color::Green,
+                    TradeTier::Acceptable => Color::Yellow,
+                    _ => Color::DarkGrey,
+                };
+
+                out.queue(SetForegroundColor(tier_color))?;
+                out.queue(Print(format!("{:<20}", view.pair_id)))?;
+                out.queue(ResetColor)?;
+                let pnl = &view.expected;
+                // Mean and volatility in bps; the basis columns are already in percent
+                let (mean, vol, z, pct, hl) = match &view.stats {
+                    Some(s) => (
+                        format!("{:+.1}bp", s.mean * 10_000.0),
+                        format!("{:.1}bp", s.std_dev * 10_000.0),
+                        format!("{:+.2}", s.z_score),
+                        format!("{:.0}%", s.percentile * 100.0),
+                        s.half_life_secs.map(|h| format!("{:.0}s", h)).unwrap_or_else(|| "-".to_string()),
+                    ),
+                    None => ("-".to_string(), "-".to_string(), "-".to_string(), "-".to_string(), "-".to_string()),
+                };
+                // bps/s, starred when the trend is statistically meaningful
+                let fmt_slope = |slope: &Option<BasisSlope>| match slope {
+                    Some(s) => format!("{:+.3}{}", s.bps_per_sec, if s.t_stat().abs() >= min_slope_t_stat { "*" } else { " " }),
+                    None => "-".to_string(),
+                };
+                out.queue(Print(format!(" | {:>6.3}% | {:>6.3}% | {:>6.3}% | {:>6.3}% | {:>6.3}% | {:>8.0}$ | {:>7} | {:>6} | {:>5} | {:>4} | {:>6} | {:>8} | {:>8} | {:>4}m | {:+.2} {}{}\r\n", 
+                    view.current_basis * 100.0, pnl.convergence * 100.0, pnl.funding * 100.0, pnl.fees * 100.0, pnl.total * 100.0,
+                    view.max_size, mean, vol, z, pct, hl, fmt_slope(&view.slope_short), fmt_slope(&view.slope_long), view.ttl / 60, view.short_obi, if view.obi_stable {"STABLE"} else {"..."}, if view.thin {" THIN"} else {""})))?;
+                if let Some(breach) = view.blocked {
+                    out.queue(SetForegroundColor(Color::Red))?;
+                    out.queue(Print(format!("{:<20}   ⛔ entry blocked by {:?} limit\r\n", "", breach)))?;
+                    out.queue(ResetColor)?;
+                }
+            }
+        }
+    }
+    if positions.is_empty() {
+        out.queue(Print("\r\n═══ 📜 MONITOR: SCANNING... ═══\r\n"))?;
+    } else {
+        out.queue(Print(format!("\r\n═══ 📜 MONITOR: {} OPEN ═══\r\n", positions.len())))?;
+        for pos in positions.iter() {
+            out.queue(Print(format!("{:<20} | entry {:>6.3}% | {:>8.0}$ | {:>4}m open\r\n",
+                pos.pair_id, pos.entry_basis * 100.0, pos.size_usd, (now - pos.opened_at).num_minutes())))?;
+        }
+    }
+    if let Some(accounts) = accounts { render_accounts(out, accounts, now)?; }
+    if let Some(plan) = plan { render_capital(out, plan)?; }
+    out.queue(Clear(ClearType::FromCursorDown))?;
+    out.flush()
+}
+
+// --- 🚀 MAIN RUNNER ---
+
+pub async fn run() -> Result<(), Box<dyn std::error::Error>> {
+    let client = redis::Client::open("redis://localhost/")?;
+    let config = StrategyConfig::load();
+    let capital_config = CapitalConfig::load();
+    let mut fees = FeeModel::load();
+    fees.fetch_account_rates(&reqwest::Client::new()).await;
+    let market_data = Arc::new(MarketStore::new(&EXCHANGES, MAX_SYMBOLS));
+    // Every stored book update is announced here so only the affected pairs get re-evaluated
+    let mut update_rx = spawn_redis_feed(Arc::clone(&market_data));
+
+    let mut signal_conn = client.get_multiplexed_async_connection().await?;
+    let mut kill = KillSwitch::load(&mut signal_conn).await;
+    let mut accounts = AccountSnapshot::load(&mut signal_conn, Utc::now()).await;
+    let mut control_sub = client.get_async_pubsub().await?;
+    control_sub.subscribe(&[CONTROL_CHANNEL, ACCOUNTS_CHANNEL]).await?;
+    let mut control_rx = control_sub.into_on_message();
+    let mut strat = StrategyState::new(&config);
+    let mut views: HashMap<RowKey, PairView> = HashMap::new();
+
+    let mut checkpoints = CheckpointStore::from_env(&client).await?;
+    match checkpoints.load().await {
+        Ok(Some(checkpoint)) => {
+            let saved_at = checkpoint.saved_at;
+            strat.restore(checkpoint);
+            reconcile(&mut strat, saved_at, &market_data, &mut signal_conn).await;
+        }
+        Ok(None) => {}
+        Err(e) if std::env::args().any(|arg| arg == "--discard-checkpoint") => {
+            println!("⚠️ [Checkpoint] Discarding unreadable checkpoint: {}", e);
+        }
+        Err(e) => return Err(format!("checkpoint unreadable ({}); fix it, or start with --discard-checkpoint to drop its positions", e).into()),
+    }
+    // Closes waiting for a checkpoint save to succeed, and why the last one failed
+    let mut held_closes = Vec::new();
+    let mut checkpoint_error: Option<String> = None;
+    let mut plan = None;
+    if let Some(snapshot) = &accounts {
+        plan = Some(apply_accounts(&mut strat, snapshot, (&config, &capital_config), &mut signal_conn).await);
+    }
+    let mut checkpoint_timer = interval(Duration::from_millis(CHECKPOINT_INTERVAL_MS));
+    let mut funding_timer = interval(Duration::from_millis(FUNDING_SAMPLE_MS));
+
+    let mut out = stdout();
+    terminal::enable_raw_mode()?; 
+    execute!(out, EnterAlternateScreen, cursor::Hide, Clear(ClearType::All))?;
+
+    // Rendering is throttled on its own clock; evaluation never waits for it
+    let mut render_timer = interval(Duration::from_millis(200));
+    render_timer.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    loop {
+        tokio::select! {
+            Some(first) = update_rx.recv() => {
+                // Coalesce bursts so each touched book is evaluated once
+                let mut touched = HashSet::from([first]);
+                while let Ok(next) = update_rx.try_recv() { touched.insert(next); }
+
+                let mut signals = Vec::new();
+                let now = Utc::now();
+                for update in touched {
+                    for (row, view, signal) in evaluate_update(&market_data, update, &mut strat, (&fees, &config), now) {
+                        views.insert(row, view);
+                        signals.extend(signal);
+                    }
+                }
+
+                // Halted: nothing new goes out, and the strategy forgets the entries it just made
+                if kill.is_some() {
+                    signals.retain(|signal| {
+                        if signal.kind == SignalKind::CloseLimit { return true; }
+                        strat.positions.close(&signal.pair_id);
+                        false
+                    });
+                }
+
+                // Persist before publishing so a crash can never forget a position we announced
+                if !signals.is_empty() {
+                    checkpoint_error = persist_and_publish(&mut strat, &mut checkpoints, signals, &mut held_closes, &mut signal_conn, now).await.err();
+                }
+            }
+            Some(msg) = control_rx.next() => {
+                let payload: String = msg.get_payload().unwrap_or_default();
+                if msg.get_channel_name() == ACCOUNTS_CHANNEL {
+                    if let Some(snapshot) = AccountSnapshot::from_json(&payload) {
+                        plan = Some(apply_accounts(&mut strat, &snapshot, (&config, &capital_config), &mut signal_conn).await);
+                        accounts = Some(snapshot);
+                    }
+                } else if let Some(command) = ControlCommand::from_json(&payload) {
+                    kill = apply_control(&mut strat, kill, &command, Utc::now());
+                }
+            }
+            _ = checkpoint_timer.tick() => {
+                // The basis windows are the bulk of the state, so only this timer writes them
+                let saved = persist_and_publish(&mut strat, &mut checkpoints, Vec::new(), &mut held_closes, &mut signal_conn, Utc::now()).await;
+                let windows = checkpoints.save_windows(&strat.basis_windows).await.map_err(|e| e.to_string());
+                checkpoint_error = saved.and(windows).err();
+                // A silent executor cannot keep venues blocked forever
+                if accounts.as_ref().is_some_and(|snapshot| !snapshot.is_fresh(Utc::now())) {
+                    accounts = None;
+                    plan = None;
+                    strat.positions.block_venues(&[]);
+                    strat.positions.set_capacity(HashMap::new());
+                }
+            }
+            _ = funding_timer.tick() => {
+                // Redis latency must never hold up evaluation
+                let (mut conn, store) = (signal_conn.clone(), Arc::clone(&market_data));
+                tokio::spawn(async move { funding_rates::record(&mut conn, &store, Utc::now()).await });
+            }
+            _ = render_timer.tick() => {
+                if event::poll(Duration::from_millis(0))? {
+                    if let Event::Key(key) = event::read()? {
+                        if key.code == KeyCode::Char('q') || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL)) {
+                            break; 
+                        }
+                        let source = "tui".to_string();
+                        let command = match key.code {
+                            KeyCode::Char('k') => Some(ControlCommand::Kill { flatten: false, reason: "operator".to_string(), source }),
+                            KeyCode::Char('K') => Some(ControlCommand::Kill { flatten: true, reason: "operator".to_string(), source }),
+                            KeyCode::Char('r') => Some(ControlCommand::Resume { source }),
+                            _ => None,
+                        };
+                        // Applied when it comes back on the channel, like everyone else's
+                        if let Some(command) = command { control::send(&mut signal_conn, &command, Utc::now()).await; }
+                    }
+                }
+                let checkpoint = checkpoint_error.as_deref().map(|error| (error, held_closes.len()));
+                render(&mut out, &market_data, &views, &strat.positions, config.entry.min_slope_t_stat, (kill.as_ref(), accounts.as_ref(), plan.as_ref(), checkpoint))?;
+            }
+        }
+    }
+
+    terminal::disable_raw_mode()?;
+    execute!(out, cursor::Show, LeaveAlternateScreen)?;
+    checkpoints.save(&strat.checkpoint(Utc::now())).await?;
+    checkpoints.save_windows(&strat.basis_windows).await?;
+    Ok(())
+}
@@ -0,0 +1,102 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Bumped whenever a consumer would need to change to read `TradeSignal`.
+pub const SIGNAL_SCHEMA_VERSION: u32 = 1;
+pub const SIGNAL_CHANNEL: &str = "trade:signals";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SignalKind { OpenLimit, CloseLimit }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Side { Buy, Sell }
+
+impl Side {
+    pub fn opposite(self) -> Self {
+        match self { Side::Buy => Side::Sell, Side::Sell => Side::Buy }
+    }
+}
+
+/// One order the consumer has to work on one venue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalLeg {
+    pub venue: String,
+    /// Venue-native instrument name (`BTCUSDT`, `BTC`, ...).
+    pub symbol: String,
+    pub side: Side,
+    /// Base-asset quantity; both legs of a pair carry the same quantity.
+    pub size: f64,
+    /// Worst acceptable price. `None` only on emergency closes without a live book.
+    pub limit_price: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeSignal {
+    pub version: u32,
+    /// Unique per signal; consumers dedupe on it.
+    pub id: Uuid,
+    pub kind: SignalKind,
+    pub pair_id: String,
+    pub created_at: DateTime<Utc>,
+    /// Drop the signal if it has not been acted on within this many ms of `created_at`.
+    pub ttl_ms: i64,
+    pub legs: Vec<SignalLeg>,
+    /// Expected net return as a fraction of notional (0 for closes).
+    pub expected_edge: f64,
+    pub reason: String,
+    /// On closes, the ID of the open signal this unwinds.
+    pub opens: Option<Uuid>,
+}
+
+impl TradeSignal {
+    pub fn new(kind: SignalKind, pair_id: &str, now: DateTime<Utc>, ttl_ms: i64, legs: Vec<SignalLeg>) -> Self {
+        Self {
+            version: SIGNAL_SCHEMA_VERSION,
+            id: Uuid::new_v4(),
+            kind,
+            pair_id: pair_id.to_string(),
+            created_at: now,
+            ttl_ms,
+            legs,
+            expected_edge: 0.0,
+            reason: String::new(),
+            opens: None,
+        }
+    }
+
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        self.created_at + Duration::milliseconds(self.ttl_ms)
+    }
+
+    pub fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        now >= self.expires_at()
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// Parses a bus payload, rejecting schema versions this build does not understand.
+    pub fn from_json(raw: &str) -> Option<Self> {
+        serde_json::from_str::<Self>(raw).ok().filter(|s| s.version == SIGNAL_SCHEMA_VERSION)
+    }
+}
+
+/// Maps the scanner's coin name to what each venue calls the USDT perp.
+pub fn venue_symbol(venue: &str, symbol: &str) -> String {
+    match venue {
+        "hyperliquid" => symbol.to_string(),
+        _ => format!("{}USDT", symbol),
+    }
+}
+
+/// Inverse of `venue_symbol`: the scanner's coin name for a venue-native instrument.
+pub fn coin_symbol<'a>(venue: &str, symbol: &'a str) -> &'a str {
+    match venue {
+        "hyperliquid" => symbol,
+        _ => symbol.strip_suffix("USDT").unwrap_or(symbol),
+    }
+}
@@ -0,0 +1,50 @@
+// --- 📏 DEPTH & SIZING ---
+
+/// Total quoted notional on one side of a book.
+pub fn book_depth_usd(levels: &[(f64, f64)]) -> f64 {
+    levels.iter().map(|(price, qty)| price * qty).sum()
+}
+
+/// Average fill price for `target_usd` of notional, or `None` if the book cannot absorb it.
+pub fn calculate_vwap(levels: &[(f64, f64)], target_usd: f64) -> Option<f64> {
+    let mut current_usd = 0.0;
+    let mut current_qty = 0.0;
+    for (price, qty) in levels {
+        let level_value = price * qty;
+        if current_usd + level_value >= target_usd {
+            let needed_usd = target_usd - current_usd;
+            current_qty += needed_usd / price;
+            return Some(target_usd / current_qty);
+        }
+        current_usd += level_value;
+        current_qty += qty;
+    }
+    None
+}
+
+/// Largest notional that can be sold into `short_bids` and bought from `long_asks` while
+/// `edge(short_vwap, long_vwap)` stays at or above `target_edge`. Zero if even the touch misses.
+///
+/// Both VWAPs only get worse as size grows, so the edge is monotonic and a bisection is enough.
+pub fn max_size_at_edge<F>(short_bids: &[(f64, f64)], long_asks: &[(f64, f64)], target_edge: f64, edge: F) -> f64
+where
+    F: Fn(f64, f64) -> f64,
+{
+    // Shave the cap so float noise never pushes the last level out of reach
+    let cap = book_depth_usd(short_bids).min(book_depth_usd(long_asks)) * (1.0 - 1e-9);
+    if cap <= 0.0 { return 0.0; }
+
+    let clears = |size: f64| match (calculate_vwap(short_bids, size), calculate_vwap(long_asks, size)) {
+        (Some(s), Some(l)) => edge(s, l) >= target_edge,
+        _ => false,
+    };
+    if clears(cap) { return cap; }
+    if !clears(cap * 1e-6) { return 0.0; }
+
+    let (mut lo, mut hi) = (cap * 1e-6, cap);
+    for _ in 0..40 {
+        let mid = (lo + hi) / 2.0;
+        if clears(mid) { lo = mid; } else { hi = mid; }
+    }
+    lo
+}
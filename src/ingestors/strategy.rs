@@ -0,0 +1,440 @@
+use std::collections::HashMap;
+use chrono::{DateTime, Utc};
+use super::market_store::{ExchangeId, MarketState, MarketStore, SymbolId};
+use super::checkpoint::Checkpoint;
+use super::basis_stats::{BasisHistory, BasisSlope, BasisStats};
+use super::fees::FeeModel;
+use super::portfolio::{LimitBreach, PairPosition, PositionBook};
+use super::pnl_model::{seconds_to_payout, ExpectedPnl, Leg, PnlModel};
+use super::signals::{venue_symbol, Side, SignalKind, SignalLeg, TradeSignal};
+use super::sizing::{book_depth_usd, calculate_vwap, max_size_at_edge};
+use super::strategy_config::{EntryRules, StrategyConfig};
+
+// --- 📊 TYPES & ENUMS ---
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TradeTier { Noise, Acceptable, GreatEntry, Sniper }
+
+fn get_trade_tier(net_profit: f64, entry: &EntryRules) -> TradeTier {
+    if net_profit < entry.acceptable { TradeTier::Noise }
+    else if net_profit < entry.great { TradeTier::Acceptable }
+    else if net_profit < entry.sniper { TradeTier::GreatEntry }
+    else { TradeTier::Sniper }
+}
+
+/// Latest evaluation of a cross-venue pair, cached for the renderer.
+#[derive(Clone)]
+pub struct PairView {
+    pub pair_id: String,
+    pub tier: TradeTier,
+    pub current_basis: f64,
+    pub expected: ExpectedPnl,
+    pub max_size: f64,
+    pub thin: bool,
+    pub stats: Option<BasisStats>,
+    pub slope_short: Option<BasisSlope>,
+    pub slope_long: Option<BasisSlope>,
+    pub blocked: Option<LimitBreach>,
+    pub ttl: i64,
+    pub short_obi: f64,
+    pub obi_stable: bool,
+}
+
+/// NEW: The Sentinel ensures the OBI "Settles" before we trade.
+pub struct TradeSentinel {
+    first_favorable_obi: Option<i64>, // unix ms, so it survives a restart
+    cooldown_ms: i64,
+}
+
+impl TradeSentinel {
+    fn new(cooldown_ms: i64) -> Self {
+        Self {
+            first_favorable_obi: None,
+            cooldown_ms,
+        }
+    }
+
+    fn check_obi_stability(&mut self, is_favorable: bool, now_ms: i64) -> bool {
+        if !is_favorable {
+            self.first_favorable_obi = None; // Reset timer if OBI dips
+            return false;
+        }
+        match self.first_favorable_obi {
+            Some(start) => now_ms - start >= self.cooldown_ms,
+            None => {
+                self.first_favorable_obi = Some(now_ms);
+                false
+            }
+        }
+    }
+}
+
+// --- 🧠 MATH & LIQUIDITY ENGINE ---
+
+fn calculate_weighted_obi(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> f64 {
+    let mut bid_vol = 0.0;
+    let mut ask_vol = 0.0;
+    for i in 0..5 {
+        let weight = (5 - i) as f64;
+        if let Some(level) = bids.get(i) { bid_vol += level.1 * weight; }
+        if let Some(level) = asks.get(i) { ask_vol += level.1 * weight; }
+    }
+    if bid_vol + ask_vol == 0.0 { return 0.0; }
+    (bid_vol - ask_vol) / (bid_vol + ask_vol)
+}
+
+const CONVERGENCE_RATIO: f64 = 0.80;
+const HOLD_HORIZON_SECS: i64 = 8 * 3600;
+const STATS_WINDOW_MS: i64 = 10 * 60 * 1000;
+const OPEN_SIGNAL_TTL_MS: i64 = 2000;
+const CLOSE_SIGNAL_TTL_MS: i64 = 10_000;
+
+/// Strategy memory carried between market updates.
+pub struct StrategyState {
+    pub positions: PositionBook,
+    pub basis_windows: HashMap<String, BasisHistory>,
+    pub signal_streak: HashMap<String, u32>,
+    /// When each streak last counted a sample; not checkpointed, a restart just counts the next one.
+    streak_sampled_at: HashMap<String, i64>,
+    pub sentinels: HashMap<String, TradeSentinel>,
+    pub entry: EntryRules,
+}
+
+impl StrategyState {
+    pub fn new(config: &StrategyConfig) -> Self {
+        Self {
+            positions: PositionBook::new(config.limits),
+            basis_windows: HashMap::new(),
+            signal_streak: HashMap::new(),
+            streak_sampled_at: HashMap::new(),
+            sentinels: HashMap::new(),
+            entry: config.entry,
+        }
+    }
+
+    /// Everything but the basis windows, which are flushed on their own by the caller.
+    pub fn checkpoint(&self, now: DateTime<Utc>) -> Checkpoint {
+        Checkpoint {
+            saved_at: now,
+            positions: self.positions.iter().cloned().collect(),
+            basis_windows: HashMap::new(),
+            signal_streak: self.signal_streak.clone(),
+            sentinels: self.sentinels.iter()
+                .filter_map(|(id, s)| s.first_favorable_obi.map(|ts| (id.clone(), ts)))
+                .collect(),
+        }
+    }
+
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        for position in checkpoint.positions { self.positions.open(position); }
+        self.basis_windows = checkpoint.basis_windows;
+        self.signal_streak = checkpoint.signal_streak;
+        for (id, ts) in checkpoint.sentinels {
+            let cooldown_ms = self.entry.sentinel_ms;
+            self.sentinels.entry(id).or_insert_with(|| TradeSentinel::new(cooldown_ms)).first_favorable_obi = Some(ts);
+        }
+    }
+
+    /// Extends or resets a pair's streak and returns it. Book updates arrive at whatever rate the
+    /// venues push them, so a trending pair only counts once per `streak_interval_ms`.
+    fn count_streak(&mut self, pair_id: &str, trending: bool, now_ms: i64) -> u32 {
+        let streak = self.signal_streak.entry(pair_id.to_string()).or_insert(0);
+        if !trending {
+            *streak = 0;
+            self.streak_sampled_at.remove(pair_id);
+            return 0;
+        }
+        let last = self.streak_sampled_at.get(pair_id).copied();
+        if last.is_none_or(|t| now_ms - t >= self.entry.streak_interval_ms) {
+            *streak += 1;
+            self.streak_sampled_at.insert(pair_id.to_string(), now_ms);
+        }
+        *streak
+    }
+}
+
+/// Builds the CLOSE_LIMIT for `pos`, pricing each leg off its book when one is available.
+pub fn close_signal(pos: &PairPosition, short: Option<&MarketState>, long: Option<&MarketState>, reason: &str, now: DateTime<Utc>) -> TradeSignal {
+    let legs = vec![
+        SignalLeg {
+            venue: pos.short_exchange.clone(),
+            symbol: venue_symbol(&pos.short_exchange, &pos.symbol),
+            side: Side::Sell.opposite(),
+            size: pos.qty,
+            limit_price: short.and_then(|s| calculate_vwap(&s.asks, pos.size_usd)),
+        },
+        SignalLeg {
+            venue: pos.long_exchange.clone(),
+            symbol: venue_symbol(&pos.long_exchange, &pos.symbol),
+            side: Side::Buy.opposite(),
+            size: pos.qty,
+            limit_price: long.and_then(|l| calculate_vwap(&l.bids, pos.size_usd)),
+        },
+    ];
+    let mut signal = TradeSignal::new(SignalKind::CloseLimit, &pos.pair_id, now, CLOSE_SIGNAL_TTL_MS, legs);
+    signal.reason = reason.to_string();
+    signal.opens = Some(pos.open_signal_id);
+    signal
+}
+
+/// One direction of a pair, priced at the clip and sized against the target edge.
+struct DirectionQuote {
+    expected: ExpectedPnl,
+    max_size: f64,
+    depth: f64,
+}
+
+fn quote_direction(
+    model: &PnlModel,
+    (short_ex, short): (&str, &MarketState),
+    (long_ex, long): (&str, &MarketState),
+    entry: &EntryRules,
+    now: DateTime<Utc>,
+) -> Option<DirectionQuote> {
+    let depth = book_depth_usd(&short.bids).min(book_depth_usd(&long.asks));
+
+    // A book thinner than the clip is priced for what it can actually fill. With nothing on our
+    // side at all the touch it still shows marks it, so an open position can still be exited
+    let (short_px, long_px) = if depth > 0.0 {
+        let size = entry.clip_usd.min(depth * (1.0 - 1e-9));
+        (calculate_vwap(&short.bids, size)?, calculate_vwap(&long.asks, size)?)
+    } else {
+        let touch = |first: &[(f64, f64)], other: &[(f64, f64)]| first.first().or(other.first()).map(|(price, _)| *price);
+        (touch(&short.bids, &short.asks)?, touch(&long.asks, &long.bids)?)
+    };
+    let expected_at = |short_px, long_px| model.expected(
+        Leg { exchange: short_ex, price: short_px, funding: short.funding },
+        Leg { exchange: long_ex, price: long_px, funding: long.funding },
+        now,
+    );
+
+    let expected = expected_at(short_px, long_px);
+    let max_size = if depth <= 0.0 { 0.0 } else { max_size_at_edge(&short.bids, &long.asks, entry.target_edge, |s, l| expected_at(s, l).total) };
+    Some(DirectionQuote { expected, max_size, depth })
+}
+
+/// Evaluates one venue pair for a symbol and returns its view plus any signal to publish.
+pub fn evaluate_pair(
+    symbol: &str,
+    (ex_a, state_a): (&str, &MarketState),
+    (ex_b, state_b): (&str, &MarketState),
+    strat: &mut StrategyState,
+    (fees, config): (&FeeModel, &StrategyConfig),
+    now: DateTime<Utc>,
+) -> Option<(PairView, Option<TradeSignal>)> {
+    // Price both directions; an open position keeps its own direction until it is closed
+    let round_trip_fees = fees.pair_round_trip(ex_a, ex_b);
+    let model = PnlModel { convergence_ratio: CONVERGENCE_RATIO, hold_horizon_secs: HOLD_HORIZON_SECS, round_trip_fees };
+    let clip_usd = config.entry.clip_usd;
+    let a_short = quote_direction(&model, (ex_a, state_a), (ex_b, state_b), &config.entry, now);
+    let b_short = quote_direction(&model, (ex_b, state_b), (ex_a, state_a), &config.entry, now);
+    let a_short_id = format!("{}-{}-{}", ex_a, ex_b, symbol).to_uppercase();
+    let b_short_id = format!("{}-{}-{}", ex_b, ex_a, symbol).to_uppercase();
+
+    // Each direction stands on its own: one without a price does not hide the other
+    let take_a_short = if strat.positions.get(&a_short_id).is_some() { true }
+                       else if strat.positions.get(&b_short_id).is_some() { false }
+                       else {
+                           match (&a_short, &b_short) {
+                               (Some(a), Some(b)) => a.expected.total >= b.expected.total,
+                               (a, _) => a.is_some(),
+                           }
+                       };
+    let (v_short, v_long, quote, pair_id) = if take_a_short {
+        (ex_a, ex_b, a_short?, a_short_id)
+    } else {
+        (ex_b, ex_a, b_short?, b_short_id)
+    };
+
+    let DirectionQuote { expected, max_size, depth } = quote;
+    let current_basis = expected.entry_basis;
+    let net_profit = expected.total;
+    let order_size = max_size.min(clip_usd);
+    let entry = config.entry;
+    let tier = get_trade_tier(net_profit, &entry);
+
+    let (short_state, long_state) = if v_short == ex_a { (state_a, state_b) } else { (state_b, state_a) };
+    let short_obi = calculate_weighted_obi(&short_state.bids, &short_state.asks);
+    let long_obi = calculate_weighted_obi(&long_state.bids, &long_state.asks);
+
+    let ttl = seconds_to_payout(ex_a, state_a.funding, now).min(seconds_to_payout(ex_b, state_b.funding, now));
+
+    // Track History & Slope
+    let window = strat.basis_windows.entry(pair_id.clone()).or_insert_with(|| BasisHistory::new(STATS_WINDOW_MS));
+    window.push(now.timestamp_millis(), current_basis);
+    let stats = window.stats(now.timestamp_millis());
+    let slope_short = window.slope(now.timestamp_millis(), entry.slope_short_ms);
+    let slope_long = window.slope(now.timestamp_millis(), entry.slope_long_ms);
+
+    let trending = slope_short.is_some_and(|s| s.bps_per_sec > 0.0 && s.t_stat() >= entry.min_slope_t_stat);
+    let streak = strat.count_streak(&pair_id, tier != TradeTier::Noise && trending, now.timestamp_millis());
+
+    // Sentinel Check (Stability)
+    let sentinel = strat.sentinels.entry(pair_id.clone()).or_insert_with(|| TradeSentinel::new(entry.sentinel_ms));
+    let is_favorable = short_obi < entry.obi_limit && long_obi > -entry.obi_limit;
+    let obi_stable = sentinel.check_obi_stability(is_favorable, now.timestamp_millis());
+
+    // --- 🧠 STRATEGY ENGINE ---
+    let mut signal = None;
+    let mut blocked = None;
+    if let Some(position) = strat.positions.get_mut(&pair_id) {
+        position.updated_at = now;
+        if let Some(reason) = position.exit_reason(current_basis, stats.map(|s| s.z_score), ttl) {
+            signal = Some(close_signal(position, Some(short_state), Some(long_state), &format!("{:?}", reason), now));
+            strat.positions.close(&pair_id);
+        }
+    } else if tier != TradeTier::Noise {
+        let is_sniper = tier == TradeTier::Sniper || (ttl < entry.sniper_ttl_secs && net_profit > entry.sniper_min_profit);
+
+        // A cold window (not enough history yet) does not block entries
+        let rich = stats.zip(entry.min_zscore).is_none_or(|(s, min)| s.z_score >= min);
+
+        if (tier == TradeTier::GreatEntry || is_sniper) && streak >= entry.min_streak && obi_stable && rich && order_size > 0.0 {
+            blocked = strat.positions.can_open(symbol, v_short, v_long, order_size).err();
+            // Limits are the VWAPs the edge was sized at; same base quantity on both legs keeps it delta-neutral
+            let prices = calculate_vwap(&short_state.bids, order_size).zip(calculate_vwap(&long_state.asks, order_size));
+            if let (None, Some((short_px, long_px))) = (blocked, prices) {
+                let qty = order_size / long_px;
+                let leg = |venue: &str, side, price| SignalLeg {
+                    venue: venue.to_string(),
+                    symbol: venue_symbol(venue, symbol),
+                    side,
+                    size: qty,
+                    limit_price: Some(price),
+                };
+                let mut open = TradeSignal::new(
+                    SignalKind::OpenLimit, &pair_id, now, OPEN_SIGNAL_TTL_MS,
+                    vec![leg(v_short, Side::Sell, short_px), leg(v_long, Side::Buy, long_px)],
+                );
+                open.expected_edge = net_profit;
+                open.reason = format!("{:?}", tier);
+
+                strat.positions.open(PairPosition {
+                    pair_id: pair_id.clone(),
+                    symbol: symbol.to_string(),
+                    short_exchange: v_short.to_string(),
+                    long_exchange: v_long.to_string(),
+                    entry_basis: current_basis,
+                    size_usd: order_size,
+                    qty,
+                    open_signal_id: open.id,
+                    opened_at: now,
+                    updated_at: now,
+                    exit: config.exit,
+                });
+                signal = Some(open);
+            }
+        }
+    }
+
+    let view = PairView { pair_id, tier, current_basis, expected, max_size, thin: depth < clip_usd, stats, slope_short, slope_long, blocked, ttl, short_obi, obi_stable };
+    Some((view, signal))
+}
+
+/// Pair rows are keyed by venue order in the store, independent of which venue is currently short.
+pub type RowKey = (ExchangeId, ExchangeId, SymbolId);
+
+/// Re-evaluates every pair that involves the book just updated at `(ex_id, sym_id)`.
+/// Live and backtest both drive the strategy through here.
+pub fn evaluate_update(
+    store: &MarketStore,
+    (ex_id, sym_id): (ExchangeId, SymbolId),
+    strat: &mut StrategyState,
+    (fees, config): (&FeeModel, &StrategyConfig),
+    now: DateTime<Utc>,
+) -> Vec<(RowKey, PairView, Option<TradeSignal>)> {
+    let mut results = Vec::new();
+    let Some(updated) = store.snapshot(ex_id, sym_id) else { return results };
+    let symbol = store.symbol_name(sym_id);
+    for other_id in (0..store.exchange_count()).filter(|id| *id != ex_id) {
+        let Some(other_state) = store.snapshot(other_id, sym_id) else { continue };
+
+        // Keep the venue order stable so pair rows do not flip around
+        let (a, b) = (ex_id.min(other_id), ex_id.max(other_id));
+        let (state_a, state_b) = if a == ex_id { (&updated, &other_state) } else { (&other_state, &updated) };
+        let leg_a = (store.exchange_name(a), state_a.as_ref());
+        let leg_b = (store.exchange_name(b), state_b.as_ref());
+
+        let Some((view, signal)) = evaluate_pair(&symbol, leg_a, leg_b, strat, (fees, config), now) else { continue };
+        results.push(((a, b, sym_id), view, signal));
+    }
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(bids: &[(f64, f64)], asks: &[(f64, f64)]) -> MarketState {
+        MarketState { bids: bids.to_vec(), asks: asks.to_vec(), funding: 0.0 }
+    }
+
+    fn evaluate(a: &MarketState, b: &MarketState, strat: &mut StrategyState, config: &StrategyConfig) -> Option<(PairView, Option<TradeSignal>)> {
+        evaluate_pair("BTC", ("binance", a), ("bybit", b), strat, (&FeeModel::default(), config), Utc::now())
+    }
+
+    #[test]
+    fn one_unpriceable_direction_does_not_hide_the_other() {
+        let config = StrategyConfig::default();
+        let mut strat = StrategyState::new(&config);
+        // Nobody bids on Binance, so Binance cannot be the short leg; Bybit still can
+        let binance = book(&[], &[(100_000.0, 1.0)]);
+        let bybit = book(&[(100_050.0, 1.0)], &[(100_060.0, 1.0)]);
+        let (view, _) = evaluate(&binance, &bybit, &mut strat, &config).expect("bybit-short direction is priceable");
+        assert_eq!(view.pair_id, "BYBIT-BINANCE-BTC");
+    }
+
+    #[test]
+    fn open_positions_still_exit_without_depth() {
+        let config = StrategyConfig::default();
+        let mut strat = StrategyState::new(&config);
+        let now = Utc::now();
+        strat.positions.open(PairPosition {
+            pair_id: "BINANCE-BYBIT-BTC".to_string(),
+            symbol: "BTC".to_string(),
+            short_exchange: "binance".to_string(),
+            long_exchange: "bybit".to_string(),
+            entry_basis: 0.0,
+            size_usd: 1_000.0,
+            qty: 0.01,
+            open_signal_id: uuid::Uuid::new_v4(),
+            opened_at: now,
+            updated_at: now,
+            exit: config.exit,
+        });
+        // The short leg's bids are gone and the basis has blown out 1% past entry
+        let binance = book(&[], &[(101_000.0, 1.0)]);
+        let bybit = book(&[(99_990.0, 1.0)], &[(100_000.0, 1.0)]);
+        let (_, signal) = evaluate(&binance, &bybit, &mut strat, &config).expect("held direction is marked at the touch");
+        let signal = signal.expect("stop loss fires");
+        assert_eq!(signal.kind, SignalKind::CloseLimit);
+        assert_eq!(signal.reason, "StopLoss");
+        assert!(strat.positions.get("BINANCE-BYBIT-BTC").is_none());
+    }
+
+    #[test]
+    fn books_are_measured_against_the_configured_clip() {
+        let binance = book(&[(100_000.0, 0.008)], &[(100_010.0, 0.008)]);
+        let bybit = book(&[(100_000.0, 0.008)], &[(100_010.0, 0.008)]);
+        let mut config = StrategyConfig::default();
+        let (view, _) = evaluate(&binance, &bybit, &mut StrategyState::new(&config), &config).unwrap();
+        assert!(view.thin, "$800 of depth is thin for a $1000 clip");
+        config.entry.clip_usd = 500.0;
+        let (view, _) = evaluate(&binance, &bybit, &mut StrategyState::new(&config), &config).unwrap();
+        assert!(!view.thin, "$800 of depth covers a $500 clip");
+    }
+
+    #[test]
+    fn streaks_count_time_not_book_updates() {
+        let config = StrategyConfig::default();
+        let mut strat = StrategyState::new(&config);
+        // A burst of updates inside one interval is a single sample
+        for ms in 0..100 { assert_eq!(strat.count_streak("P", true, ms * 5), 1); }
+        assert_eq!(strat.count_streak("P", true, 999), 1);
+        assert_eq!(strat.count_streak("P", true, 1000), 2);
+        assert_eq!(strat.count_streak("P", true, 2500), 3);
+        assert_eq!(strat.count_streak("P", false, 2600), 0);
+        // A broken streak starts over straight away
+        assert_eq!(strat.count_streak("P", true, 2700), 1);
+    }
+}
@@ -0,0 +1,84 @@
+use super::portfolio::{ExitRules, PositionLimits};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_CONFIG_PATH: &str = "config/strategy.json";
+
+/// When a pair is allowed to open. Profit figures are fractions of notional, net of fees.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EntryRules {
+    /// Tier cutoffs: below `acceptable` is noise; `great` and `sniper` may enter.
+    pub acceptable: f64,
+    pub great: f64,
+    pub sniper: f64,
+    /// Consecutive trending samples needed before entering.
+    pub min_streak: u32,
+    /// A trending pair adds at most one sample to its streak per interval, however often its books update.
+    pub streak_interval_ms: i64,
+    /// Basis slope windows. The short one decides trending; both are shown in the scanner.
+    /// Neither reaches further back than the 10-minute stats window.
+    pub slope_short_ms: i64,
+    pub slope_long_ms: i64,
+    /// The short-window trend only feeds the streak when its t-statistic clears this.
+    pub min_slope_t_stat: f64,
+    /// Short-leg OBI must stay below this and long-leg OBI above its negative.
+    pub obi_limit: f64,
+    /// How long the OBI must stay favourable before it counts as settled.
+    pub sentinel_ms: i64,
+    /// Close to a payout, anything above `sniper_min_profit` is treated as a sniper entry.
+    pub sniper_ttl_secs: i64,
+    pub sniper_min_profit: f64,
+    /// Largest notional one entry opens per leg.
+    pub clip_usd: f64,
+    /// Net edge the marginal unit must still clear; entries are sized down until it does.
+    pub target_edge: f64,
+    /// Only enter when the basis is this many standard deviations rich versus its window. Off when unset.
+    pub min_zscore: Option<f64>,
+}
+
+impl Default for EntryRules {
+    fn default() -> Self {
+        Self {
+            acceptable: 0.0002,
+            great: 0.0005,
+            sniper: 0.0015,
+            min_streak: 5,
+            streak_interval_ms: 1000,
+            slope_short_ms: 30 * 1000,
+            slope_long_ms: 5 * 60 * 1000,
+            min_slope_t_stat: 2.0,
+            obi_limit: 0.6,
+            sentinel_ms: 500,
+            sniper_ttl_secs: 600,
+            sniper_min_profit: 0.0003,
+            clip_usd: 1000.0,
+            target_edge: 0.0002,
+            min_zscore: None,
+        }
+    }
+}
+
+/// Strategy knobs that can be changed without a rebuild.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StrategyConfig {
+    pub entry: EntryRules,
+    pub limits: PositionLimits,
+    pub exit: ExitRules,
+}
+
+impl StrategyConfig {
+    /// Reads `STRATEGY_CONFIG` (or `config/strategy.json`); missing fields keep their defaults.
+    pub fn load() -> Self {
+        let path = std::env::var("STRATEGY_CONFIG").unwrap_or_else(|_| DEFAULT_CONFIG_PATH.to_string());
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &str) -> Self {
+        let Ok(raw) = std::fs::read_to_string(path) else { return Self::default() };
+        serde_json::from_str(&raw).unwrap_or_else(|e| {
+            println!("⚠️ [Strategy] Ignoring {}: {}", path, e);
+            Self::default()
+        })
+    }
+}
@@ -0,0 +1,3 @@
+pub mod ingestors;
+pub mod execution;
+pub mod backtest;
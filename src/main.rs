@@ -1,4 +1,94 @@
-mod ingestors;
+use scanner_rust::execution::{self, binance_client::{BinanceCredentials, BinanceFutures}, bybit_client::{BybitCredentials, BybitLinear}, hyperliquid_client::{HyperliquidCredentials, HyperliquidExchange}, executor::Clients, exchange::ExchangeClient, mock_exchange::{MockExchange, MockFillMode}, paper::PaperConfig};
+use scanner_rust::ingestors::fees::FeeModel;
+use scanner_rust::backtest;
+use scanner_rust::ingestors;
+use std::sync::Arc;
+
+const VENUES: [&str; 3] = ["binance", "hyperliquid", "bybit"];
+
+/// `scanner-rust executor [--live]`: consume trade:signals against mock venues, or the real
+/// clients for every venue that has credentials (signals touching any other venue are refused).
+async fn run_executor() {
+    let live = std::env::args().any(|arg| arg == "--live");
+    let clients: Clients = if live {
+        let mut clients = Clients::new();
+        if let Some(creds) = BinanceCredentials::from_env() {
+            let binance = Arc::new(BinanceFutures::new(creds));
+            match binance.connect().await {
+                Ok(()) => { clients.insert("binance".to_string(), binance as Arc<dyn ExchangeClient>); }
+                Err(e) => println!("❌ Binance client failed to start: {}", e),
+            }
+        }
+        if let Some(creds) = BybitCredentials::from_env() {
+            let bybit = Arc::new(BybitLinear::new(creds));
+            match bybit.connect().await {
+                Ok(()) => { clients.insert("bybit".to_string(), bybit as Arc<dyn ExchangeClient>); }
+                Err(e) => println!("❌ Bybit client failed to start: {}", e),
+            }
+        }
+        if let Some(creds) = HyperliquidCredentials::from_env() {
+            match HyperliquidExchange::new(creds) {
+                Ok(hyperliquid) => match hyperliquid.connect().await {
+                    Ok(()) => { clients.insert("hyperliquid".to_string(), Arc::new(hyperliquid) as Arc<dyn ExchangeClient>); }
+                    Err(e) => println!("❌ Hyperliquid client failed to start: {}", e),
+                },
+                Err(e) => println!("❌ Hyperliquid agent key: {}", e),
+            }
+        }
+        println!("💸 Executor running LIVE on {:?}", clients.keys().collect::<Vec<_>>());
+        clients
+    } else {
+        println!("🧪 Executor running against mock venues");
+        VENUES.iter()
+            .map(|v| (v.to_string(), Arc::new(MockExchange::new(v, MockFillMode::Immediate)) as Arc<dyn ExchangeClient>))
+            .collect()
+    };
+    if let Err(e) = execution::executor::run(clients).await {
+        println!("❌ Executor exited: {}", e);
+    }
+}
+
+/// `scanner-rust paper`: consume trade:signals against venues simulated on the live books.
+async fn run_paper() {
+    if let Err(e) = execution::paper::run(&VENUES, FeeModel::load(), PaperConfig::default()).await {
+        println!("❌ Paper trader exited: {}", e);
+    }
+}
+
+/// `scanner-rust record <file>` / `backtest <file> [report.json]` / `sweep <file> [best.json]`.
+async fn run_backtest_tools(command: &str) {
+    let path = std::env::args().nth(2).unwrap_or_else(|| "market_data.tsv".to_string());
+    let result = match command {
+        "record" => backtest::recorder::record(&path).await,
+        "sweep" => {
+            let out = std::env::args().nth(3).unwrap_or_else(|| "best_strategy.json".to_string());
+            backtest::sweep::run_file(&path, &out, &VENUES).await
+        }
+        _ => {
+            let out = std::env::args().nth(3).unwrap_or_else(|| "backtest_report.json".to_string());
+            backtest::engine::run_file(&path, &out, &VENUES).await
+        }
+    };
+    if let Err(e) = result {
+        println!("❌ {} failed: {}", command, e);
+    }
+}
+
+/// `scanner-rust ledger <file> [daily.csv]`: daily PnL attribution from a persisted ledger.
+fn run_ledger_report() {
+    let path = std::env::args().nth(2).unwrap_or_else(|| "ledger.jsonl".to_string());
+    let out = std::env::args().nth(3);
+    if let Err(e) = execution::ledger::run_report(&path, out.as_deref()) {
+        println!("❌ ledger failed: {}", e);
+    }
+}
+
+/// `scanner-rust rebalance`: proposed transfers between venues from the latest balances.
+async fn run_rebalance_report() {
+    if let Err(e) = ingestors::capital::run_report().await {
+        println!("❌ rebalance failed: {}", e);
+    }
+}
 
 #[tokio::main]
 async fn main() {
@@ -7,6 +97,15 @@ async fn main() {
         .install_default()
         .expect("Failed to install rustls crypto provider");
 
+    match std::env::args().nth(1).as_deref() {
+        Some("executor") => return run_executor().await,
+        Some("paper") => return run_paper().await,
+        Some("ledger") => return run_ledger_report(),
+        Some("rebalance") => return run_rebalance_report().await,
+        Some(command @ ("record" | "backtest" | "sweep")) => return run_backtest_tools(command).await,
+        _ => {}
+    }
+
     println!("🚀 Starting Centralized Arbitrage Node...");
 
     // Master Watchlist
@@ -37,7 +136,9 @@ async fn main() {
 
     // --- 5. Run Scanner (Foreground) ---
     // The matrix logic now lives inside this function
-    ingestors::scanner::run().await;
+    if let Err(e) = ingestors::scanner::run().await {
+        println!("❌ Scanner exited: {}", e);
+    }
 
     // Join handles if scanner ever exits
     let _ = tokio::join!(binance, bybit, hyperliquid);